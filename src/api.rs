@@ -0,0 +1,566 @@
+use crate::config::config;
+use crate::contest::{Contest, ContestResponse, Phase};
+use crate::local::{log_error, log_info, log_warn, write_atomically};
+use crate::paths::last_fetch_path;
+use crate::source::{self, ContestSource};
+
+use reqwest::blocking::{Client, Response};
+use reqwest::header::{AUTHORIZATION, ETAG, IF_NONE_MATCH};
+use reqwest::StatusCode;
+use serde::{Serialize, Deserialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io::Read;
+
+/// Contests of the last successful fetch saved in last_fetch.json.
+#[derive(Serialize, Deserialize, Debug)]
+struct LastFetch {
+    /// Time of the fetch in seconds (Unix epoch).
+    fetched_at: i64,
+    /// Contests of all sources that were not finished at that time.
+    contests: Vec<Contest>,
+    /// ETag and hash of the last response of each source.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    validators: BTreeMap<String, Validator>,
+}
+
+/// What identifies the last response of a source.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+struct Validator {
+    /// ETag of the response, sent as If-None-Match on the next fetch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    etag: Option<String>,
+    /// Hash of the response body, to notice an unchanged contest list
+    /// if the source ignores If-None-Match.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    hash: Option<u64>,
+}
+
+/// Outcome of requesting the contest list of a source.
+enum Fetched {
+    /// Same contest list as in last_fetch.json.
+    Unchanged,
+    /// New contests with the ETag and hash of the response.
+    Changed(Vec<Contest>, Validator),
+}
+
+/// HTTP client for all requests to Codeforces,
+/// sending the User-Agent from the config.
+pub struct CodeforcesClient {
+    client: Client,
+}
+
+impl CodeforcesClient {
+    /// Builds a client sending `user_agent` with every request.
+    ///
+    /// Terminates and logs errors if the client can not be built.
+    pub fn new(user_agent: &str) -> Self {
+        match Client::builder().user_agent(user_agent).build() {
+            Ok(client) => CodeforcesClient { client },
+            Err(e) => {
+                log_error(&format!("Could not create HTTP client. {}", e));
+                std::process::exit(1);
+            }
+        }
+    }
+
+    /// Builds a client with the User-Agent from the config.
+    pub fn from_config() -> Self {
+        CodeforcesClient::new(&config().user_agent)
+    }
+
+    /// Sends a GET request to the url.
+    pub fn get(&self, url: &str) -> reqwest::Result<Response> {
+        self.client.get(url).send()
+    }
+
+    /// Sends a GET request to the url with the Authorization header.
+    pub fn get_authorized(&self, url: &str, authorization: &str) -> reqwest::Result<Response> {
+        self.client.get(url).header(AUTHORIZATION, authorization).send()
+    }
+
+    /// Sends a POST request with the JSON body to the url.
+    pub fn post_json(&self, url: &str, body: &serde_json::Value) -> reqwest::Result<Response> {
+        self.client.post(url).json(body).send()
+    }
+
+    /// Sends a GET request to the url, asking for 304 Not Modified
+    /// if the resource still has the given ETag.
+    pub fn get_if_none_match(&self, url: &str, etag: Option<&str>) -> reqwest::Result<Response> {
+        let request = self.client.get(url);
+
+        match etag {
+            Some(etag) => request.header(IF_NONE_MATCH, etag).send(),
+            None => request.send(),
+        }
+    }
+}
+
+/// Retrieves upcoming contests 
+/// using Codeforces's API as a HashSet.
+///
+/// Reuses last_fetch.json instead if it is younger than fetch_ttl_seconds
+/// in the config, unless force is set. Only saves the fetched contests
+/// to last_fetch.json if save is set, previews like --diff leave it alone.
+///
+/// Terminates and logs errors if it fails to retrieve the data 
+/// or fails to deserialize the JSON.
+pub fn fetch_current_upcoming_contests(force: bool, save: bool) -> HashSet<Contest> {
+    tracked(fetch_contests(force, save))
+}
+
+/// Retrieves all contests of every source in the config, or the ones saved
+/// in last_fetch.json if it is younger than fetch_ttl_seconds, unless force is set.
+pub fn fetch_contests(force: bool, save: bool) -> Vec<Contest> {
+    fetch_contests_from(&source::configured(), force, save)
+}
+
+/// Retrieves and merges the contests of the sources, or the ones saved
+/// in last_fetch.json if it is younger than fetch_ttl_seconds, unless force is set.
+///
+/// The sources need not be the configured ones, e.g. a fake one.
+///
+/// The saved contests of a source are reused as well if its response is
+/// 304 Not Modified or has the same hash as the saved one, without
+/// deserializing it again. A source that fails is logged and its saved
+/// contests are used instead, last_fetch.json is only updated if all succeed
+/// and save is set.
+///
+/// Terminates and logs errors if no source can be fetched.
+pub fn fetch_contests_from(sources: &[Box<dyn ContestSource>], force: bool, save: bool) -> Vec<Contest> {
+    let last_fetch = load_last_fetch();
+
+    if let Some(last_fetch) = &last_fetch {
+        let age = chrono::Utc::now().timestamp() - last_fetch.fetched_at;
+        if !force && is_fresh(age, config().fetch_ttl_seconds) {
+            log_info(&format!("Reusing contests fetched {} seconds ago.", age));
+            return last_fetch.contests.clone();
+        }
+    }
+
+    let saved_contests = |source: &str| -> Vec<Contest> {
+        last_fetch.iter()
+            .flat_map(|last_fetch| last_fetch.contests.iter())
+            .filter(|contest| contest.source_url() == source)
+            .cloned()
+            .collect()
+    };
+
+    let mut contests = Vec::new();
+    let mut validators = BTreeMap::new();
+    let mut failed = 0;
+
+    for source in sources {
+        let source_url = source.url();
+        let saved = last_fetch.as_ref().and_then(|last_fetch| last_fetch.validators.get(source_url));
+
+        match fetch_source(source.as_ref(), saved).map(|fetched| match fetched {
+            Fetched::Changed(fetched, validator) => (Some(fetched), validator),
+            Fetched::Unchanged => (None, saved.cloned().unwrap_or_default()),
+        }) {
+            Ok((Some(fetched), validator)) => {
+                contests.extend(fetched.into_iter().map(|contest| contest.with_source(source_url)));
+                validators.insert(source_url.to_string(), validator);
+            }
+            Ok((None, validator)) => {
+                log_info(&format!("Contest list of {} unchanged since the last fetch.", source_url));
+                contests.extend(saved_contests(source_url));
+                validators.insert(source_url.to_string(), validator);
+            }
+            Err(e) => {
+                log_error(&format!("Could not fetch contests from {}, using the last fetched ones. {}", source_url, e));
+                contests.extend(saved_contests(source_url));
+                failed += 1;
+            }
+        }
+    }
+
+    if failed == sources.len() {
+        log_error("Could not retrieve any online contest list.");
+        std::process::exit(1);
+    }
+
+    if failed == 0 && save {
+        save_last_fetch(&contests, validators);
+    }
+    contests
+}
+
+/// Requests the contest list from the source, sending the saved ETag.
+///
+/// Returns Fetched::Unchanged on 304 Not Modified or if the body
+/// hashes to the saved hash, the parsed contests otherwise.
+fn fetch_source(source: &dyn ContestSource, saved: Option<&Validator>) -> Result<Fetched, String> {
+    let saved_etag = saved.and_then(|saved| saved.etag.as_deref());
+
+    let response: Response = source.request(&CodeforcesClient::from_config(), saved_etag)
+        .map_err(|e| format!("Could not retrieve online contest list. {}", e))?;
+
+    if response.status() == StatusCode::NOT_MODIFIED && saved.is_some() {
+        return Ok(Fetched::Unchanged);
+    }
+
+    let etag = response.headers()
+        .get(ETAG)
+        .and_then(|etag| etag.to_str().ok())
+        .map(str::to_string);
+
+    let body = response.text().map_err(|e| format!("Could not retrieve online contest list. {}", e))?;
+
+    let hash = content_hash(&body);
+    if saved.and_then(|saved| saved.hash) == Some(hash) {
+        return Ok(Fetched::Unchanged);
+    }
+
+    Ok(Fetched::Changed(source.parse(&body)?, Validator { etag, hash: Some(hash) }))
+}
+
+/// Hashes a response body to compare it with the last one.
+///
+/// The hash may change between Rust versions, which only costs
+/// one extra deserialization.
+fn content_hash(body: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    body.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Reads a Codeforces API response (e.g. piped from curl) instead of
+/// fetching it and returns the upcoming contests as a HashSet.
+///
+/// Terminates and logs errors if the input is empty, can not be read
+/// or fails to deserialize.
+pub fn read_upcoming_contests(mut reader: impl Read) -> HashSet<Contest> {
+    let mut input = String::new();
+
+    if let Err(e) = reader.read_to_string(&mut input) {
+        log_error(&format!("Could not read contest JSON from stdin. {}", e));
+        std::process::exit(1);
+    }
+
+    if input.trim().is_empty() {
+        log_error("No contest JSON on stdin, expected a contest.list response.");
+        std::process::exit(1);
+    }
+
+    let response: ContestResponse = match serde_json::from_str(&input) {
+        Ok(response) => response,
+        Err(e) => {
+            log_error(&format!("Could not parse contest JSON from stdin. {}", e));
+            std::process::exit(1);
+        }
+    };
+
+    tracked(checked_result(response))
+}
+
+/// Returns the contests of a response.
+///
+/// Terminates and logs the comment if the response status is not OK.
+fn checked_result(response: ContestResponse) -> Vec<Contest> {
+    source_result(response).unwrap_or_else(|e| {
+        log_error(&e);
+        std::process::exit(1);
+    })
+}
+
+/// Returns the contests of a response, or the comment
+/// if the response status is not OK.
+pub fn source_result(response: ContestResponse) -> Result<Vec<Contest>, String> {
+    if response.status != "OK" {
+        let comment = response.comment.unwrap_or_else(|| "No comment.".to_string());
+        return Err(format!("Codeforces response status FAILED. Comment: {}.", comment));
+    }
+
+    Ok(response.result)
+}
+
+/// Returns the upcoming contests.
+///
+/// Upcoming means Phase::Before, or Phase::Coding as well
+/// if include_running is set in the config.
+pub fn tracked(contests: Vec<Contest>) -> HashSet<Contest> {
+    dedup_by_id(contests)
+        .into_iter()
+        .filter(|contest| contest.phase.is_tracked(config().include_running))
+        .collect::<HashSet<Contest>>()
+}
+
+/// Removes contests with a source and id seen before, so collecting into
+/// a HashSet does not drop one of them arbitrarily.
+///
+/// Of two contests with the same id, the one in Phase::Before is kept,
+/// otherwise the first one. Duplicates are logged at WARN.
+fn dedup_by_id(contests: Vec<Contest>) -> Vec<Contest> {
+    let mut kept: Vec<Contest> = Vec::with_capacity(contests.len());
+    let mut index_of: HashMap<(Option<String>, usize), usize> = HashMap::new();
+
+    for contest in contests {
+        let key = (contest.source.clone(), contest.id);
+        match index_of.get(&key) {
+            None => {
+                index_of.insert(key, kept.len());
+                kept.push(contest);
+            }
+            Some(&index) => {
+                log_warn(&format!("Duplicate contest id {} in response: {} and {}",
+                    contest.id, kept[index].name, contest.name));
+                if kept[index].phase != Phase::Before && contest.phase == Phase::Before {
+                    kept[index] = contest;
+                }
+            }
+        }
+    }
+
+    kept
+}
+
+/// Returns the content of last_fetch.json, None if it is missing or unreadable.
+fn load_last_fetch() -> Option<LastFetch> {
+    let path = last_fetch_path();
+
+    if !path.exists() { return None }
+
+    let last_fetch: LastFetch = match fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|contents| serde_json::from_str(&contents).map_err(|e| e.to_string())) {
+        Ok(last_fetch) => last_fetch,
+        Err(e) => {
+            log_warn(&format!("Ignoring unreadable last fetch file. {}", e));
+            return None;
+        }
+    };
+
+    Some(last_fetch)
+}
+
+/// Returns true if a fetch age seconds ago is still within the ttl.
+/// Fetches from the future (e.g. after changing the clock) are never fresh,
+/// nothing is fresh with a ttl of 0.
+fn is_fresh(age: i64, ttl_seconds: u64) -> bool {
+    age >= 0 && (age as u64) < ttl_seconds
+}
+
+/// Saves the contests that are not finished to last_fetch.json,
+/// together with the ETag and hash of the response of each source.
+///
+/// Failing to save is logged, but only means the next run fetches again.
+fn save_last_fetch(contests: &[Contest], validators: BTreeMap<String, Validator>) {
+    let last_fetch = LastFetch {
+        fetched_at: chrono::Utc::now().timestamp(),
+        contests: contests
+            .iter()
+            .filter(|contest| contest.phase.is_tracked(true))
+            .cloned()
+            .collect(),
+        validators,
+    };
+
+    let result = serde_json::to_string(&last_fetch)
+        .map_err(std::io::Error::from)
+        .and_then(|json| write_atomically(&last_fetch_path(), &json));
+
+    if let Err(e) = result {
+        log_error(&format!("Failed to save last fetch file. {}", e));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RESPONSE: &str = r#"{
+        "status": "OK",
+        "result": [
+            {"id": 2101, "name": "Codeforces Round 1021 (Div. 2)", "type": "CF", "phase": "BEFORE",
+             "frozen": false, "durationSeconds": 7200, "startTimeSeconds": 1745850900, "relativeTimeSeconds": -86400},
+            {"id": 2100, "name": "Codeforces Round 1020 (Div. 3)", "type": "ICPC", "phase": "CODING",
+             "frozen": false, "durationSeconds": 8100, "startTimeSeconds": 1745591700, "relativeTimeSeconds": 600},
+            {"id": 2099, "name": "Codeforces Round 1019 (Div. 1)", "type": "CF", "phase": "FINISHED",
+             "frozen": false, "durationSeconds": 7200, "startTimeSeconds": 1745332500, "relativeTimeSeconds": 259800}
+        ]
+    }"#;
+
+    fn ids(contests: &HashSet<Contest>) -> Vec<usize> {
+        let mut ids: Vec<usize> = contests.iter().map(|contest| contest.id).collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    #[test]
+    fn reads_upcoming_contests_from_reader() {
+        let contests = read_upcoming_contests(RESPONSE.as_bytes());
+
+        assert_eq!(ids(&contests), [2101]);
+        let contest = contests.iter().next().unwrap();
+        assert_eq!(contest.name, "Codeforces Round 1021 (Div. 2)");
+        assert_eq!(contest.start_time_seconds, Some(1745850900));
+        assert_eq!(contest.duration_seconds, Some(7200));
+    }
+
+    #[test]
+    fn unknown_phase_is_not_tracked() {
+        let response = r#"{"status": "OK", "result": [
+            {"id": 2101, "name": "Codeforces Round 1021 (Div. 2)", "phase": "BEFORE"},
+            {"id": 2102, "name": "Codeforces Round 1022 (Div. 2)", "phase": "POSTPONED"}
+        ]}"#;
+
+        let contests = read_upcoming_contests(response.as_bytes());
+
+        assert_eq!(ids(&contests), [2101]);
+        assert_eq!(serde_json::from_str::<Phase>(r#""POSTPONED""#).unwrap(), Phase::Unknown);
+        assert_eq!(serde_json::from_str::<Phase>(r#""PENDING_SYSTEM_TEST""#).unwrap(), Phase::PendingSystemTest);
+    }
+
+    #[test]
+    fn duplicate_ids_keep_the_upcoming_contest() {
+        let response = r#"{"status": "OK", "result": [
+            {"id": 2101, "name": "Old entry", "phase": "FINISHED"},
+            {"id": 2101, "name": "Codeforces Round 1021 (Div. 2)", "phase": "BEFORE", "startTimeSeconds": 1745850900},
+            {"id": 2102, "name": "First", "phase": "BEFORE"},
+            {"id": 2102, "name": "Second", "phase": "BEFORE"}
+        ]}"#;
+
+        let contests = read_upcoming_contests(response.as_bytes());
+
+        assert_eq!(ids(&contests), [2101, 2102]);
+        let name = |id| contests.iter().find(|contest| contest.id == id).map(|contest| contest.name.as_str());
+        assert_eq!(name(2101), Some("Codeforces Round 1021 (Div. 2)"));
+        assert_eq!(name(2102), Some("First"));
+    }
+
+    /// Answers a single request on a local port with response and returns
+    /// the URL to request and the request as received.
+    fn serve_once(response: &'static str) -> (String, std::thread::JoinHandle<String>) {
+        use std::io::Write;
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/api/contest.list", listener.local_addr().unwrap());
+
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut buffer = [0; 1024];
+            while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+                let read = stream.read(&mut buffer).unwrap();
+                if read == 0 { break }
+                request.extend_from_slice(&buffer[..read]);
+            }
+            stream.write_all(response.as_bytes()).unwrap();
+            String::from_utf8_lossy(&request).into_owned()
+        });
+
+        (url, server)
+    }
+
+    #[test]
+    fn sends_the_user_agent() {
+        let (url, server) = serve_once("HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n");
+
+        let response = CodeforcesClient::new("codeforces-reminder-test/1.0 (me@example.org)").get(&url).unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let request = server.join().unwrap().to_lowercase();
+        assert!(request.contains("\r\nuser-agent: codeforces-reminder-test/1.0 (me@example.org)\r\n"), "{}", request);
+    }
+
+    #[test]
+    fn reuses_fetch_within_ttl() {
+        assert!(is_fresh(0, 900));
+        assert!(is_fresh(899, 900));
+    }
+
+    #[test]
+    fn refetches_after_ttl() {
+        assert!(!is_fresh(900, 900));
+        assert!(!is_fresh(3600, 900));
+        assert!(!is_fresh(0, 0), "a ttl of 0 fetches on every run");
+        assert!(!is_fresh(-5, 900), "fetches from the future are never fresh");
+    }
+
+    #[test]
+    fn failed_response_returns_comment() {
+        let response: ContestResponse = serde_json::from_str(r#"{"status": "FAILED", "comment": "Call limit exceeded", "result": []}"#).unwrap();
+
+        assert_eq!(source_result(response).unwrap_err(), "Codeforces response status FAILED. Comment: Call limit exceeded.");
+    }
+
+    const UNCHANGED_BODY: &str = r#"{"status": "OK", "result": [{"id": 2101, "name": "Codeforces Round 1021 (Div. 2)", "phase": "BEFORE"}]}"#;
+
+    fn api(url: &str) -> source::CodeforcesApi {
+        source::CodeforcesApi::new(url.trim_end_matches("/contest.list"))
+    }
+
+    #[test]
+    fn not_modified_keeps_the_saved_contests() {
+        let (url, server) = serve_once("HTTP/1.1 304 Not Modified\r\nETag: \"v1\"\r\nConnection: close\r\n\r\n");
+        let saved = Validator { etag: Some("\"v1\"".to_string()), hash: None };
+
+        let fetched = fetch_source(&api(&url), Some(&saved)).unwrap();
+
+        assert!(matches!(fetched, Fetched::Unchanged));
+        let request = server.join().unwrap().to_lowercase();
+        assert!(request.contains("\r\nif-none-match: \"v1\"\r\n"), "{}", request);
+    }
+
+    #[test]
+    fn same_hash_keeps_the_saved_contests() {
+        let (url, server) = serve_once(concat!(
+            "HTTP/1.1 200 OK\r\nContent-Length: 103\r\nConnection: close\r\n\r\n",
+            r#"{"status": "OK", "result": [{"id": 2101, "name": "Codeforces Round 1021 (Div. 2)", "phase": "BEFORE"}]}"#,
+        ));
+        let saved = Validator { etag: None, hash: Some(content_hash(UNCHANGED_BODY)) };
+
+        let fetched = fetch_source(&api(&url), Some(&saved)).unwrap();
+
+        assert!(matches!(fetched, Fetched::Unchanged));
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn changed_body_is_parsed() {
+        let (url, server) = serve_once(concat!(
+            "HTTP/1.1 200 OK\r\nETag: \"v2\"\r\nContent-Length: 103\r\nConnection: close\r\n\r\n",
+            r#"{"status": "OK", "result": [{"id": 2101, "name": "Codeforces Round 1021 (Div. 2)", "phase": "BEFORE"}]}"#,
+        ));
+        let saved = Validator { etag: Some("\"v1\"".to_string()), hash: Some(content_hash("{}")) };
+
+        let Fetched::Changed(contests, validator) = fetch_source(&api(&url), Some(&saved)).unwrap() else {
+            panic!("changed body was not parsed");
+        };
+
+        assert_eq!(contests.iter().map(|contest| contest.id).collect::<Vec<_>>(), [2101]);
+        assert_eq!(validator.etag.as_deref(), Some("\"v2\""));
+        assert_eq!(validator.hash, Some(content_hash(UNCHANGED_BODY)));
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn merges_sources_with_overlapping_ids() {
+        let response = concat!(
+            "HTTP/1.1 200 OK\r\nContent-Length: 103\r\nConnection: close\r\n\r\n",
+            r#"{"status": "OK", "result": [{"id": 2101, "name": "Codeforces Round 1021 (Div. 2)", "phase": "BEFORE"}]}"#,
+        );
+        let (first, first_server) = serve_once(response);
+        let (second, second_server) = serve_once(response);
+        let closed = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        let sources: Vec<Box<dyn ContestSource>> = vec![
+            Box::new(api(&first)),
+            Box::new(api(&format!("http://{}/api/contest.list", closed))),
+            Box::new(api(&second)),
+        ];
+
+        let contests = fetch_contests_from(&sources, true, false);
+
+        first_server.join().unwrap();
+        second_server.join().unwrap();
+        let mut merged: Vec<(usize, &str)> = contests.iter().map(|contest| (contest.id, contest.source_url())).collect();
+        merged.sort_unstable();
+        let mut expected = [(2101, sources[0].url()), (2101, sources[2].url())];
+        expected.sort_unstable();
+        assert_eq!(merged, expected);
+        assert_eq!(tracked(contests).len(), 2);
+    }
+}
@@ -0,0 +1,84 @@
+use crate::contest::Contest;
+use crate::local::{log_error, write_atomically};
+use crate::paths::archive_path;
+
+use std::fs;
+
+/// Adds finished contests to archive.json, replacing archived
+/// contests with the same id, so no contest is archived twice.
+///
+/// Failing to read or save the archive is logged, but never aborts the run.
+/// An unreadable archive is left untouched instead of being overwritten.
+pub fn archive(finished: &[Contest]) {
+    if finished.is_empty() { return }
+
+    let path = archive_path();
+
+    let mut archived = match load_archive() {
+        Ok(archived) => archived,
+        Err(e) => {
+            log_error(&format!("Failed to read archive file, not archiving. {}", e));
+            return;
+        }
+    };
+
+    merge(&mut archived, finished);
+
+    let result = serde_json::to_string_pretty(&archived)
+        .map_err(std::io::Error::from)
+        .and_then(|json| write_atomically(&path, &json));
+
+    if let Err(e) = result {
+        log_error(&format!("Failed to save archive file. {}", e));
+    }
+}
+
+/// Returns the contests in archive.json, none if it does not exist.
+pub fn load_archive() -> Result<Vec<Contest>, String> {
+    let path = archive_path();
+
+    if !path.exists() { return Ok(Vec::new()) }
+
+    fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|contents| serde_json::from_str(&contents).map_err(|e| e.to_string()))
+}
+
+/// Appends the finished contests to archived, replacing
+/// the archived version of contests with the same source and id.
+fn merge(archived: &mut Vec<Contest>, finished: &[Contest]) {
+    for contest in finished {
+        match archived.iter_mut().find(|archived| *archived == contest) {
+            Some(archived) => *archived = contest.clone(),
+            None => archived.push(contest.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contest::tests::contest;
+
+    #[test]
+    fn archives_each_contest_once_across_runs() {
+        let mut archived = Vec::new();
+
+        merge(&mut archived, &[contest(2100, "Round 1020", 0), contest(2101, "Round 1021", 0)]);
+        let json = serde_json::to_string_pretty(&archived).unwrap();
+        let mut archived: Vec<Contest> = serde_json::from_str(&json).unwrap();
+        merge(&mut archived, &[contest(2101, "Round 1021 (renamed)", 0), contest(2102, "Round 1022", 0)]);
+
+        let archived: Vec<(usize, &str)> = archived.iter().map(|contest| (contest.id, contest.name.as_str())).collect();
+        assert_eq!(archived, [(2100, "Round 1020"), (2101, "Round 1021 (renamed)"), (2102, "Round 1022")]);
+    }
+
+    #[test]
+    fn same_id_of_other_source_is_archived_separately() {
+        let mut archived = vec![contest(400, "Codeforces Round 400", 0)];
+
+        merge(&mut archived, &[contest(400, "abc400", 0).with_source("https://atcoder.jp")]);
+
+        assert_eq!(archived.len(), 2);
+    }
+}
@@ -0,0 +1,159 @@
+use crate::api::CodeforcesClient;
+use crate::contest::{slug_id, Contest, Phase};
+use crate::source::ContestSource;
+
+use chrono::DateTime;
+use reqwest::blocking::Response;
+use std::collections::BTreeMap;
+
+/// Source of the contests scraped from AtCoder, listed in sources
+/// like the base URL of an API.
+pub const ATCODER: &str = "https://atcoder.jp";
+
+/// Page listing the running and upcoming AtCoder contests.
+pub const CONTESTS_URL: &str = "https://atcoder.jp/contests/?lang=en";
+
+/// Running and upcoming AtCoder contests, which has no API,
+/// scraped from its contests page.
+pub struct AtCoder;
+
+impl ContestSource for AtCoder {
+    fn url(&self) -> &str {
+        ATCODER
+    }
+
+    fn request(&self, client: &CodeforcesClient, etag: Option<&str>) -> reqwest::Result<Response> {
+        client.get_if_none_match(CONTESTS_URL, etag)
+    }
+
+    fn parse(&self, body: &str) -> Result<Vec<Contest>, String> {
+        parse_contests(body)
+    }
+}
+
+/// Extracts the running and upcoming contests of the AtCoder contests page.
+///
+/// Every contest is a row of the table with its start time, a link
+/// to /contests/{slug} with its name and its duration as hh:mm.
+/// The id is derived from the slug, page_url links the contest page.
+pub fn parse_contests(html: &str) -> Result<Vec<Contest>, String> {
+    let mut contests = Vec::new();
+
+    for (table, phase) in [("id=\"contest-table-action\"", Phase::Coding), ("id=\"contest-table-upcoming\"", Phase::Before)] {
+        let Some(start) = html.find(table) else { continue };
+        let rest = &html[start..];
+        let body = match (rest.find("<tbody>"), rest.find("</tbody>")) {
+            (Some(start), Some(end)) if start < end => &rest[start..end],
+            _ => continue,
+        };
+
+        for row in body.split("<tr>").skip(1) {
+            contests.push(parse_row(row, phase.clone())
+                .ok_or_else(|| format!("Could not parse AtCoder contest row: {}", row.trim()))?);
+        }
+    }
+
+    if contests.is_empty() && !html.contains("id=\"contest-table-upcoming\"") {
+        return Err("No upcoming contest table on the AtCoder contests page.".to_string());
+    }
+    Ok(contests)
+}
+
+fn parse_row(row: &str, phase: Phase) -> Option<Contest> {
+    let time = between(row, "<time class='fixtime fixtime-full'>", "</time>")?;
+    let start = DateTime::parse_from_str(time.trim(), "%Y-%m-%d %H:%M:%S%z").ok()?.timestamp();
+
+    let link = &row[row.find("<a href=\"/contests/")? + "<a href=\"/contests/".len()..];
+    let slug = &link[..link.find('"')?];
+    let name = between(link, ">", "</a>")?;
+
+    // The duration is the first cell after the contest name, e.g. "01:40".
+    let duration = between(&link[link.find("</td>")?..], "<td class=\"text-center\">", "</td>")?;
+    let (hours, minutes) = duration.trim().split_once(':')?;
+    let duration = hours.parse::<i64>().ok()? * 3600 + minutes.parse::<i64>().ok()? * 60;
+
+    Some(Contest {
+        id: slug_id(ATCODER, slug),
+        name: unescape_html(name.trim()),
+        contest_type: None,
+        phase,
+        start_time_seconds: Some(start),
+        duration_seconds: Some(duration),
+        description: None,
+        scraped_description: None,
+        reminder_ids: Vec::new(),
+        delivery_ids: BTreeMap::new(),
+        failed_deliveries: Vec::new(),
+        source: Some(ATCODER.to_string()),
+        lead_time_override: None,
+        page_url: Some(format!("{}/contests/{}", ATCODER, slug)),
+    })
+}
+
+/// Returns the text between the first start and the following end.
+fn between<'a>(text: &'a str, start: &str, end: &str) -> Option<&'a str> {
+    let rest = &text[text.find(start)? + start.len()..];
+    Some(&rest[..rest.find(end)?])
+}
+
+fn unescape_html(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE: &str = r#"<html><body>
+        <div id="contest-table-action"><table><tbody>
+        <tr>
+            <td class="text-center"><a href='http://www.timeanddate.com/worldclock/fixedtime.html?iso=20250426T2100&p1=248' target='blank'><time class='fixtime fixtime-full'>2025-04-26 21:00:00+0900</time></a></td>
+            <td><span>Ⓐ</span> <a href="/contests/abc402">AtCoder Beginner Contest 402</a></td>
+            <td class="text-center">01:40</td>
+            <td class="text-center"> - 1999</td>
+        </tr>
+        </tbody></table></div>
+        <div id="contest-table-upcoming"><table><tbody>
+        <tr>
+            <td class="text-center"><a href='http://www.timeanddate.com/worldclock/fixedtime.html?iso=20250503T2100&p1=248' target='blank'><time class='fixtime fixtime-full'>2025-05-03 21:00:00+0900</time></a></td>
+            <td><span>Ⓐ</span> <a href="/contests/arc197">AtCoder Regular Contest 197 (Div. 2) &amp; &quot;Extra&quot;</a></td>
+            <td class="text-center">02:00</td>
+            <td class="text-center"> 1200 - 2399</td>
+        </tr>
+        </tbody></table></div>
+    </body></html>"#;
+
+    #[test]
+    fn parses_running_and_upcoming_contests() {
+        let contests = parse_contests(PAGE).unwrap();
+
+        assert_eq!(contests.len(), 2);
+        let (running, upcoming) = (&contests[0], &contests[1]);
+        assert_eq!(running.name, "AtCoder Beginner Contest 402");
+        assert_eq!(running.phase, Phase::Coding);
+        assert_eq!(running.start_time_seconds, Some(1745668800));
+        assert_eq!(running.duration_seconds, Some(6000));
+        assert_eq!(upcoming.name, r#"AtCoder Regular Contest 197 (Div. 2) & "Extra""#);
+        assert_eq!(upcoming.phase, Phase::Before);
+        assert_eq!(upcoming.id, slug_id(ATCODER, "arc197"));
+        assert_eq!(upcoming.page_url.as_deref(), Some("https://atcoder.jp/contests/arc197"));
+        assert_eq!(upcoming.source_url(), ATCODER);
+    }
+
+    #[test]
+    fn page_without_contests_table_fails() {
+        assert!(parse_contests("<html>Maintenance</html>").is_err());
+        assert!(parse_contests(r#"<div id="contest-table-upcoming"><table><tbody></tbody></table></div>"#).unwrap().is_empty());
+    }
+
+    #[test]
+    fn unparsable_row_fails() {
+        let page = PAGE.replace("02:00", "soon");
+
+        assert!(parse_contests(&page).unwrap_err().contains("Could not parse AtCoder contest row"));
+    }
+}
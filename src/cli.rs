@@ -0,0 +1,278 @@
+use std::path::PathBuf;
+
+/// Longest lead time --snooze accepts, 30 days.
+pub const MAX_SNOOZE_SECONDS: i64 = 30 * 24 * 60 * 60;
+
+/// Command line options.
+///
+/// Running without any option performs the regular sync.
+#[derive(Debug, Default)]
+pub struct Args {
+    /// Print which contests are new, finished or changed and exit
+    /// without creating reminders or saving contests.
+    pub diff: bool,
+    /// Print the effective config and the resolved paths and exit.
+    pub show_config: bool,
+    /// Manage the watch rules in the config and exit.
+    pub watch: Option<WatchCommand>,
+    /// Pin or unpin a contest id in the config and exit.
+    pub pin: Option<PinCommand>,
+    /// Remove all but the given number of lines from the log and exit.
+    pub prune_logs: Option<usize>,
+    /// Read the contest list response from stdin instead of fetching it.
+    pub stdin: bool,
+    /// Fetch the contest list even if the last fetch is recent.
+    pub force: bool,
+    /// Delete all reminders if the contest list has no upcoming contests.
+    pub allow_empty: bool,
+    /// Print errors and a single summary line if the run changed anything.
+    pub cron: bool,
+    /// Open the page of the soonest saved contest in the browser and exit.
+    pub open_next: bool,
+    /// Print the soonest saved contest on a single line and exit.
+    pub next: bool,
+    /// Print stats of the saved, archived and historical contests and exit.
+    pub stats: bool,
+    /// Send one message with the contests of the coming week and exit.
+    pub digest: bool,
+    /// Check contests.json for problems and exit.
+    pub verify_cache: bool,
+    /// Print which events the .ics file would gain, lose or change and exit.
+    pub ics_diff: bool,
+    /// Write the saved contests with alarms as .ics file to this path and exit.
+    pub export_ics: Option<PathBuf>,
+    /// Print the saved contests as Markdown table, or write it
+    /// to the path, and exit.
+    pub render_markdown: Option<Option<PathBuf>>,
+    /// Serve the saved contests as calendar feed on this address until interrupted.
+    pub serve: Option<String>,
+    /// Grant the google-calendar notifier access to the calendar and exit.
+    pub google_auth: bool,
+    /// Grant the outlook notifier access to the calendar and exit.
+    pub outlook_auth: bool,
+    /// Print why the contest with this id does or does not get a reminder and exit.
+    pub explain: Option<usize>,
+    /// Move the reminder of the saved contest with this id to this many
+    /// seconds before its start and exit.
+    pub snooze: Option<(usize, i64)>,
+    /// Source of the contest id given to --pin, --unpin, --snooze or
+    /// --explain, for ids that several sources have.
+    pub source: Option<String>,
+    /// Use the config section and data directory of this profile.
+    pub profile: Option<String>,
+}
+
+/// Subcommands of --watch.
+#[derive(Debug)]
+pub enum WatchCommand {
+    Add(String),
+    Remove(String),
+    List,
+}
+
+/// Options changing the pinned contest ids.
+#[derive(Debug)]
+pub enum PinCommand {
+    Pin(usize),
+    Unpin(usize),
+}
+
+/// Parses the command line arguments.
+///
+/// Prints usage and terminates with exit code 2 on unknown arguments.
+pub fn parse_args() -> Args {
+    parse(std::env::args().skip(1)).unwrap_or_else(|msg| usage_error(&msg))
+}
+
+/// Parses the arguments after the program name, failing with the
+/// message to print above the usage.
+fn parse(mut iter: impl Iterator<Item = String>) -> Result<Args, String> {
+    let mut args = Args::default();
+
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--diff" => args.diff = true,
+            "--show-config" => args.show_config = true,
+            "--stdin" => args.stdin = true,
+            "--force" => args.force = true,
+            "--allow-empty" => args.allow_empty = true,
+            "--cron" => args.cron = true,
+            "--open-next" => args.open_next = true,
+            "--next" => args.next = true,
+            "--stats" => args.stats = true,
+            "--digest" => args.digest = true,
+            "--verify-cache" => args.verify_cache = true,
+            "--ics-diff" => args.ics_diff = true,
+            "--google-auth" => args.google_auth = true,
+            "--outlook-auth" => args.outlook_auth = true,
+            "--watch" => {
+                let command = match iter.next().as_deref() {
+                    Some("add") => WatchCommand::Add(value(&mut iter, "--watch add")?),
+                    Some("remove") => WatchCommand::Remove(value(&mut iter, "--watch remove")?),
+                    Some("list") => WatchCommand::List,
+                    _ => return Err("--watch expects add <regex>, remove <regex> or list".to_string()),
+                };
+                args.watch = Some(command);
+            }
+            "--export-ics" => args.export_ics = Some(PathBuf::from(value(&mut iter, "--export-ics")?)),
+            "--serve" => args.serve = Some(value(&mut iter, "--serve")?),
+            "--explain" => args.explain = Some(contest_id(&mut iter, "--explain")?),
+            "--pin" => args.pin = Some(PinCommand::Pin(contest_id(&mut iter, "--pin")?)),
+            "--unpin" => args.pin = Some(PinCommand::Unpin(contest_id(&mut iter, "--unpin")?)),
+            "--snooze" => {
+                let id = value(&mut iter, "--snooze")?;
+                let lead_time = value(&mut iter, "--snooze <id>")?;
+                match (id.parse(), lead_time.parse::<i64>()) {
+                    (Ok(id), Ok(lead_time)) if (0..=MAX_SNOOZE_SECONDS).contains(&lead_time) => args.snooze = Some((id, lead_time)),
+                    _ => return Err(format!("--snooze expects a contest id and at most {} seconds before the start, got {} {}", MAX_SNOOZE_SECONDS, id, lead_time)),
+                }
+            }
+            "--source" => args.source = Some(value(&mut iter, "--source")?),
+            "--profile" => {
+                let name = value(&mut iter, "--profile")?;
+                if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+                    return Err(format!("--profile expects letters, digits, - and _, got {}", name));
+                }
+                args.profile = Some(name);
+            }
+            "--render-markdown" => args.render_markdown = Some(None),
+            _ if arg.starts_with("--render-markdown=") => {
+                args.render_markdown = Some(Some(PathBuf::from(&arg["--render-markdown=".len()..])));
+            }
+            "--prune-logs" => args.prune_logs = Some(0),
+            _ if arg.starts_with("--prune-logs=") => {
+                let keep = &arg["--prune-logs=".len()..];
+                match keep.parse() {
+                    Ok(keep) => args.prune_logs = Some(keep),
+                    Err(_) => return Err(format!("--prune-logs expects a number of lines, got {}", keep)),
+                }
+            }
+            _ => return Err(format!("Unknown argument: {}", arg)),
+        }
+    }
+
+    Ok(args)
+}
+
+/// Returns the next argument as the value of `option`.
+fn value(iter: &mut impl Iterator<Item = String>, option: &str) -> Result<String, String> {
+    iter.next().ok_or_else(|| format!("{} expects a value", option))
+}
+
+/// Returns the next argument parsed as the contest id expected by `option`.
+fn contest_id(iter: &mut impl Iterator<Item = String>, option: &str) -> Result<usize, String> {
+    let id = value(iter, option)?;
+    id.parse().map_err(|_| format!("{} expects a contest id, got {}", option, id))
+}
+
+fn usage_error(msg: &str) -> ! {
+    eprintln!("{}", msg);
+    eprintln!("{}", USAGE);
+    std::process::exit(2);
+}
+
+const USAGE: &str = "Usage: codeforces-reminder [OPTION]
+
+    --diff                   Print new, finished and changed contests without side effects
+    --stdin                  Read the contest.list response from stdin instead of fetching it
+    --force                  Fetch the contest list even if the last fetch is recent
+    --allow-empty            Delete all reminders if no contest is upcoming online
+    --cron                   Print errors and one summary line if anything changed
+    --next                   Print name, start and countdown of the next contest
+    --stats                  Print counts of saved, archived and historical contests
+    --digest                 Send one message with the saved contests of the coming week
+    --open-next              Open the page of the next contest in the browser
+    --verify-cache           Check contests.json for problems without changing it
+    --ics-diff               Print the changes the next write of the .ics file would make
+    --export-ics <path>      Write the saved contests with alarms as .ics file, - for stdout
+    --render-markdown[=path] Print the saved contests as Markdown table, or write it to path
+    --serve <address>        Serve the saved contests as webcal feed at /contests.ics
+    --google-auth            Allow the google-calendar notifier to access the calendar
+    --outlook-auth           Allow the outlook notifier to access the calendar
+    --explain <id>           Print every decision taken for the contest with this id
+    --snooze <id> <seconds>  Move the reminder of a saved contest to this long before its start
+    --profile <name>         Use the config section and data directory of a profile
+    --show-config            Print the effective config and the paths in use
+    --watch add <regex>      Only remind of contests matching one of the watch rules
+    --watch remove <regex>   Remove a watch rule
+    --watch list             Print the watch rules
+    --pin <id>               Always remind of the contest with this id, ignoring all filters
+    --unpin <id>             Remove a pinned contest id
+    --source <url>           Source of the id given to --pin, --unpin, --snooze or --explain
+    --prune-logs[=N]         Clear the error log, or keep only its last N lines";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_str(args: &[&str]) -> Result<Args, String> {
+        parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn no_arguments_sync() {
+        let args = parse_str(&[]).unwrap();
+        assert!(!args.diff && !args.force && args.watch.is_none());
+    }
+
+    #[test]
+    fn diff_combines_with_other_flags() {
+        let args = parse_str(&["--diff", "--force"]).unwrap();
+        assert!(args.diff);
+        assert!(args.force);
+    }
+
+    #[test]
+    fn watch_subcommands() {
+        assert!(matches!(parse_str(&["--watch", "add", "Div. 2"]).unwrap().watch, Some(WatchCommand::Add(rule)) if rule == "Div. 2"));
+        assert!(matches!(parse_str(&["--watch", "remove", "ECR"]).unwrap().watch, Some(WatchCommand::Remove(rule)) if rule == "ECR"));
+        assert!(matches!(parse_str(&["--watch", "list"]).unwrap().watch, Some(WatchCommand::List)));
+        assert!(parse_str(&["--watch", "add"]).is_err());
+        assert!(parse_str(&["--watch", "clear"]).is_err());
+    }
+
+    #[test]
+    fn next_is_a_flag() {
+        assert!(parse_str(&["--next"]).unwrap().next);
+        assert!(!parse_str(&[]).unwrap().next);
+        assert!(parse_str(&["--next", "2100"]).is_err());
+    }
+
+    #[test]
+    fn pin_and_unpin_take_an_id() {
+        assert!(matches!(parse_str(&["--pin", "2100"]).unwrap().pin, Some(PinCommand::Pin(2100))));
+        assert!(matches!(parse_str(&["--unpin", "2100"]).unwrap().pin, Some(PinCommand::Unpin(2100))));
+        assert!(parse_str(&["--pin"]).is_err());
+        assert!(parse_str(&["--pin", "round"]).is_err());
+    }
+
+    #[test]
+    fn source_qualifies_pin() {
+        let args = parse_str(&["--pin", "400", "--source", "https://atcoder.jp"]).unwrap();
+
+        assert!(matches!(args.pin, Some(PinCommand::Pin(400))));
+        assert_eq!(args.source.as_deref(), Some("https://atcoder.jp"));
+    }
+
+    #[test]
+    fn snooze_takes_id_and_lead_time() {
+        assert_eq!(parse_str(&["--snooze", "2100", "600"]).unwrap().snooze, Some((2100, 600)));
+        assert_eq!(parse_str(&["--snooze", "2100", "0"]).unwrap().snooze, Some((2100, 0)));
+        assert_eq!(parse_str(&["--snooze", "2100", &MAX_SNOOZE_SECONDS.to_string()]).unwrap().snooze,
+            Some((2100, MAX_SNOOZE_SECONDS)));
+    }
+
+    #[test]
+    fn snooze_rejects_invalid_lead_times() {
+        for lead_time in ["-1", "2592001", "9223372036854775807", "soon"] {
+            assert!(parse_str(&["--snooze", "2100", lead_time]).is_err(), "accepted {}", lead_time);
+        }
+        assert!(parse_str(&["--snooze", "round", "600"]).is_err());
+        assert!(parse_str(&["--snooze", "2100"]).is_err());
+    }
+
+    #[test]
+    fn rejects_unknown_arguments() {
+        assert_eq!(parse_str(&["--dif"]).unwrap_err(), "Unknown argument: --dif");
+    }
+}
@@ -0,0 +1,146 @@
+use crate::api::CodeforcesClient;
+use crate::config::ClistConfig;
+use crate::contest::{slug_id, Contest, Phase};
+use crate::source::ContestSource;
+
+use chrono::NaiveDateTime;
+use reqwest::blocking::Response;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// Source of the contests aggregated by clist.by, added to the
+/// sources if clist is set in the config.
+pub const CLIST: &str = "https://clist.by";
+
+/// Upcoming contests of all judges, soonest first.
+const CONTESTS_URL: &str = "https://clist.by/api/v4/contest/?upcoming=true&order_by=start&limit=1000&format=json";
+
+/// Upcoming contests aggregated by clist.by, asked with the API key
+/// of the config, which does not answer with an ETag.
+pub struct Clist<'a> {
+    config: &'a ClistConfig,
+}
+
+impl<'a> Clist<'a> {
+    pub fn new(config: &'a ClistConfig) -> Self {
+        Clist { config }
+    }
+}
+
+impl ContestSource for Clist<'_> {
+    fn url(&self) -> &str {
+        CLIST
+    }
+
+    fn request(&self, client: &CodeforcesClient, _etag: Option<&str>) -> reqwest::Result<Response> {
+        client.get_authorized(CONTESTS_URL, &authorization(self.config))
+    }
+
+    fn parse(&self, body: &str) -> Result<Vec<Contest>, String> {
+        parse_contests(body, self.config)
+    }
+}
+
+#[derive(Deserialize)]
+struct ContestList {
+    objects: Vec<ClistContest>,
+}
+
+#[derive(Deserialize)]
+struct ClistContest {
+    id: u64,
+    event: String,
+    /// Host of the judge, e.g. "codeforces.com".
+    resource: String,
+    href: String,
+    /// Start in UTC without offset, e.g. "2025-04-20T14:35:00".
+    start: String,
+    /// Duration in seconds.
+    duration: i64,
+}
+
+/// Value of the Authorization header for the API key of the config.
+fn authorization(config: &ClistConfig) -> String {
+    format!("ApiKey {}:{}", config.username, config.api_key)
+}
+
+/// Returns the contests of a clist.by response on one of the
+/// resources in the config, or on any judge if there are none.
+///
+/// The id is derived from the clist.by id, page_url links the
+/// contest page on the judge.
+pub fn parse_contests(body: &str, config: &ClistConfig) -> Result<Vec<Contest>, String> {
+    let response: ContestList = serde_json::from_str(body)
+        .map_err(|e| format!("Could not parse clist.by contest JSON. {}", e))?;
+
+    response.objects
+        .into_iter()
+        .filter(|contest| config.resources.is_empty() || config.resources.contains(&contest.resource))
+        .map(|contest| {
+            let start = NaiveDateTime::parse_from_str(&contest.start, "%Y-%m-%dT%H:%M:%S")
+                .map_err(|e| format!("Invalid start of clist.by contest {}. {}", contest.id, e))?;
+
+            Ok(Contest {
+                id: slug_id(CLIST, &contest.id.to_string()),
+                name: contest.event,
+                contest_type: None,
+                phase: Phase::Before,
+                start_time_seconds: Some(start.and_utc().timestamp()),
+                duration_seconds: Some(contest.duration),
+                description: None,
+                scraped_description: None,
+                reminder_ids: Vec::new(),
+                delivery_ids: BTreeMap::new(),
+                failed_deliveries: Vec::new(),
+                source: Some(CLIST.to_string()),
+                lead_time_override: None,
+                page_url: Some(contest.href),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RESPONSE: &str = r#"{"meta": {"limit": 1000}, "objects": [
+        {"id": 57123001, "event": "Codeforces Round 1021 (Div. 2)", "resource": "codeforces.com", "resource_id": 1,
+         "href": "https://codeforces.com/contests/2101", "start": "2025-04-28T14:35:00", "end": "2025-04-28T16:35:00", "duration": 7200},
+        {"id": 57123002, "event": "AtCoder Beginner Contest 403", "resource": "atcoder.jp", "resource_id": 93,
+         "href": "https://atcoder.jp/contests/abc403", "start": "2025-04-27T12:00:00", "end": "2025-04-27T13:40:00", "duration": 6000}
+    ]}"#;
+
+    fn config(resources: &[&str]) -> ClistConfig {
+        ClistConfig {
+            username: "me".to_string(),
+            api_key: "key".to_string(),
+            resources: resources.iter().map(|resource| resource.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn parses_contests_of_all_resources() {
+        let contests = parse_contests(RESPONSE, &config(&[])).unwrap();
+
+        assert_eq!(contests.len(), 2);
+        let round = &contests[0];
+        assert_eq!(round.id, slug_id(CLIST, "57123001"));
+        assert_eq!(round.start_time_seconds, Some(1745850900));
+        assert_eq!(round.duration_seconds, Some(7200));
+        assert_eq!(round.page_url.as_deref(), Some("https://codeforces.com/contests/2101"));
+        assert_eq!(round.source_url(), CLIST);
+    }
+
+    #[test]
+    fn keeps_only_configured_resources() {
+        let contests = parse_contests(RESPONSE, &config(&["atcoder.jp"])).unwrap();
+
+        assert_eq!(contests.iter().map(|contest| contest.name.as_str()).collect::<Vec<_>>(), ["AtCoder Beginner Contest 403"]);
+    }
+
+    #[test]
+    fn authorizes_with_api_key() {
+        assert_eq!(authorization(&config(&[])), "ApiKey me:key");
+    }
+}
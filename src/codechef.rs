@@ -0,0 +1,137 @@
+use crate::api::CodeforcesClient;
+use crate::contest::{slug_id, Contest, Phase};
+use crate::source::ContestSource;
+
+use chrono::DateTime;
+use reqwest::blocking::Response;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// Source of the CodeChef contests, listed in sources like
+/// the base URL of an API.
+pub const CODECHEF: &str = "https://www.codechef.com";
+
+/// Endpoint of the CodeChef website listing running and upcoming contests.
+pub const CONTESTS_URL: &str = "https://www.codechef.com/api/list/contests/all?sort_by=START&sorting_order=asc&offset=0&mode=all";
+
+/// Running and upcoming CodeChef contests, asked through
+/// the endpoint of its website.
+pub struct CodeChef;
+
+impl ContestSource for CodeChef {
+    fn url(&self) -> &str {
+        CODECHEF
+    }
+
+    fn request(&self, client: &CodeforcesClient, etag: Option<&str>) -> reqwest::Result<Response> {
+        client.get_if_none_match(CONTESTS_URL, etag)
+    }
+
+    fn parse(&self, body: &str) -> Result<Vec<Contest>, String> {
+        parse_contests(body)
+    }
+}
+
+#[derive(Deserialize)]
+struct ContestList {
+    status: String,
+    #[serde(default)]
+    message: Option<String>,
+    #[serde(default)]
+    present_contests: Vec<CodeChefContest>,
+    #[serde(default)]
+    future_contests: Vec<CodeChefContest>,
+}
+
+#[derive(Deserialize)]
+struct CodeChefContest {
+    contest_code: String,
+    contest_name: String,
+    contest_start_date_iso: String,
+    /// Duration in minutes.
+    contest_duration: String,
+}
+
+/// Returns the running and upcoming contests of a CodeChef response,
+/// e.g. Starters and Cook-Offs.
+///
+/// The id is derived from the contest code (e.g. "START180") and
+/// page_url links the contest page.
+pub fn parse_contests(body: &str) -> Result<Vec<Contest>, String> {
+    let response: ContestList = serde_json::from_str(body)
+        .map_err(|e| format!("Could not parse CodeChef contest JSON. {}", e))?;
+
+    if response.status != "success" {
+        let message = response.message.unwrap_or_else(|| "No message.".to_string());
+        return Err(format!("CodeChef response status {}. Message: {}", response.status, message));
+    }
+
+    let present = response.present_contests.into_iter().map(|contest| (contest, Phase::Coding));
+    let future = response.future_contests.into_iter().map(|contest| (contest, Phase::Before));
+
+    present.chain(future).map(|(contest, phase)| {
+        let start = DateTime::parse_from_rfc3339(&contest.contest_start_date_iso)
+            .map_err(|e| format!("Invalid start of CodeChef contest {}. {}", contest.contest_code, e))?;
+        let minutes: i64 = contest.contest_duration.trim().parse()
+            .map_err(|e| format!("Invalid duration of CodeChef contest {}. {}", contest.contest_code, e))?;
+        let duration = minutes.checked_mul(60)
+            .ok_or_else(|| format!("Invalid duration of CodeChef contest {}. Too long.", contest.contest_code))?;
+
+        Ok(Contest {
+            id: slug_id(CODECHEF, &contest.contest_code),
+            name: contest.contest_name,
+            contest_type: None,
+            phase,
+            start_time_seconds: Some(start.timestamp()),
+            duration_seconds: Some(duration),
+            description: None,
+            scraped_description: None,
+            reminder_ids: Vec::new(),
+            delivery_ids: BTreeMap::new(),
+            failed_deliveries: Vec::new(),
+            source: Some(CODECHEF.to_string()),
+            lead_time_override: None,
+            page_url: Some(format!("{}/{}", CODECHEF, contest.contest_code)),
+        })
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(duration: &str) -> String {
+        format!(r#"{{"status": "success", "message": "All contests list",
+            "present_contests": [{{"contest_code": "START181", "contest_name": "Starters 181", "contest_start_date_iso": "2025-04-23T20:00:00+05:30", "contest_duration": "120"}}],
+            "future_contests": [{{"contest_code": "START182", "contest_name": "Starters 182", "contest_start_date_iso": "2025-04-30T20:00:00+05:30", "contest_duration": "{}"}}],
+            "past_contests": []}}"#, duration)
+    }
+
+    #[test]
+    fn parses_running_and_upcoming_contests() {
+        let contests = parse_contests(&response("180")).unwrap();
+
+        assert_eq!(contests.len(), 2);
+        assert_eq!((contests[0].name.as_str(), &contests[0].phase), ("Starters 181", &Phase::Coding));
+        let upcoming = &contests[1];
+        assert_eq!(upcoming.phase, Phase::Before);
+        assert_eq!(upcoming.id, slug_id(CODECHEF, "START182"));
+        assert_eq!(upcoming.start_time_seconds, Some(1746023400));
+        assert_eq!(upcoming.duration_seconds, Some(10800));
+        assert_eq!(upcoming.page_url, Some(format!("{}/START182", CODECHEF)));
+    }
+
+    #[test]
+    fn invalid_durations_fail() {
+        for duration in ["two hours", &i64::MAX.to_string()] {
+            assert!(parse_contests(&response(duration)).unwrap_err().contains("Invalid duration of CodeChef contest START182"));
+        }
+    }
+
+    #[test]
+    fn failed_status_returns_message() {
+        let error = parse_contests(r#"{"status": "failure", "message": "Service unavailable"}"#).unwrap_err();
+
+        assert_eq!(error, "CodeChef response status failure. Message: Service unavailable");
+    }
+}
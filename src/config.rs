@@ -0,0 +1,954 @@
+use crate::contest::{Contest, CODEFORCES_API};
+use crate::local::{log_error, write_atomically, Level};
+use crate::paths::{config_path, profile, DEFAULT_PROFILE};
+
+use serde::{Serialize, Deserialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// Permissions of created files, readable and writable by the owner only.
+pub const DEFAULT_FILE_MODE: u32 = 0o600;
+
+/// User configuration read from config.json.
+///
+/// Every field is optional in the file, missing fields
+/// (or a missing file) fall back to the defaults.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct Config {
+    /// Fetch the contest page of contests without a description
+    /// and use the announcement link as reminder body.
+    pub scrape_descriptions: bool,
+    /// Save contests.json without indentation.
+    pub compact_json: bool,
+    /// Save contests.json after every created reminder, not only at the end.
+    pub save_incrementally: bool,
+    /// Add the difficulty inferred from the contest name to the reminder body.
+    pub show_difficulty: bool,
+    /// Render the description as Rust Debug output, e.g. Some("...")
+    /// or None, instead of the plain text.
+    pub debug_description: bool,
+    /// Name patterns and the difficulty they stand for, first match wins.
+    pub difficulty_tiers: Vec<DifficultyTier>,
+    /// Regexes matched against contest names, only matching contests
+    /// get reminders. Every contest matches if there are none.
+    pub watch: Vec<String>,
+    /// Contests that always get reminders, bypassing every filter.
+    pub pinned: Vec<PinnedContest>,
+    /// Also keep reminders for contests that already started (Phase::Coding),
+    /// marking them as live.
+    pub include_running: bool,
+    /// Also fetch the gym contests of Codeforces.
+    pub include_gym: bool,
+    /// Regexes matched against gym names, only matching gyms are fetched.
+    /// Every gym matches if there are none.
+    pub gym_patterns: Vec<String>,
+    /// Append every created, updated and deleted reminder to history.jsonl.
+    pub history: bool,
+    /// Move finished contests to archive.json instead of dropping them.
+    pub archive_finished: bool,
+    /// Backend delivering the notifications (reminders, notify-send, toast,
+    /// telegram, discord, slack, email, ntfy, gotify, pushover, pushbullet,
+    /// matrix, webhook, twilio, shortcuts, things, omnifocus, todoist, notion,
+    /// google-calendar, caldav, calendar or outlook),
+    /// the default of the platform if None.
+    pub notifier: Option<String>,
+    /// Several backends notifying at once, each with its own lead times
+    /// and filter. Replaces notifier if not empty.
+    pub notifiers: Vec<NotifierSettings>,
+    /// Bot and chat of the telegram notifier.
+    pub telegram: Option<TelegramConfig>,
+    /// Webhook the discord notifier posts to.
+    pub discord_webhook_url: Option<String>,
+    /// Webhooks of the slack notifier.
+    pub slack: Option<SlackConfig>,
+    /// SMTP server and addresses of the email notifier.
+    pub email: Option<EmailConfig>,
+    /// Server and topic of the ntfy notifier.
+    pub ntfy: Option<NtfyConfig>,
+    /// Server and priorities of the gotify notifier.
+    pub gotify: Option<GotifyConfig>,
+    /// Keys and priorities of the pushover notifier.
+    pub pushover: Option<PushoverConfig>,
+    /// Access token of the account the pushbullet notifier pushes to.
+    pub pushbullet_token: Option<String>,
+    /// Homeserver and room of the matrix notifier.
+    pub matrix: Option<MatrixConfig>,
+    /// Endpoint and headers of the webhook notifier.
+    pub webhook: Option<WebhookConfig>,
+    /// Account and numbers of the twilio notifier.
+    pub twilio: Option<TwilioConfig>,
+    /// Account and project of the todoist notifier.
+    pub todoist: Option<TodoistConfig>,
+    /// Integration and database of the notion notifier.
+    pub notion: Option<NotionConfig>,
+    /// OAuth client and calendar of the google-calendar notifier.
+    pub google_calendar: Option<GoogleCalendarConfig>,
+    /// Calendar and account of the caldav notifier.
+    pub caldav: Option<CaldavConfig>,
+    /// Calendar.app calendar the calendar notifier adds events to.
+    pub calendar_name: String,
+    /// App registration and calendar of the outlook notifier.
+    pub outlook: Option<OutlookConfig>,
+    /// Name of the Apple Shortcut the shortcuts notifier runs.
+    pub shortcut_name: Option<String>,
+    /// Project the things notifier adds its to-dos to, the Inbox if None.
+    pub things_project: Option<String>,
+    /// Project the omnifocus notifier adds its tasks to, the Inbox if None.
+    pub omnifocus_project: Option<String>,
+    /// Show a Notification Center banner listing the contests
+    /// whose reminders a run created.
+    pub banner: bool,
+    /// Send a summary of every run that changed reminders
+    /// through the notifier.
+    pub notify_summary: bool,
+    /// Backend (like notifier) sending a message about the first error
+    /// of a run, None to only log errors.
+    pub failure_notifier: Option<String>,
+    /// Check URL of healthchecks.io (or a compatible service) pinged
+    /// when a run starts, succeeds or fails.
+    pub healthcheck_url: Option<String>,
+    /// DSN of the Sentry project every logged error is sent to.
+    pub sentry_dsn: Option<String>,
+    /// Application the AppleScript talks to, for renamed or
+    /// third-party apps understanding the Reminders dictionary.
+    pub reminders_app: String,
+    /// List new reminders are created in, the default list of the app if None.
+    pub default_list: Option<String>,
+    /// Lists for contests of a type or matching a pattern, the first match wins.
+    pub reminder_lists: Vec<ReminderList>,
+    /// Seconds before the start reminders are due, one reminder per entry.
+    pub lead_times_seconds: Vec<i64>,
+    /// With several lead times, create one reminder per contest with
+    /// a subtask per lead time instead of separate reminders.
+    pub group_lead_times: bool,
+    /// Maximum number of characters of reminder titles. Longer contest
+    /// names are shortened, the full name is put in the body.
+    pub max_title_length: Option<usize>,
+    /// Least severe messages written to error_log.txt (error, warn or info).
+    pub log_level: Level,
+    /// Contests lasting longer are skipped.
+    pub max_duration_seconds: Option<i64>,
+    /// Also skip contests of unknown duration if max_duration_seconds is set.
+    pub exclude_unknown_duration: bool,
+    /// Create one reminder per day listing its contests
+    /// instead of one reminder per contest.
+    pub daily_summary: bool,
+    /// Local time (HH:MM) daily summary reminders are due.
+    pub daily_summary_time: String,
+    /// Reuse the last fetched contest list if it is younger than this,
+    /// 0 fetches on every run.
+    pub fetch_ttl_seconds: u64,
+    /// Prefix reminder titles with a short label like "[ECR178]".
+    pub show_short_label: bool,
+    /// Checklist of preparation steps added to every reminder body.
+    pub prep_items: Vec<String>,
+    /// Fields of the reminder body in their order: name, description, note,
+    /// difficulty, prep, url, time, countdown, duration and type.
+    pub body_fields: Vec<String>,
+    /// Contest types (CF, IOI, ICPC) getting reminders, all if empty.
+    /// Contests of unknown type are always kept.
+    pub allowed_types: Vec<String>,
+    /// Flag reminders of contests whose name matches one of these regexes.
+    pub flag_patterns: Vec<String>,
+    /// Flag reminders of contests of these types (CF, IOI, ICPC).
+    pub flag_types: Vec<String>,
+    /// User-Agent header sent with every request to Codeforces.
+    pub user_agent: String,
+    /// Base URLs of APIs answering contest.list like Codeforces, merged.
+    pub sources: Vec<String>,
+    /// LeetCode contests fetched in addition to the sources.
+    pub leetcode: LeetCodeConfig,
+    /// clist.by account whose API key fetches contests of other judges.
+    pub clist: Option<ClistConfig>,
+    /// URLs of ICS feeds whose events are reminded of like contests.
+    pub ics_sources: Vec<String>,
+    /// Local times reminders are never due in, shifted to their end instead.
+    pub quiet_hours: Option<QuietHours>,
+    /// Skip new contests whose id is not above the highest id saved before.
+    pub ignore_below_high_water_mark: bool,
+    /// Add a reminder this long before the start, when registration closes.
+    pub registration_buffer_seconds: Option<i64>,
+    /// Contests whose name matches one of these regexes require registration,
+    /// all contests if empty.
+    pub registration_patterns: Vec<String>,
+    /// Write Prometheus metrics to this file after every run.
+    pub metrics_path: Option<PathBuf>,
+    /// Keep a calendar of the saved contests in this .ics file.
+    pub ics_path: Option<PathBuf>,
+    /// Keep the saved contests as Org-mode entries in this .org file.
+    pub org_path: Option<PathBuf>,
+    /// .ics files whose events overlapping a contest are listed in its
+    /// reminder body.
+    pub conflict_calendars: Vec<PathBuf>,
+    /// Skip new contests overlapping an event of conflict_calendars.
+    pub skip_conflicts: bool,
+    /// Vault whose daily notes get a line per saved contest.
+    pub obsidian: Option<ObsidianConfig>,
+    /// Unix permissions of contests.json, the log and the other files
+    /// written to the data directory, as octal string like "600".
+    #[serde(with = "octal_mode")]
+    pub file_mode: u32,
+    /// Shell command run after every completed run.
+    pub post_run_hook: Option<String>,
+    /// Sections selected with --profile, overriding the fields above.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, Value>,
+}
+
+/// Contest pinned with --pin, a plain id for contests of the Codeforces API.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum PinnedContest {
+    Id(usize),
+    /// Contest of another source, e.g. "https://atcoder.jp".
+    Source { source: String, id: usize },
+}
+
+impl PinnedContest {
+    pub fn new(id: usize, source: Option<&str>) -> Self {
+        match source.map(|source| source.trim_end_matches('/')) {
+            Some(source) if source != CODEFORCES_API => PinnedContest::Source { source: source.to_string(), id },
+            _ => PinnedContest::Id(id),
+        }
+    }
+
+    pub fn matches(&self, contest: &Contest) -> bool {
+        match self {
+            PinnedContest::Id(id) => contest.is(*id, Some(CODEFORCES_API)),
+            PinnedContest::Source { source, id } => contest.is(*id, Some(source)),
+        }
+    }
+}
+
+impl fmt::Display for PinnedContest {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PinnedContest::Id(id) => write!(f, "{}", id),
+            PinnedContest::Source { source, id } => write!(f, "{} of {}", id, source),
+        }
+    }
+}
+
+/// Which LeetCode contests are fetched, none unless enabled.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct LeetCodeConfig {
+    pub enabled: bool,
+    pub weekly: bool,
+    pub biweekly: bool,
+    /// Contests that are neither weekly nor biweekly, e.g. sponsored ones.
+    pub other: bool,
+}
+
+impl Default for LeetCodeConfig {
+    fn default() -> Self {
+        LeetCodeConfig { enabled: false, weekly: true, biweekly: true, other: true }
+    }
+}
+
+/// clist.by account and the judges whose contests are fetched from it.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ClistConfig {
+    pub username: String,
+    /// API key shown on https://clist.by/api/v4/doc/.
+    pub api_key: String,
+    /// Hosts of the judges, e.g. "atcoder.jp", all judges if empty.
+    #[serde(default)]
+    pub resources: Vec<String>,
+}
+
+/// Window of local time, as "HH:MM", that may cross midnight (e.g. 23:00 to 07:00).
+/// The start belongs to the window, the end does not.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct QuietHours {
+    pub start: String,
+    pub end: String,
+}
+
+/// Puts reminders of contests of `type` whose name matches the regex `pattern`
+/// into `list`. Leaving out type or pattern matches all contests.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReminderList {
+    #[serde(default, rename = "type")]
+    pub contest_type: Option<String>,
+    #[serde(default)]
+    pub pattern: Option<String>,
+    pub list: String,
+}
+
+/// Backend of notifiers used for contests of `type` whose name matches the
+/// regex `pattern`, leaving out either matches all contests.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NotifierSettings {
+    /// Name of the backend, like notifier.
+    pub backend: String,
+    /// Lead times of this backend, lead_times_seconds if None.
+    #[serde(default)]
+    pub lead_times_seconds: Option<Vec<i64>>,
+    #[serde(default, rename = "type")]
+    pub contest_type: Option<String>,
+    #[serde(default)]
+    pub pattern: Option<String>,
+}
+
+/// Token of the bot sending the messages and the chat they are sent to,
+/// a numeric id or "@channel".
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TelegramConfig {
+    pub bot_token: String,
+    pub chat_id: String,
+}
+
+/// Incoming webhooks of the slack notifier, each posting to one channel.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SlackConfig {
+    /// Webhook of contests matching none of channels, skipped if None.
+    #[serde(default)]
+    pub webhook_url: Option<String>,
+    /// Webhooks for contests of a type or matching a pattern, the first match wins.
+    #[serde(default)]
+    pub channels: Vec<SlackChannel>,
+}
+
+/// Posts contests of `type` whose name matches the regex `pattern` to the
+/// channel of `webhook_url`. Leaving out type or pattern matches all contests.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SlackChannel {
+    #[serde(default, rename = "type")]
+    pub contest_type: Option<String>,
+    #[serde(default)]
+    pub pattern: Option<String>,
+    pub webhook_url: String,
+}
+
+/// SMTP server the email notifier sends through, and its addresses.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EmailConfig {
+    pub smtp_host: String,
+    /// Port of the server, 465 (or 587 with starttls) if None.
+    #[serde(default)]
+    pub smtp_port: Option<u16>,
+    /// Connect without TLS and upgrade with STARTTLS.
+    #[serde(default)]
+    pub starttls: bool,
+    /// Login of the server, none if None.
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    pub from: String,
+    pub to: Vec<String>,
+}
+
+/// Topic the ntfy notifier publishes to.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NtfyConfig {
+    /// Base URL of the server, ntfy.sh by default.
+    #[serde(default = "default_ntfy_server")]
+    pub server: String,
+    pub topic: String,
+    /// Access token of protected topics.
+    #[serde(default)]
+    pub token: Option<String>,
+    /// Priority of the messages from 1 (min) to 5 (max), the default 3 if None.
+    #[serde(default)]
+    pub priority: Option<u8>,
+}
+
+fn default_ntfy_server() -> String {
+    "https://ntfy.sh".to_string()
+}
+
+/// Gotify server and application the gotify notifier pushes to.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GotifyConfig {
+    /// Base URL of the server.
+    pub url: String,
+    /// Token of the application the messages are pushed as.
+    pub app_token: String,
+    /// Priorities of contests of a type or matching a pattern, the first match wins.
+    #[serde(default = "default_gotify_priorities")]
+    pub priorities: Vec<GotifyPriority>,
+    /// Priority of contests matching none of priorities.
+    #[serde(default = "default_gotify_priority")]
+    pub default_priority: u8,
+}
+
+/// Pushes contests of `type` whose name matches the regex `pattern` with
+/// `priority`. Leaving out type or pattern matches all contests.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GotifyPriority {
+    #[serde(default, rename = "type")]
+    pub contest_type: Option<String>,
+    #[serde(default)]
+    pub pattern: Option<String>,
+    pub priority: u8,
+}
+
+/// Higher divisions get higher priorities, so they can alert louder.
+fn default_gotify_priorities() -> Vec<GotifyPriority> {
+    [("Div. 1", 8), ("Div. 2", 6)]
+        .into_iter()
+        .map(|(pattern, priority)| GotifyPriority { contest_type: None, pattern: Some(pattern.to_string()), priority })
+        .collect()
+}
+
+fn default_gotify_priority() -> u8 {
+    5
+}
+
+/// Pushover application and user the pushover notifier pushes to.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PushoverConfig {
+    /// API token of the application.
+    pub app_token: String,
+    /// Key of the user or group receiving the messages.
+    pub user_key: String,
+    /// Device receiving the messages, all devices of the user if None.
+    #[serde(default)]
+    pub device: Option<String>,
+    /// Priority from -2 to 2 of the message announcing a new contest.
+    #[serde(default)]
+    pub priority: i8,
+    /// Priority of the messages pushed when a reminder is due,
+    /// 2 repeats them until acknowledged.
+    #[serde(default = "default_pushover_due_priority")]
+    pub due_priority: i8,
+}
+
+fn default_pushover_due_priority() -> i8 {
+    1
+}
+
+/// Room the matrix notifier posts to, and the account it posts as.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MatrixConfig {
+    /// Base URL of the homeserver, e.g. "https://matrix.org".
+    pub homeserver: String,
+    pub access_token: String,
+    /// Id of the room like "!abc:matrix.org", not its alias.
+    pub room_id: String,
+}
+
+/// Endpoint the webhook notifier posts JSON to.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WebhookConfig {
+    pub url: String,
+    /// Headers sent with every request, e.g. Authorization.
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+}
+
+/// Twilio account the twilio notifier texts from.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TwilioConfig {
+    pub account_sid: String,
+    pub auth_token: String,
+    /// Twilio number the texts are sent from, e.g. "+15017122661".
+    pub from: String,
+    /// Numbers receiving the texts.
+    pub to: Vec<String>,
+}
+
+/// Todoist account and project the todoist notifier adds tasks to.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TodoistConfig {
+    /// API token from the integration settings of Todoist.
+    pub api_token: String,
+    /// Project of new tasks, the Inbox if None.
+    #[serde(default)]
+    pub project_id: Option<String>,
+    /// Labels of every task.
+    #[serde(default)]
+    pub labels: Vec<String>,
+}
+
+/// Notion integration and database the notion notifier adds rows to.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NotionConfig {
+    /// Secret of the internal integration, which the database is shared with.
+    pub token: String,
+    pub database_id: String,
+    /// Archive the rows of finished contests instead of setting
+    /// their status to Finished.
+    #[serde(default)]
+    pub archive_finished: bool,
+}
+
+/// Obsidian vault the daily notes with the contests of each day are in.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ObsidianConfig {
+    pub vault_path: PathBuf,
+    /// Folder of the daily notes inside the vault, the vault itself if empty.
+    #[serde(default)]
+    pub daily_notes_folder: String,
+    /// strftime format of the names of the daily notes.
+    #[serde(default = "default_daily_note_format")]
+    pub date_format: String,
+    /// Line added per contest, with {name}, {time}, {duration} and {url}.
+    #[serde(default = "default_daily_note_template")]
+    pub template: String,
+}
+
+fn default_daily_note_format() -> String {
+    "%Y-%m-%d".to_string()
+}
+
+fn default_daily_note_template() -> String {
+    "- [ ] {time} [{name}]({url})".to_string()
+}
+
+/// OAuth client of a Google Cloud project and the calendar the
+/// google-calendar notifier inserts events into.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GoogleCalendarConfig {
+    pub client_id: String,
+    pub client_secret: String,
+    /// Calendar of the events, the primary one by default.
+    #[serde(default = "default_calendar_id")]
+    pub calendar_id: String,
+}
+
+fn default_calendar_id() -> String {
+    "primary".to_string()
+}
+
+/// Microsoft Entra app registration and calendar the outlook notifier
+/// creates events in.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OutlookConfig {
+    /// Application (client) id of a registration allowing public client flows.
+    pub client_id: String,
+    /// Directory of the account, "common" for any work, school or personal one.
+    #[serde(default = "default_tenant")]
+    pub tenant: String,
+    /// Calendar of the events, the default calendar if None.
+    #[serde(default)]
+    pub calendar_id: Option<String>,
+}
+
+fn default_tenant() -> String {
+    "common".to_string()
+}
+
+/// CalDAV calendar the caldav notifier saves events in.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CaldavConfig {
+    /// URL of the calendar collection, e.g.
+    /// https://cloud.example.org/remote.php/dav/calendars/me/contests/.
+    pub calendar_url: String,
+    pub username: String,
+    /// Password, preferably an app password.
+    pub password: String,
+}
+
+/// Maps contests whose name contains `pattern` (ignoring case) to a difficulty.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DifficultyTier {
+    pub pattern: String,
+    pub difficulty: String,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            scrape_descriptions: false,
+            compact_json: false,
+            save_incrementally: false,
+            show_difficulty: false,
+            debug_description: false,
+            difficulty_tiers: default_difficulty_tiers(),
+            watch: Vec::new(),
+            pinned: Vec::new(),
+            include_running: false,
+            include_gym: false,
+            gym_patterns: Vec::new(),
+            history: false,
+            archive_finished: false,
+            notifier: None,
+            notifiers: Vec::new(),
+            telegram: None,
+            discord_webhook_url: None,
+            slack: None,
+            email: None,
+            ntfy: None,
+            gotify: None,
+            pushover: None,
+            pushbullet_token: None,
+            matrix: None,
+            webhook: None,
+            twilio: None,
+            todoist: None,
+            notion: None,
+            google_calendar: None,
+            caldav: None,
+            calendar_name: "Codeforces".to_string(),
+            outlook: None,
+            shortcut_name: None,
+            things_project: None,
+            omnifocus_project: None,
+            banner: false,
+            notify_summary: false,
+            failure_notifier: None,
+            healthcheck_url: None,
+            sentry_dsn: None,
+            reminders_app: "Reminders".to_string(),
+            default_list: None,
+            reminder_lists: Vec::new(),
+            lead_times_seconds: vec![1800],
+            group_lead_times: false,
+            max_title_length: None,
+            log_level: Level::Warn,
+            max_duration_seconds: None,
+            exclude_unknown_duration: false,
+            daily_summary: false,
+            daily_summary_time: "08:00".to_string(),
+            fetch_ttl_seconds: 900,
+            show_short_label: false,
+            prep_items: Vec::new(),
+            body_fields: ["name", "description", "note", "difficulty", "prep", "conflicts"].map(String::from).to_vec(),
+            allowed_types: Vec::new(),
+            flag_patterns: Vec::new(),
+            flag_types: Vec::new(),
+            user_agent: concat!("codeforces-reminder/", env!("CARGO_PKG_VERSION")).to_string(),
+            sources: vec![CODEFORCES_API.to_string()],
+            leetcode: LeetCodeConfig::default(),
+            clist: None,
+            ics_sources: Vec::new(),
+            quiet_hours: None,
+            ignore_below_high_water_mark: false,
+            registration_buffer_seconds: None,
+            registration_patterns: Vec::new(),
+            metrics_path: None,
+            ics_path: None,
+            org_path: None,
+            conflict_calendars: Vec::new(),
+            skip_conflicts: false,
+            obsidian: None,
+            file_mode: DEFAULT_FILE_MODE,
+            post_run_hook: None,
+            profiles: BTreeMap::new(),
+        }
+    }
+}
+
+/// Ratings usually targeted by the regular rounds.
+/// Combined rounds come first, as their names also contain "Div. 1".
+fn default_difficulty_tiers() -> Vec<DifficultyTier> {
+    [
+        ("Div. 1 + Div. 2", "all ratings"),
+        ("Div. 1", "~1900+"),
+        ("Div. 2", "~1600"),
+        ("Div. 3", "~1200"),
+        ("Div. 4", "~1000"),
+        ("Global Round", "all ratings"),
+    ]
+    .into_iter()
+    .map(|(pattern, difficulty)| DifficultyTier {
+        pattern: pattern.to_string(),
+        difficulty: difficulty.to_string(),
+    })
+    .collect()
+}
+
+/// Keys of config fields holding tokens, passwords or secret URLs.
+const SECRET_KEYS: &[&str] = &[
+    "discord_webhook_url", "pushbullet_token", "healthcheck_url", "sentry_dsn",
+    "api_key", "bot_token", "webhook_url", "password", "token", "app_token",
+    "user_key", "access_token", "auth_token", "api_token", "client_secret",
+];
+
+/// Shown instead of secret values.
+const REDACTED: &str = "***";
+
+/// Serializes the config with every secret replaced by "***", for printing.
+///
+/// Besides the fields in SECRET_KEYS this hides url and headers of the
+/// webhook notifier, which typically carry credentials.
+pub fn redacted(config: &Config) -> serde_json::Result<Value> {
+    let mut value = serde_json::to_value(config)?;
+    redact(&mut value);
+    Ok(value)
+}
+
+fn redact(value: &mut Value) {
+    match value {
+        Value::Object(fields) => {
+            for (key, field) in fields.iter_mut() {
+                if field.is_null() { continue }
+
+                if SECRET_KEYS.contains(&key.as_str()) {
+                    *field = Value::String(REDACTED.to_string());
+                } else if key == "webhook" {
+                    if let Value::Object(webhook) = field {
+                        if webhook.contains_key("url") {
+                            webhook.insert("url".to_string(), Value::String(REDACTED.to_string()));
+                        }
+                        if let Some(Value::Object(headers)) = webhook.get_mut("headers") {
+                            headers.values_mut().for_each(|header| *header = Value::String(REDACTED.to_string()));
+                        }
+                    }
+                } else {
+                    redact(field);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact),
+        _ => {}
+    }
+}
+
+/// Returns the configuration, loading it on first use.
+pub fn config() -> &'static Config {
+    #[cfg(test)]
+    if let Some(config) = tests::thread_config() { return config }
+
+    CONFIG.get_or_init(load_config)
+}
+
+/// Returns the configuration if it is already loaded, without loading it.
+///
+/// For code run while loading the config, e.g. opening the log file.
+pub fn loaded_config() -> Option<&'static Config> {
+    CONFIG.get()
+}
+
+/// Saves the fields of config that differ from the loaded one atomically
+/// to config.json, e.g. watch after --watch add.
+///
+/// Only the changed fields are written, to the top level or with a profile
+/// other than the default to its section in profiles. Everything else in
+/// config.json stays as the user wrote it, defaults are not filled in.
+pub fn save_config(config: &Config) -> std::io::Result<()> {
+    let root = match fs::read_to_string(config_path()) {
+        Ok(contents) => serde_json::from_str(&contents)?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Value::Object(Map::new()),
+        Err(e) => return Err(e),
+    };
+
+    let root = with_changes(root, crate::config::config(), config, profile())?;
+    write_atomically(config_path(), &serde_json::to_string_pretty(&root)?)
+}
+
+/// Sets the fields of changed that differ from loaded in root, the content
+/// of config.json, at the top level or in the section of profile.
+fn with_changes(mut root: Value, loaded: &Config, changed: &Config, profile: &str) -> std::io::Result<Value> {
+    let loaded = fields(loaded)?;
+    let changed = fields(changed)?
+        .into_iter()
+        .filter(|(key, value)| key != "profiles" && loaded.get(key) != Some(value));
+
+    let Value::Object(root_fields) = &mut root else {
+        return Err(std::io::Error::other("config.json is not a JSON object"));
+    };
+
+    let section = if profile == DEFAULT_PROFILE {
+        root_fields
+    } else {
+        let profiles = root_fields.entry("profiles").or_insert_with(|| Value::Object(Map::new()));
+        let Value::Object(profiles) = profiles else {
+            return Err(std::io::Error::other("profiles in config.json is not a JSON object"));
+        };
+        match profiles.entry(profile).or_insert_with(|| Value::Object(Map::new())) {
+            Value::Object(section) => section,
+            _ => return Err(std::io::Error::other(format!("Profile {} in config.json is not a JSON object", profile))),
+        }
+    };
+    section.extend(changed);
+
+    Ok(root)
+}
+
+/// Fields of the config serialized as JSON object.
+fn fields(config: &Config) -> serde_json::Result<Map<String, Value>> {
+    match serde_json::to_value(config)? {
+        Value::Object(fields) => Ok(fields),
+        _ => unreachable!("Config serializes as struct"),
+    }
+}
+
+/// Deserializes config.json or returns the defaults if it does not exist.
+///
+/// With a profile other than the default, the fields of its section in
+/// profiles override the top level ones.
+///
+/// Terminates and logs errors if config.json exists, but fails
+/// to read it or parse it, or the profile has no section.
+fn load_config() -> Config {
+    let path = config_path();
+
+    if !path.exists() {
+        if profile() != DEFAULT_PROFILE {
+            log_error(&format!("Profile {} not found, there is no config file.", profile()));
+            std::process::exit(1);
+        }
+        return Config::default()
+    }
+
+    let contents = match fs::read_to_string(path) {
+        Ok(c) => c,
+        Err(e) => {
+            log_error(&format!("Failed to read config file: {}", e));
+            std::process::exit(1);
+        }
+    };
+
+    let config = serde_json::from_str(&contents)
+        .and_then(|root| match with_profile(root, profile()) {
+            Some(root) => serde_json::from_value(root),
+            None => {
+                log_error(&format!("Profile {} not found in config file.", profile()));
+                std::process::exit(1);
+            }
+        });
+
+    match config {
+        Ok(config) => config,
+        Err(e) => {
+            log_error(&format!("Failed to parse config JSON: {}", e));
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Returns the top level config with the fields of the profile section
+/// replacing the top level ones, None if the profile has no section.
+fn with_profile(mut root: Value, profile: &str) -> Option<Value> {
+    if profile == DEFAULT_PROFILE { return Some(root) }
+
+    let Value::Object(fields) = &mut root else { return None };
+    let Some(Value::Object(section)) = fields.get("profiles").and_then(|p| p.get(profile)).cloned() else {
+        return None
+    };
+
+    fields.extend(section);
+    Some(root)
+}
+
+/// (De)serializes file permissions as octal string, e.g. "600".
+mod octal_mode {
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(mode: &u32, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("{:o}", mode))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u32, D::Error> {
+        let mode = String::deserialize(deserializer)?;
+
+        match u32::from_str_radix(&mode, 8) {
+            Ok(parsed) if parsed <= 0o777 => Ok(parsed),
+            _ => Err(de::Error::custom(format!("invalid file_mode {}, expected octal like \"600\"", mode))),
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::cell::Cell;
+
+    thread_local! {
+        /// Config returned by config() on this thread, see with_config.
+        static THREAD_CONFIG: Cell<Option<&'static Config>> = const { Cell::new(None) };
+    }
+
+    pub fn thread_config() -> Option<&'static Config> {
+        THREAD_CONFIG.with(Cell::get)
+    }
+
+    /// Runs f with config() returning config on this thread,
+    /// so tests running in parallel each see their own config.
+    pub fn with_config<T>(config: Config, f: impl FnOnce() -> T) -> T {
+        let previous = THREAD_CONFIG.with(|current| current.replace(Some(Box::leak(Box::new(config)))));
+        let result = f();
+        THREAD_CONFIG.with(|current| current.set(previous));
+        result
+    }
+
+    #[test]
+    fn profile_fields_override_top_level() {
+        let root = json!({ "log_level": "info", "watch": ["Div. 2"], "profiles": { "coaching": { "watch": ["ICPC"] } } });
+
+        let coaching: Config = serde_json::from_value(with_profile(root.clone(), "coaching").unwrap()).unwrap();
+        let default: Config = serde_json::from_value(with_profile(root.clone(), DEFAULT_PROFILE).unwrap()).unwrap();
+
+        assert_eq!(coaching.watch, ["ICPC"]);
+        assert_eq!(coaching.log_level, Level::Info);
+        assert_eq!(default.watch, ["Div. 2"]);
+        assert!(with_profile(root, "work").is_none());
+    }
+
+    #[test]
+    fn saves_only_changed_fields() {
+        let root = json!({ "log_level": "info" });
+        let loaded: Config = serde_json::from_value(root.clone()).unwrap();
+        let changed = Config { watch: vec!["Educational".to_string()], ..loaded.clone() };
+
+        let saved = with_changes(root, &loaded, &changed, DEFAULT_PROFILE).unwrap();
+
+        assert_eq!(saved, json!({ "log_level": "info", "watch": ["Educational"] }));
+    }
+
+    #[test]
+    fn saves_changes_of_a_profile_in_its_section() {
+        let root = json!({ "watch": ["Div. 2"], "profiles": { "coaching": { "lead_times_seconds": [3600] } } });
+        let loaded: Config = serde_json::from_value(with_profile(root.clone(), "coaching").unwrap()).unwrap();
+        let changed = Config { pinned: vec![PinnedContest::Id(2100)], ..loaded.clone() };
+
+        let saved = with_changes(root, &loaded, &changed, "coaching").unwrap();
+
+        assert_eq!(saved, json!({
+            "watch": ["Div. 2"],
+            "profiles": { "coaching": { "lead_times_seconds": [3600], "pinned": [2100] } },
+        }));
+    }
+
+    #[test]
+    fn redacts_secrets_only() {
+        let config: Config = serde_json::from_value(json!({
+            "telegram": { "bot_token": "123:abc", "chat_id": "42" },
+            "caldav": { "calendar_url": "https://dav.example.org/contests/", "username": "me", "password": "hunter2" },
+            "webhook": { "url": "https://hooks.example.org/secret", "headers": { "Authorization": "Bearer xyz" } },
+            "sentry_dsn": "https://key@sentry.example.org/1",
+            "profiles": { "work": { "pushbullet_token": "o.abc" } },
+        })).unwrap();
+
+        let value = redacted(&config).unwrap();
+        let printed = value.to_string();
+
+        for secret in ["123:abc", "hunter2", "hooks.example.org", "Bearer xyz", "key@sentry", "o.abc"] {
+            assert!(!printed.contains(secret), "{} is shown", secret);
+        }
+        assert_eq!(value["telegram"]["bot_token"], REDACTED);
+        assert_eq!(value["telegram"]["chat_id"], "42");
+        assert_eq!(value["caldav"]["username"], "me");
+        assert_eq!(value["caldav"]["calendar_url"], "https://dav.example.org/contests/");
+        assert_eq!(value["profiles"]["work"]["pushbullet_token"], REDACTED);
+    }
+
+    #[test]
+    fn unset_secrets_stay_null() {
+        let value = redacted(&Config::default()).unwrap();
+
+        assert_eq!(value["healthcheck_url"], Value::Null);
+        assert_eq!(value["discord_webhook_url"], Value::Null);
+    }
+
+    #[test]
+    fn file_mode_is_octal() {
+        let config: Config = serde_json::from_value(json!({ "file_mode": "640" })).unwrap();
+
+        assert_eq!(config.file_mode, 0o640);
+        assert!(serde_json::from_value::<Config>(json!({ "file_mode": "800" })).is_err());
+        assert!(serde_json::from_value::<Config>(json!({ "file_mode": "1777" })).is_err());
+    }
+}
@@ -0,0 +1,110 @@
+use crate::config::config;
+use crate::contest::Contest;
+use crate::ics::{parse_date_time, parse_duration, parse_events, property, unescape};
+use crate::local::log_error;
+
+use std::fs;
+use std::sync::OnceLock;
+
+/// Events of conflict_calendars, read once per run.
+static EVENTS: OnceLock<Vec<Event>> = OnceLock::new();
+
+/// Timed event of a calendar, in seconds since Unix epoch.
+#[derive(Debug)]
+struct Event {
+    summary: String,
+    start: i64,
+    end: i64,
+}
+
+/// Returns the summaries of the events in conflict_calendars overlapping
+/// the contest, from its start to its end.
+///
+/// All-day events and repetitions of recurring events are not considered.
+pub fn conflicts(contest: &Contest) -> Vec<String> {
+    let Some(start) = contest.start_time_seconds else { return Vec::new() };
+    let end = start + contest.duration_seconds.unwrap_or_default();
+
+    overlapping(EVENTS.get_or_init(load_events), start, end)
+}
+
+/// Returns the summaries of the events overlapping start to end.
+fn overlapping(events: &[Event], start: i64, end: i64) -> Vec<String> {
+    events
+        .iter()
+        .filter(|event| event.start < end && start < event.end)
+        .map(|event| event.summary.clone())
+        .collect()
+}
+
+/// Reads the events of every calendar in conflict_calendars.
+/// Calendars that can not be read are logged and skipped.
+fn load_events() -> Vec<Event> {
+    let mut events = Vec::new();
+
+    for path in &config().conflict_calendars {
+        match fs::read_to_string(path) {
+            Ok(ics) => events.extend(parse_events(&ics).values().filter_map(|lines| event(lines))),
+            Err(e) => log_error(&format!("Failed to read calendar {} for conflicts. {}", path.display(), e)),
+        }
+    }
+    events
+}
+
+/// Parses the content lines of a VEVENT, None for all-day events
+/// and events ending too far in the future to represent.
+fn event(lines: &[String]) -> Option<Event> {
+    let property = |name: &str| property(lines, name);
+
+    let start = parse_date_time(property("DTSTART")?)?;
+    let end = match (property("DTEND"), property("DURATION")) {
+        (Some(end), _) => parse_date_time(end)?,
+        (None, Some(duration)) => start.checked_add(parse_duration(duration)?)?,
+        (None, None) => start,
+    };
+    let summary = property("SUMMARY").map_or("Busy".to_string(), unescape);
+
+    Some(Event { summary, start, end })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|line| line.to_string()).collect()
+    }
+
+    #[test]
+    fn event_ends_at_dtend_or_after_duration() {
+        let with_end = event(&lines(&["DTSTART:20250428T140000Z", "DTEND:20250428T150000Z", r"SUMMARY:Team meeting\, weekly"])).unwrap();
+        let with_duration = event(&lines(&["DTSTART;VALUE=DATE-TIME:20250428T140000Z", "DURATION:PT30M"])).unwrap();
+        let instant = event(&lines(&["DTSTART:20250428T140000Z"])).unwrap();
+
+        assert_eq!((with_end.start, with_end.end), (1745848800, 1745852400));
+        assert_eq!(with_end.summary, "Team meeting, weekly");
+        assert_eq!(with_duration.end - with_duration.start, 1800);
+        assert_eq!(with_duration.summary, "Busy");
+        assert_eq!(instant.end, instant.start);
+    }
+
+    #[test]
+    fn all_day_and_unrepresentable_events_are_skipped() {
+        assert!(event(&lines(&["DTSTART;VALUE=DATE:20250428", "SUMMARY:Holiday"])).is_none());
+        assert!(event(&lines(&["SUMMARY:No start"])).is_none());
+        assert!(event(&lines(&["DTSTART:20250428T140000Z", &format!("DURATION:PT{}S", i64::MAX)])).is_none());
+    }
+
+    #[test]
+    fn touching_events_do_not_conflict() {
+        let events = [
+            Event { summary: "Before".to_string(), start: 0, end: 1_000 },
+            Event { summary: "During".to_string(), start: 1_500, end: 1_600 },
+            Event { summary: "Overlapping end".to_string(), start: 2_999, end: 4_000 },
+            Event { summary: "After".to_string(), start: 3_000, end: 4_000 },
+        ];
+
+        assert_eq!(overlapping(&events, 1_000, 3_000), ["During", "Overlapping end"]);
+        assert!(overlapping(&[], 1_000, 3_000).is_empty());
+    }
+}
@@ -1,6 +1,17 @@
+use crate::config::DifficultyTier;
+
 use serde::{Serialize, Deserialize};
+use chrono::{Utc, TimeZone};
+use std::collections::BTreeMap;
 use std::hash::{Hash, Hasher};
 
+/// Base URL of the Codeforces API, the source of contests without a source.
+pub const CODEFORCES_API: &str = "https://codeforces.com/api";
+
+/// Source of the gym contests of the Codeforces API, added to the
+/// sources if include_gym is set in the config.
+pub const CODEFORCES_GYM: &str = "https://codeforces.com/gym";
+
 /// Possible phases for a Codeforces contest.
 /// Before is the only relevant phase for upcoming contests.
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
@@ -11,6 +22,21 @@ pub enum Phase {
     PendingSystemTest,
     SystemTest,
     Finished,
+    /// Any phase added by Codeforces later. Never tracked.
+    #[serde(other)]
+    Unknown,
+}
+
+impl Phase {
+    /// Returns true for the phases reminders are kept for.
+    /// Running contests only count if include_running is set.
+    pub fn is_tracked(&self, include_running: bool) -> bool {
+        match self {
+            Phase::Before => true,
+            Phase::Coding => include_running,
+            _ => false,
+        }
+    }
 }
 
 /// Struct representing a contest.
@@ -21,27 +47,252 @@ pub struct Contest {
     pub id: usize,
     /// Contest name.
     pub name: String,
+    /// Scoring type of the contest: CF, IOI or ICPC.
+    #[serde(rename = "type")]
+    pub contest_type: Option<String>,
     /// Contest phase. Phase::Before means upcoming contest.
     pub phase: Phase,
     /// Start time in seconds (Unix epoch).
     pub start_time_seconds: Option<i64>,
+    /// Duration of the contest in seconds.
+    pub duration_seconds: Option<i64>,
     /// Description of the contest.
     pub description: Option<String>,
+    /// Description scraped from the contest page if the API provides none.
+    /// Only set locally, so it is saved and never fetched twice.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scraped_description: Option<String>,
+    /// Ids of the reminders created for this contest, as returned by Reminders.
+    /// Only set locally, empty for contests saved before ids were kept.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reminder_ids: Vec<String>,
+    /// Ids of what the notifiers after the first in notifiers delivered,
+    /// by backend name. The first one uses reminder_ids.
+    /// Only set locally.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub delivery_ids: BTreeMap<String, Vec<String>>,
+    /// Backends in notifiers that failed to deliver this contest while
+    /// others succeeded, so only they are notified again next run.
+    /// Only set locally.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failed_deliveries: Vec<String>,
+    /// Base URL of the API the contest was fetched from, None for Codeforces.
+    /// Ids are only unique within a source.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Lead time set with --snooze, replacing lead_times_seconds for this contest.
+    /// Only set locally, so later runs keep the snoozed reminder.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lead_time_override: Option<i64>,
+    /// Link to the contest page for sources that are not Codeforces,
+    /// e.g. AtCoder, whose pages are not found by id.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page_url: Option<String>,
+}
+
+impl Contest {
+    /// Returns true for contests of the Codeforces API, whose ids grow with
+    /// the rounds added. Ids of other sources, e.g. gyms or ids derived
+    /// from slugs, follow an order of their own.
+    pub fn is_from_codeforces_api(&self) -> bool {
+        self.source.is_none()
+    }
+
+    /// Returns true if the contest has the id and, if given, was fetched
+    /// from source, e.g. for an id given on the command line.
+    pub fn is(&self, id: usize, source: Option<&str>) -> bool {
+        self.id == id && source.is_none_or(|source| self.source_url() == source.trim_end_matches('/'))
+    }
+
+    /// Returns the API description, or the scraped one if there is none.
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref().or(self.scraped_description.as_deref())
+    }
+
+    /// Infers the difficulty from the contest name using the first
+    /// tier whose pattern is contained in it, ignoring case.
+    pub fn difficulty<'a>(&self, tiers: &'a [DifficultyTier]) -> Option<&'a str> {
+        let name = self.name.to_lowercase();
+
+        tiers
+            .iter()
+            .find(|tier| name.contains(&tier.pattern.to_lowercase()))
+            .map(|tier| tier.difficulty.as_str())
+    }
+
+    /// Returns the upcoming version of this contest, keeping
+    /// the fields that are only known locally.
+    pub fn updated_from(&self, upcoming: Contest) -> Contest {
+        Contest {
+            scraped_description: self.scraped_description.clone(),
+            reminder_ids: self.reminder_ids.clone(),
+            delivery_ids: self.delivery_ids.clone(),
+            failed_deliveries: self.failed_deliveries.clone(),
+            lead_time_override: self.lead_time_override,
+            ..upcoming
+        }
+    }
+
+    /// Returns the contest with its source set to base_url,
+    /// or None if base_url is the Codeforces API.
+    pub fn with_source(self, base_url: &str) -> Contest {
+        let source = if base_url == CODEFORCES_API { None } else { Some(base_url.to_string()) };
+        Contest { source, ..self }
+    }
+
+    /// Base URL of the API the contest was fetched from.
+    pub fn source_url(&self) -> &str {
+        self.source.as_deref().unwrap_or(CODEFORCES_API)
+    }
+
+    /// Host of the API the contest was fetched from, e.g. "codeforces.com".
+    pub fn source_host(&self) -> &str {
+        self.source_url()
+            .trim_start_matches("https://")
+            .trim_start_matches("http://")
+            .split('/')
+            .next()
+            .unwrap_or_default()
+    }
+
+    /// Link to the contest page, on Codeforces unless the source set one.
+    pub fn url(&self) -> String {
+        match &self.page_url {
+            Some(url) => url.clone(),
+            None => format!("https://codeforces.com/contests/{}", self.id),
+        }
+    }
+
+    /// Short label for reminder titles, e.g. "ECR178" for
+    /// "Educational Codeforces Round 178 (Rated for Div. 2)" or
+    /// "CF1000-D2" for "Codeforces Round 1000 (Div. 2)".
+    ///
+    /// Falls back to "#id" for names that follow no known pattern.
+    pub fn short_label(&self) -> String {
+        const SERIES: [(&str, &str); 3] = [
+            ("Educational Codeforces Round ", "ECR"),
+            ("Codeforces Global Round ", "CGR"),
+            ("Codeforces Round ", "CF"),
+        ];
+
+        for (prefix, label) in SERIES {
+            let Some(number) = number_after(&self.name, prefix) else { continue };
+
+            let division = (1..=4).find(|div| self.name.contains(&format!("(Div. {})", div)));
+            return match (label, division) {
+                ("CF", Some(div)) => format!("CF{}-D{}", number, div),
+                _ => format!("{}{}", label, number),
+            };
+        }
+
+        format!("#{}", self.id)
+    }
+
+    /// Compares everything but the id, i.e. whether a contest
+    /// with the same id changed between two fetches.
+    pub fn same_content(&self, other: &Self) -> bool {
+        self.name == other.name
+            && self.contest_type == other.contest_type
+            && self.phase == other.phase
+            && self.start_time_seconds == other.start_time_seconds
+            && self.duration_seconds == other.duration_seconds
+            && self.description == other.description
+            && self.page_url == other.page_url
+    }
+}
+
+/// Returns the source of the contests with the id, of source if given.
+///
+/// Fails naming the sources if contests of several sources have the id,
+/// so a command given only the id never picks one of them at random.
+pub fn source_of_id<'a>(contests: impl IntoIterator<Item = &'a Contest>, id: usize, source: Option<&str>) -> Result<Option<String>, String> {
+    let mut sources: Vec<&str> = contests
+        .into_iter()
+        .filter(|contest| contest.is(id, source))
+        .map(|contest| contest.source_url())
+        .collect();
+    sources.sort_unstable();
+    sources.dedup();
+
+    match sources.as_slice() {
+        [] => Ok(None),
+        [source] => Ok(Some(source.to_string())),
+        _ => Err(format!("Contests of several sources have the id {}: {}. Choose one with --source.", id, sources.join(", "))),
+    }
+}
+
+/// Returns the contest starting soonest at or after now
+/// (seconds since Unix epoch), ignoring contests without start time.
+pub fn next_contest<'a>(contests: impl IntoIterator<Item = &'a Contest>, now: i64) -> Option<&'a Contest> {
+    contests
+        .into_iter()
+        .filter(|contest| contest.start_time_seconds.is_some_and(|start| start >= now))
+        .min_by_key(|contest| (contest.start_time_seconds, contest.id))
+}
+
+/// Smallest id of contests from judges identifying them by slug,
+/// far above every Codeforces contest id.
+pub const SLUG_IDS_START: usize = 1_000_000_000;
+
+/// Id of a contest from the judge at source identifying its contests
+/// by slug, e.g. "abc400" on AtCoder.
+///
+/// The FNV-1a hash of source and slug, so the id stays the same across
+/// runs and Rust versions, in the range above SLUG_IDS_START that still
+/// fits 32 bit targets. Hashing the source keeps the same slug on two
+/// judges apart on commands only taking an id, e.g. --pin.
+pub fn slug_id(source: &str, slug: &str) -> usize {
+    let hash = format!("{}/{}", source, slug).bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+    SLUG_IDS_START + (hash % 3_000_000_000) as usize
+}
+
+/// Returns the round number following prefix in name,
+/// allowing a '#' before it (e.g. "Codeforces Round #912").
+fn number_after<'a>(name: &'a str, prefix: &str) -> Option<&'a str> {
+    let rest = &name[name.find(prefix)? + prefix.len()..];
+    let rest = rest.strip_prefix('#').unwrap_or(rest);
+    let digits = rest.chars().take_while(|c| c.is_ascii_digit()).count();
+
+    if digits == 0 { None } else { Some(&rest[..digits]) }
+}
+
+/// Formats seconds since Unix epoch as local time, e.g. "25/04/2025 16:35 CEST".
+pub fn format_local_time(seconds: i64) -> String {
+    match Utc.timestamp_opt(seconds, 0).single() {
+        Some(time) => time.with_timezone(&chrono::Local).format("%d/%m/%Y %H:%M %Z").to_string(),
+        None => format!("{} seconds since Unix epoch", seconds),
+    }
 }
 
-/// Hashing based on id.
+/// Formats a number of seconds as e.g. "1 hour 30 minutes".
+pub fn format_duration(seconds: i64) -> String {
+    let hours = seconds / 3600;
+    let minutes = seconds % 3600 / 60;
+    let plural = |n: i64| if n == 1 { "" } else { "s" };
+
+    match (hours, minutes) {
+        (0, m) => format!("{} minute{}", m, plural(m)),
+        (h, 0) => format!("{} hour{}", h, plural(h)),
+        (h, m) => format!("{} hour{} {} minute{}", h, plural(h), m, plural(m)),
+    }
+}
+
+/// Hashing based on source and id.
 impl Hash for Contest {
     fn hash<H: Hasher>(&self, state: &mut H) {
+        self.source.hash(state);
         self.id.hash(state);
     }
 }
 
 impl Eq for Contest {}
 
-/// Comparison based on id.
+/// Comparison based on source and id.
 impl PartialEq for Contest {
     fn eq(&self, other: &Self) -> bool {
-        self.id == other.id
+        self.source == other.source && self.id == other.id
     }
 }
 
@@ -55,3 +306,150 @@ pub struct ContestResponse {
     /// Retrieved contests.
     pub result: Vec<Contest>,
 }
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    /// Upcoming Codeforces contest with the id, name and start time,
+    /// lasting two hours, for the tests of every module.
+    pub fn contest(id: usize, name: &str, start: i64) -> Contest {
+        Contest {
+            id,
+            name: name.to_string(),
+            contest_type: Some("CF".to_string()),
+            phase: Phase::Before,
+            start_time_seconds: Some(start),
+            duration_seconds: Some(7200),
+            description: None,
+            scraped_description: None,
+            reminder_ids: Vec::new(),
+            delivery_ids: BTreeMap::new(),
+            failed_deliveries: Vec::new(),
+            source: None,
+            lead_time_override: None,
+            page_url: None,
+        }
+    }
+
+    #[test]
+    fn same_content_ignores_local_fields() {
+        let fetched = contest(1, "Codeforces Round 1000 (Div. 2)", 1_000);
+        let saved = Contest { reminder_ids: vec!["x-apple-reminder://1".to_string()], ..fetched.clone() };
+
+        assert!(saved.same_content(&fetched));
+        assert!(!saved.same_content(&Contest { start_time_seconds: Some(2_000), ..fetched.clone() }));
+        assert!(!saved.same_content(&Contest { name: "Codeforces Round 1001 (Div. 2)".to_string(), ..fetched }));
+    }
+
+    #[test]
+    fn short_label_of_known_series() {
+        let label = |name: &str| contest(2100, name, 0).short_label();
+
+        assert_eq!(label("Educational Codeforces Round 178 (Rated for Div. 2)"), "ECR178");
+        assert_eq!(label("Codeforces Round 1000 (Div. 2)"), "CF1000-D2");
+        assert_eq!(label("Codeforces Round #912 (Div. 1)"), "CF912-D1");
+        assert_eq!(label("Codeforces Round 1001"), "CF1001");
+        assert_eq!(label("Codeforces Global Round 28"), "CGR28");
+    }
+
+    #[test]
+    fn short_label_falls_back_to_id() {
+        assert_eq!(contest(2100, "Kotlin Heroes: Episode 12", 0).short_label(), "#2100");
+        assert_eq!(contest(2101, "Codeforces Round (Div. 2)", 0).short_label(), "#2101");
+    }
+
+    #[test]
+    fn running_contests_tracked_only_if_included() {
+        assert!(Phase::Before.is_tracked(false));
+        assert!(!Phase::Coding.is_tracked(false));
+        assert!(Phase::Coding.is_tracked(true));
+
+        for phase in [Phase::PendingSystemTest, Phase::SystemTest, Phase::Finished] {
+            assert!(!phase.is_tracked(true));
+        }
+    }
+
+    #[test]
+    fn next_contest_starts_soonest_from_now() {
+        let contests = [
+            contest(1, "Started", 900),
+            contest(2, "Later", 3_000),
+            contest(3, "Soonest", 2_000),
+            Contest { start_time_seconds: None, ..contest(4, "Unknown start", 0) },
+        ];
+
+        assert_eq!(next_contest(&contests, 1_000).map(|contest| contest.id), Some(3));
+        assert_eq!(next_contest(&contests, 2_000).map(|contest| contest.id), Some(3));
+        assert_eq!(next_contest(&contests, 2_001).map(|contest| contest.id), Some(2));
+        assert!(next_contest(&contests, 3_001).is_none());
+    }
+
+    #[test]
+    fn id_of_several_sources_needs_a_source() {
+        let contests = [
+            contest(400, "Codeforces Round 400", 0),
+            contest(400, "abc400", 0).with_source("https://atcoder.jp"),
+            contest(401, "Codeforces Round 401", 0),
+        ];
+
+        assert!(source_of_id(&contests, 400, None).unwrap_err().contains("https://atcoder.jp"));
+        assert_eq!(source_of_id(&contests, 400, Some("https://atcoder.jp/")).unwrap().as_deref(), Some("https://atcoder.jp"));
+        assert_eq!(source_of_id(&contests, 401, None).unwrap().as_deref(), Some(CODEFORCES_API));
+        assert_eq!(source_of_id(&contests, 402, None), Ok(None));
+    }
+
+    #[test]
+    fn slug_ids_are_stable_and_apart_from_codeforces() {
+        // Saved in contests.json and pinned by users, so it must never change.
+        assert_eq!(slug_id("https://atcoder.jp", "abc400"), 3_978_674_381);
+        assert_eq!(slug_id("https://atcoder.jp", "abc400"), slug_id("https://atcoder.jp", "abc400"));
+        assert_ne!(slug_id("https://atcoder.jp", "abc400"), slug_id("https://atcoder.jp", "abc401"));
+        assert_ne!(slug_id("https://atcoder.jp", "abc400"), slug_id("https://www.codechef.com", "abc400"));
+        for slug in ["abc400", "arc195", "agc072", ""] {
+            let id = slug_id("https://atcoder.jp", slug);
+            assert!((SLUG_IDS_START..SLUG_IDS_START + 3_000_000_000).contains(&id), "{}", id);
+        }
+    }
+
+    #[test]
+    fn durations_in_hours_and_minutes() {
+        assert_eq!(format_duration(0), "0 minutes");
+        assert_eq!(format_duration(60), "1 minute");
+        assert_eq!(format_duration(3600), "1 hour");
+        assert_eq!(format_duration(7200), "2 hours");
+        assert_eq!(format_duration(3660), "1 hour 1 minute");
+        assert_eq!(format_duration(8100 + 59), "2 hours 15 minutes");
+    }
+
+    #[test]
+    fn snooze_survives_saving_and_updates() {
+        let snoozed = Contest { lead_time_override: Some(300), ..contest(1, "Round", 1_000) };
+
+        let saved: Contest = serde_json::from_str(&serde_json::to_string(&snoozed).unwrap()).unwrap();
+        assert_eq!(saved.lead_time_override, Some(300));
+
+        let updated = saved.updated_from(contest(1, "Round", 2_000));
+        assert_eq!(updated.lead_time_override, Some(300));
+        assert_eq!(updated.start_time_seconds, Some(2_000));
+    }
+
+    #[test]
+    fn out_of_range_times_are_formatted_as_seconds() {
+        assert_eq!(format_local_time(i64::MAX), format!("{} seconds since Unix epoch", i64::MAX));
+    }
+
+    #[test]
+    fn difficulty_from_first_matching_tier() {
+        let tiers = crate::config::Config::default().difficulty_tiers;
+        let difficulty = |name: &str| contest(1, name, 0).difficulty(&tiers).map(str::to_string);
+
+        assert_eq!(difficulty("Codeforces Round 1000 (Div. 1 + Div. 2)").as_deref(), Some("all ratings"));
+        assert_eq!(difficulty("Codeforces Round 999 (Div. 1)").as_deref(), Some("~1900+"));
+        assert_eq!(difficulty("Educational Codeforces Round 178 (Rated for Div. 2)").as_deref(), Some("~1600"));
+        assert_eq!(difficulty("Codeforces Round 1002 (DIV. 3)").as_deref(), Some("~1200"));
+        assert_eq!(difficulty("Codeforces Round 1003 (Div. 4)").as_deref(), Some("~1000"));
+        assert_eq!(difficulty("Codeforces Global Round 28").as_deref(), Some("all ratings"));
+        assert_eq!(difficulty("Kotlin Heroes: Practice 12"), None);
+    }
+}
@@ -0,0 +1,184 @@
+use crate::contest::{Contest, format_local_time};
+
+use std::collections::HashSet;
+use std::fmt::Write;
+
+/// Result of comparing the locally saved contests with the
+/// upcoming contests retrieved from Codeforces.
+#[derive(Debug, Default)]
+pub struct ContestDiff {
+    /// Upcoming contests that are not saved locally yet.
+    pub new: Vec<Contest>,
+    /// Locally saved contests that are no longer upcoming.
+    pub finished: Vec<Contest>,
+    /// Contests present in both sets whose content differs, as (local, upcoming).
+    pub changed: Vec<(Contest, Contest)>,
+    /// Locally saved contests that are still upcoming and unchanged.
+    pub unchanged: Vec<Contest>,
+    /// New contests excluded by the filters in the config.
+    pub skipped: Vec<Contest>,
+}
+
+/// Splits local and upcoming contests into new, finished, changed and unchanged ones.
+///
+/// Filters are not applied here, see filter::skip_filtered.
+///
+/// Every group is sorted by start time (then id) so the result does not
+/// depend on HashSet iteration order.
+pub fn compute_contest_diff(local: &HashSet<Contest>, upcoming: &HashSet<Contest>) -> ContestDiff {
+    let mut diff = ContestDiff::default();
+
+    for contest in upcoming {
+        match local.get(contest) {
+            None => diff.new.push(contest.clone()),
+            Some(saved) if saved.same_content(contest) => diff.unchanged.push(saved.clone()),
+            Some(saved) => diff.changed.push((saved.clone(), contest.clone())),
+        }
+    }
+
+    diff.finished = local
+        .iter()
+        .filter(|contest| !upcoming.contains(contest))
+        .cloned()
+        .collect();
+
+    let key = |contest: &Contest| (contest.start_time_seconds, contest.id);
+    diff.new.sort_by_key(key);
+    diff.finished.sort_by_key(key);
+    diff.unchanged.sort_by_key(key);
+    diff.changed.sort_by_key(|(_, upcoming)| key(upcoming));
+
+    diff
+}
+
+/// Builds the human-readable report printed by --diff.
+pub fn format_diff_report(diff: &ContestDiff) -> String {
+    let mut report = String::new();
+
+    let _ = writeln!(report, "New contests ({}):", diff.new.len());
+    for contest in &diff.new {
+        let _ = writeln!(report, "  + {}", describe(contest));
+    }
+
+    if !diff.skipped.is_empty() {
+        let _ = writeln!(report, "Skipped by filters ({}):", diff.skipped.len());
+        for contest in &diff.skipped {
+            let _ = writeln!(report, "  x {}", describe(contest));
+        }
+    }
+
+    let _ = writeln!(report, "Finished contests ({}):", diff.finished.len());
+    for contest in &diff.finished {
+        let _ = writeln!(report, "  - {}", describe(contest));
+    }
+
+    let _ = writeln!(report, "Changed contests ({}):", diff.changed.len());
+    for (local, upcoming) in &diff.changed {
+        let _ = writeln!(report, "  ~ {}, id: {}", upcoming.name, upcoming.id);
+        if local.name != upcoming.name {
+            let _ = writeln!(report, "      name: {} -> {}", local.name, upcoming.name);
+        }
+        if local.start_time_seconds != upcoming.start_time_seconds {
+            let _ = writeln!(report, "      start: {} -> {}",
+                start_time(local), start_time(upcoming));
+        }
+    }
+
+    let _ = writeln!(report, "Unchanged contests: {}", diff.unchanged.len());
+    report
+}
+
+fn describe(contest: &Contest) -> String {
+    format!("{}, id: {}, starts {}", contest.name, contest.id, start_time(contest))
+}
+
+fn start_time(contest: &Contest) -> String {
+    contest.start_time_seconds
+        .map(format_local_time)
+        .unwrap_or_else(|| "at unknown time".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contest::tests::contest;
+
+    fn set(contests: &[Contest]) -> HashSet<Contest> {
+        contests.iter().cloned().collect()
+    }
+
+    fn ids(contests: &[Contest]) -> Vec<usize> {
+        contests.iter().map(|contest| contest.id).collect()
+    }
+
+    #[test]
+    fn splits_contests_into_groups() {
+        let local = set(&[
+            contest(1, "Finished", 100),
+            contest(2, "Unchanged", 200),
+            contest(3, "Moved", 300),
+        ]);
+        let upcoming = set(&[
+            contest(2, "Unchanged", 200),
+            contest(3, "Moved", 350),
+            contest(5, "New later", 500),
+            contest(4, "New", 400),
+        ]);
+
+        let diff = compute_contest_diff(&local, &upcoming);
+
+        assert_eq!(ids(&diff.new), [4, 5]);
+        assert_eq!(ids(&diff.finished), [1]);
+        assert_eq!(ids(&diff.unchanged), [2]);
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].0.start_time_seconds, Some(300));
+        assert_eq!(diff.changed[0].1.start_time_seconds, Some(350));
+        assert!(diff.skipped.is_empty());
+    }
+
+    #[test]
+    fn same_id_of_other_source_is_another_contest() {
+        let local = set(&[contest(1, "Codeforces", 100)]);
+        let atcoder = Contest { source: Some("https://atcoder.jp".to_string()), ..contest(1, "AtCoder", 100) };
+        let upcoming = set(&[contest(1, "Codeforces", 100), atcoder]);
+
+        let diff = compute_contest_diff(&local, &upcoming);
+
+        assert_eq!(diff.new.len(), 1);
+        assert_eq!(diff.new[0].name, "AtCoder");
+        assert_eq!(ids(&diff.unchanged), [1]);
+    }
+
+    #[test]
+    fn report_lists_every_group() {
+        let diff = ContestDiff {
+            new: vec![contest(4, "New Round", 400)],
+            finished: vec![contest(1, "Old Round", 100)],
+            changed: vec![(contest(3, "Round", 300), contest(3, "Renamed Round", 300))],
+            unchanged: vec![contest(2, "Same", 200)],
+            skipped: Vec::new(),
+        };
+
+        let report = format_diff_report(&diff);
+
+        assert!(report.contains("New contests (1):\n  + New Round, id: 4, starts "));
+        assert!(report.contains("Finished contests (1):\n  - Old Round, id: 1, starts "));
+        assert!(report.contains("Changed contests (1):\n  ~ Renamed Round, id: 3\n      name: Round -> Renamed Round\n"));
+        assert!(!report.contains("      start:"));
+        assert!(!report.contains("Skipped by filters"));
+        assert!(report.ends_with("Unchanged contests: 1\n"));
+    }
+
+    #[test]
+    fn report_shows_skipped_and_unknown_start() {
+        let diff = ContestDiff {
+            skipped: vec![Contest { start_time_seconds: None, ..contest(7, "Filtered", 0) }],
+            ..ContestDiff::default()
+        };
+
+        let report = format_diff_report(&diff);
+
+        assert!(report.contains("Skipped by filters (1):\n  x Filtered, id: 7, starts at unknown time\n"));
+        assert!(report.starts_with("New contests (0):\n"));
+    }
+}
@@ -0,0 +1,182 @@
+use crate::api::{fetch_contests, tracked};
+use crate::config::config;
+use crate::contest::{source_of_id, Contest, format_local_time};
+use crate::filter::Filters;
+use crate::local::fetch_local_upcoming_contests;
+use crate::reminder::{contest_lead_times, due_time, registration_due};
+
+use std::collections::HashSet;
+
+/// Prints every decision a sync would take for the contest with the given
+/// id, of source if given.
+///
+/// Terminates with exit code 1 if contests of several sources have the id.
+pub fn explain_command(id: usize, source: Option<&str>, force: bool) {
+    // Only explains a sync, so last_fetch.json is left as it is.
+    let live = fetch_contests(force, false);
+    let local = fetch_local_upcoming_contests();
+    let now = chrono::Utc::now().timestamp();
+
+    let source = match source_of_id(live.iter().chain(&local), id, source) {
+        Ok(found) => found.or(source.map(str::to_string)),
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    for line in explain(id, source.as_deref(), &live, &local, now) {
+        println!("{}", line);
+    }
+}
+
+/// Traces the contest with the given id through the sync: lookup, dedup,
+/// phase filter, local cache, filters, lead times and the final action.
+/// Only contests of source are considered if it is given.
+pub fn explain(id: usize, source: Option<&str>, live: &[Contest], local: &HashSet<Contest>, now: i64) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    let matches: Vec<&Contest> = live.iter().filter(|contest| contest.is(id, source)).collect();
+    let upcoming = tracked(matches.iter().map(|contest| (*contest).clone()).collect());
+    let saved = local.iter().find(|contest| contest.is(id, source));
+
+    let Some(contest) = upcoming.iter().next().or(matches.first().copied()).or(saved) else {
+        lines.push(format!("Contest {}: not in the contest list nor saved locally.", id));
+        lines.push("Action: none".to_string());
+        return lines;
+    };
+
+    lines.push(format!("Contest {}: {}", id, contest.name));
+
+    match matches.len() {
+        0 => lines.push("Contest list: not found (finished or removed)".to_string()),
+        1 => lines.push(format!("Contest list: found, phase {:?}", matches[0].phase)),
+        n => lines.push(format!("Contest list: found {} entries with this id, kept {:?}", n,
+            upcoming.iter().next().map_or(matches[0].phase.clone(), |contest| contest.phase.clone()))),
+    }
+
+    let is_upcoming = !upcoming.is_empty();
+    if !matches.is_empty() {
+        lines.push(format!("Phase filter: {}", if is_upcoming { "passed" } else { "excluded, not upcoming" }));
+    }
+
+    lines.push(format!("Local cache: {}", if saved.is_some() { "saved" } else { "not saved" }));
+
+    let action = match (is_upcoming, saved) {
+        (true, None) => {
+            let filters = Filters::from_config(config());
+            let mut exclusion = None;
+
+            if filters.is_pinned(contest) {
+                lines.push("Pinned: yes, filters are bypassed".to_string());
+            }
+
+            for (name, result) in filters.checks(contest) {
+                match &result {
+                    Some(reason) => lines.push(format!("{}: excluded, {}", capitalize(name), reason)),
+                    None => lines.push(format!("{}: passed", capitalize(name))),
+                }
+                exclusion = exclusion.or(result);
+            }
+
+            lines.extend(lead_time_lines(contest, now));
+
+            match exclusion {
+                Some(reason) => format!("skip, {}", reason),
+                None if config().daily_summary => "add to the daily summary".to_string(),
+                None => "create reminder".to_string(),
+            }
+        }
+        (true, Some(saved)) => {
+            lines.push("Filters: not applied, contest already has a reminder".to_string());
+            lines.extend(lead_time_lines(&saved.updated_from(contest.clone()), now));
+
+            if saved.same_content(contest) { "none, reminder is up to date".to_string() }
+            else { "update reminder".to_string() }
+        }
+        (false, Some(_)) => "delete reminder".to_string(),
+        (false, None) => "none".to_string(),
+    };
+
+    lines.push(format!("Action: {}", action));
+    lines
+}
+
+/// Lists when each reminder of the contest is due, marking the ones in the past.
+fn lead_time_lines(contest: &Contest, now: i64) -> Vec<String> {
+    let Some(start) = contest.start_time_seconds else {
+        return vec!["Lead times: no start time, no reminder can be created".to_string()];
+    };
+
+    let past = |due: i64| if due < now { " (in the past)" } else { "" };
+
+    let mut lines: Vec<String> = contest_lead_times(contest)
+        .into_iter()
+        .map(|lead_time| {
+            let due = due_time(contest, start, lead_time);
+            format!("Lead time {}s: due {}{}", lead_time, format_local_time(due), past(due))
+        })
+        .collect();
+
+    if let Some(due) = registration_due(contest) {
+        lines.push(format!("Registration: due {}{}", format_local_time(due), past(due)));
+    }
+
+    lines
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::tests::with_config;
+    use crate::config::Config;
+    use crate::contest::tests::contest;
+
+    #[test]
+    fn names_the_excluding_filter() {
+        let config = Config { watch: vec!["Educational".to_string()], ..Config::default() };
+        let live = [contest(2100, "Codeforces Round 1020 (Div. 2)", 2_000_000)];
+
+        let lines = with_config(config, || explain(2100, None, &live, &HashSet::new(), 1_000_000));
+
+        assert!(lines.contains(&"Contest list: found, phase Before".to_string()));
+        assert!(lines.contains(&"Watch rules: excluded, no watch rule matches".to_string()));
+        assert!(lines.contains(&"Duration filter: passed".to_string()));
+        assert_eq!(lines.last().map(String::as_str), Some("Action: skip, no watch rule matches"));
+    }
+
+    #[test]
+    fn new_contest_gets_a_reminder() {
+        let live = [contest(2100, "Codeforces Round 1020 (Div. 2)", 2_000_000)];
+
+        let lines = with_config(Config::default(), || explain(2100, None, &live, &HashSet::new(), 1_000_000));
+
+        assert!(lines.iter().any(|line| line.starts_with("Lead time 1800s: due ") && !line.ends_with("(in the past)")));
+        assert_eq!(lines.last().map(String::as_str), Some("Action: create reminder"));
+    }
+
+    #[test]
+    fn saved_contest_no_longer_listed_is_deleted() {
+        let local: HashSet<Contest> = [contest(2100, "Round", 2_000_000)].into_iter().collect();
+
+        let lines = with_config(Config::default(), || explain(2100, None, &[], &local, 1_000_000));
+
+        assert!(lines.contains(&"Contest list: not found (finished or removed)".to_string()));
+        assert_eq!(lines.last().map(String::as_str), Some("Action: delete reminder"));
+    }
+
+    #[test]
+    fn unknown_contest() {
+        let lines = with_config(Config::default(), || explain(7, None, &[], &HashSet::new(), 0));
+
+        assert_eq!(lines, ["Contest 7: not in the contest list nor saved locally.", "Action: none"]);
+    }
+}
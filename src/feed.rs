@@ -0,0 +1,161 @@
+use crate::api::CodeforcesClient;
+use crate::contest::{slug_id, Contest, Phase};
+use crate::ics::{parse_date_time, parse_duration, parse_events, property, unescape};
+use crate::source::ContestSource;
+
+use chrono::{Local, NaiveDate};
+use reqwest::blocking::Response;
+use std::collections::BTreeMap;
+
+/// ICS feed in ics_sources, whose events are reminded of like contests.
+pub struct Feed {
+    url: String,
+}
+
+impl Feed {
+    pub fn new(url: &str) -> Self {
+        Feed { url: url.to_string() }
+    }
+}
+
+impl ContestSource for Feed {
+    fn url(&self) -> &str {
+        &self.url
+    }
+
+    fn request(&self, client: &CodeforcesClient, etag: Option<&str>) -> reqwest::Result<Response> {
+        client.get_if_none_match(&feed_url(&self.url), etag)
+    }
+
+    fn parse(&self, body: &str) -> Result<Vec<Contest>, String> {
+        parse_contests(body, &self.url, chrono::Utc::now().timestamp())
+    }
+}
+
+/// URL the ICS feed of a source in ics_sources is fetched from,
+/// with webcal:// as https://.
+fn feed_url(source: &str) -> String {
+    match source.strip_prefix("webcal://") {
+        Some(rest) => format!("https://{}", rest),
+        None => source.to_string(),
+    }
+}
+
+/// Returns the events of an ICS feed as contests of the source,
+/// finished ones in Phase::Finished.
+///
+/// The id is derived from the UID of the event, page_url is its URL or
+/// the feed itself. Events on whole days start at local midnight.
+/// Repetitions of recurring events are not considered, events ending
+/// too far in the future to represent are skipped.
+pub fn parse_contests(ics: &str, source: &str, now: i64) -> Result<Vec<Contest>, String> {
+    if !ics.trim_start().starts_with("BEGIN:VCALENDAR") {
+        return Err(format!("{} is not an ICS calendar.", source));
+    }
+
+    Ok(parse_events(ics)
+        .into_iter()
+        .filter_map(|(uid, lines)| contest(&uid, &lines, source, now))
+        .collect())
+}
+
+fn contest(uid: &str, lines: &[String], source: &str, now: i64) -> Option<Contest> {
+    let property = |name: &str| property(lines, name);
+    let parse_time = |value: &str| parse_date_time(value).or_else(|| parse_date(value));
+
+    let start = parse_time(property("DTSTART")?)?;
+    let end = match (property("DTEND"), property("DURATION")) {
+        (Some(end), _) => parse_time(end)?,
+        (None, Some(duration)) => start.checked_add(parse_duration(duration)?)?,
+        (None, None) => start,
+    };
+
+    let phase = if now < start {
+        Phase::Before
+    } else if now < end {
+        Phase::Coding
+    } else {
+        Phase::Finished
+    };
+
+    Some(Contest {
+        id: slug_id(source, uid),
+        name: property("SUMMARY").map_or("Untitled event".to_string(), unescape),
+        contest_type: None,
+        phase,
+        start_time_seconds: Some(start),
+        duration_seconds: Some(end - start),
+        description: property("DESCRIPTION").map(unescape),
+        scraped_description: None,
+        reminder_ids: Vec::new(),
+        delivery_ids: BTreeMap::new(),
+        failed_deliveries: Vec::new(),
+        source: Some(source.to_string()),
+        lead_time_override: None,
+        page_url: Some(property("URL").map_or_else(|| feed_url(source), str::to_string)),
+    })
+}
+
+/// Parses a DATE value like "20251018" as local midnight.
+fn parse_date(value: &str) -> Option<i64> {
+    let date = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+    date.and_hms_opt(0, 0, 0)?.and_local_timezone(Local).earliest().map(|time| time.timestamp())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "webcal://example.org/training.ics";
+
+    const FEED: &str = "BEGIN:VCALENDAR\r
+VERSION:2.0\r
+BEGIN:VEVENT\r
+UID:mashup-1@example.org\r
+DTSTART:20250428T140000Z\r
+DURATION:PT5H\r
+SUMMARY:Team training\\, gym mashup\r
+URL:https://codeforces.com/gym/105800\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:retro@example.org\r
+DTSTART:20250420T140000Z\r
+DTEND:20250420T160000Z\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:overflow@example.org\r
+DTSTART:20250428T140000Z\r
+DURATION:PT9223372036854775807S\r
+END:VEVENT\r
+END:VCALENDAR\r
+";
+
+    #[test]
+    fn events_become_contests_of_the_feed() {
+        let contests = parse_contests(FEED, SOURCE, 1745848800 - 1).unwrap();
+
+        assert_eq!(contests.len(), 2, "the overflowing event is skipped");
+        let training = contests.iter().find(|contest| contest.id == slug_id(SOURCE, "mashup-1@example.org")).unwrap();
+        assert_eq!(training.name, "Team training, gym mashup");
+        assert_eq!(training.phase, Phase::Before);
+        assert_eq!(training.duration_seconds, Some(5 * 3600));
+        assert_eq!(training.page_url.as_deref(), Some("https://codeforces.com/gym/105800"));
+        let retro = contests.iter().find(|contest| contest.id == slug_id(SOURCE, "retro@example.org")).unwrap();
+        assert_eq!(retro.name, "Untitled event");
+        assert_eq!(retro.phase, Phase::Finished);
+        assert_eq!(retro.page_url.as_deref(), Some("https://example.org/training.ics"));
+    }
+
+    #[test]
+    fn running_events_are_coding() {
+        let contests = parse_contests(FEED, SOURCE, 1745848800 + 60).unwrap();
+
+        let training = contests.iter().find(|contest| contest.id == slug_id(SOURCE, "mashup-1@example.org")).unwrap();
+        assert_eq!(training.phase, Phase::Coding);
+    }
+
+    #[test]
+    fn other_documents_are_rejected() {
+        assert!(parse_contests("<html>Not found</html>", SOURCE, 0).is_err());
+    }
+}
@@ -0,0 +1,308 @@
+use crate::config::{config, Config, PinnedContest};
+use crate::conflict::conflicts;
+use crate::contest::Contest;
+use crate::diff::ContestDiff;
+use crate::local::{log_error, log_info};
+use crate::status::high_water_mark;
+
+use regex::Regex;
+use std::fmt;
+
+/// Compiled watch rules from the config.
+///
+/// Every contest matches if there are no rules.
+pub struct WatchRules {
+    rules: Vec<Regex>,
+}
+
+impl WatchRules {
+    /// Compiles the patterns, logging and skipping invalid ones.
+    pub fn compile(patterns: &[String]) -> Self {
+        let rules = patterns
+            .iter()
+            .filter_map(|pattern| match Regex::new(pattern) {
+                Ok(regex) => Some(regex),
+                Err(e) => {
+                    log_error(&format!("Skipping invalid watch rule {}. {}", pattern, e));
+                    None
+                }
+            })
+            .collect();
+
+        WatchRules { rules }
+    }
+
+    /// Returns true if the name of the contest matches at least one rule.
+    pub fn matches(&self, contest: &Contest) -> bool {
+        self.rules.is_empty() || self.rules.iter().any(|rule| rule.is_match(&contest.name))
+    }
+}
+
+/// Reason for skipping a new contest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Exclusion {
+    /// No watch rule matches the name.
+    NotWatched,
+    /// Longer than max_duration_seconds.
+    TooLong(i64),
+    /// Unknown duration while exclude_unknown_duration is set.
+    UnknownDuration,
+    /// Type not in allowed_types.
+    TypeNotAllowed(String),
+    /// Id at or below the highest id saved before, while
+    /// ignore_below_high_water_mark is set.
+    BelowHighWaterMark(usize),
+    /// Overlaps these events of conflict_calendars, while skip_conflicts is set.
+    Conflicts(Vec<String>),
+}
+
+impl fmt::Display for Exclusion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Exclusion::NotWatched => write!(f, "no watch rule matches"),
+            Exclusion::TooLong(duration) => write!(f, "lasts {} seconds", duration),
+            Exclusion::UnknownDuration => write!(f, "duration is unknown"),
+            Exclusion::TypeNotAllowed(contest_type) => write!(f, "type {} is not allowed", contest_type),
+            Exclusion::BelowHighWaterMark(mark) => write!(f, "id is not above the highest id saved, {}", mark),
+            Exclusion::Conflicts(events) => write!(f, "conflicts with {}", events.join(", ")),
+        }
+    }
+}
+
+/// All filters from the config, ready to be applied to contests.
+pub struct Filters {
+    watch: WatchRules,
+    max_duration_seconds: Option<i64>,
+    exclude_unknown_duration: bool,
+    allowed_types: Vec<String>,
+    high_water_mark: Option<usize>,
+    pinned: Vec<PinnedContest>,
+    skip_conflicts: bool,
+}
+
+impl Filters {
+    pub fn from_config(config: &Config) -> Self {
+        Filters {
+            watch: WatchRules::compile(&config.watch),
+            max_duration_seconds: config.max_duration_seconds,
+            exclude_unknown_duration: config.exclude_unknown_duration,
+            allowed_types: config.allowed_types.clone(),
+            high_water_mark: if config.ignore_below_high_water_mark { high_water_mark() } else { None },
+            pinned: config.pinned.clone(),
+            skip_conflicts: config.skip_conflicts,
+        }
+    }
+
+    /// Returns why the contest should be skipped, or None if it gets a reminder.
+    pub fn exclusion(&self, contest: &Contest) -> Option<Exclusion> {
+        self.checks(contest).into_iter().find_map(|(_, exclusion)| exclusion)
+    }
+
+    /// Returns true if the contest is pinned and bypasses all filters.
+    pub fn is_pinned(&self, contest: &Contest) -> bool {
+        self.pinned.iter().any(|pinned| pinned.matches(contest))
+    }
+
+    /// Applies every filter to the contest and returns the name of each
+    /// filter with the exclusion it caused, in the order they are applied.
+    /// Pinned contests are not filtered at all.
+    pub fn checks(&self, contest: &Contest) -> Vec<(&'static str, Option<Exclusion>)> {
+        if self.is_pinned(contest) { return Vec::new() }
+
+        vec![
+            ("watch rules", self.watch_exclusion(contest)),
+            ("duration filter", self.duration_exclusion(contest)),
+            ("type filter", self.type_exclusion(contest)),
+            ("high-water mark", self.high_water_mark_exclusion(contest)),
+            ("calendar conflicts", self.conflict_exclusion(contest)),
+        ]
+    }
+
+    fn watch_exclusion(&self, contest: &Contest) -> Option<Exclusion> {
+        if self.watch.matches(contest) { None } else { Some(Exclusion::NotWatched) }
+    }
+
+    fn duration_exclusion(&self, contest: &Contest) -> Option<Exclusion> {
+        let max = self.max_duration_seconds?;
+
+        match contest.duration_seconds {
+            Some(duration) if duration > max => Some(Exclusion::TooLong(duration)),
+            None if self.exclude_unknown_duration => Some(Exclusion::UnknownDuration),
+            _ => None,
+        }
+    }
+
+    fn type_exclusion(&self, contest: &Contest) -> Option<Exclusion> {
+        let contest_type = contest.contest_type.as_ref()?;
+
+        let allowed = self.allowed_types.is_empty()
+            || self.allowed_types.iter().any(|allowed| allowed.eq_ignore_ascii_case(contest_type));
+
+        if allowed { None } else { Some(Exclusion::TypeNotAllowed(contest_type.clone())) }
+    }
+
+    fn high_water_mark_exclusion(&self, contest: &Contest) -> Option<Exclusion> {
+        let mark = self.high_water_mark?;
+        if !contest.is_from_codeforces_api() { return None }
+
+        if contest.id > mark { None } else { Some(Exclusion::BelowHighWaterMark(mark)) }
+    }
+
+    fn conflict_exclusion(&self, contest: &Contest) -> Option<Exclusion> {
+        if !self.skip_conflicts { return None }
+
+        let events = conflicts(contest);
+        if events.is_empty() { None } else { Some(Exclusion::Conflicts(events)) }
+    }
+}
+
+/// Moves new contests excluded by the config from diff.new to diff.skipped,
+/// logging why at INFO.
+///
+/// Skipped contests are not saved locally, so they are checked
+/// again on the next run (e.g. after adding a watch rule).
+pub fn skip_filtered(diff: &mut ContestDiff) {
+    let filters = Filters::from_config(config());
+
+    let (new, skipped) = std::mem::take(&mut diff.new)
+        .into_iter()
+        .partition(|contest| match filters.exclusion(contest) {
+            Some(exclusion) => {
+                log_info(&format!("Skipping contest {}, id: {}: {}", contest.name, contest.id, exclusion));
+                false
+            }
+            None => true,
+        });
+
+    diff.new = new;
+    diff.skipped = skipped;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contest::tests::contest;
+
+    fn rules(patterns: &[&str]) -> WatchRules {
+        WatchRules::compile(&patterns.iter().map(|pattern| pattern.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn no_watch_rules_match_everything() {
+        assert!(rules(&[]).matches(&contest(1, "Kotlin Heroes", 0)));
+    }
+
+    #[test]
+    fn watch_rules_match_any_rule() {
+        let rules = rules(&["Educational", "Div. 2"]);
+
+        assert!(rules.matches(&contest(1, "Educational Codeforces Round 178", 0)));
+        assert!(rules.matches(&contest(2, "Codeforces Round 1000 (Div. 2)", 0)));
+        assert!(!rules.matches(&contest(3, "Codeforces Round 999 (Div. 1)", 0)));
+    }
+
+    #[test]
+    fn duration_limit_is_inclusive() {
+        let config = Config { max_duration_seconds: Some(7200), ..Config::default() };
+        let filters = Filters::from_config(&config);
+
+        assert_eq!(filters.exclusion(&Contest { duration_seconds: Some(7200), ..contest(1, "Round", 0) }), None);
+        assert_eq!(filters.exclusion(&Contest { duration_seconds: Some(7201), ..contest(2, "Marathon", 0) }),
+            Some(Exclusion::TooLong(7201)));
+    }
+
+    #[test]
+    fn unknown_duration_kept_unless_excluded() {
+        let unknown = Contest { duration_seconds: None, ..contest(1, "Round", 0) };
+        let filters = |exclude_unknown_duration| Filters::from_config(&Config {
+            max_duration_seconds: Some(7200),
+            exclude_unknown_duration,
+            ..Config::default()
+        });
+
+        assert_eq!(filters(false).exclusion(&unknown), None);
+        assert_eq!(filters(true).exclusion(&unknown), Some(Exclusion::UnknownDuration));
+        // Without a limit the duration is never checked.
+        let no_limit = Filters::from_config(&Config { exclude_unknown_duration: true, ..Config::default() });
+        assert_eq!(no_limit.exclusion(&unknown), None);
+    }
+
+    #[test]
+    fn allowed_types_ignore_case_and_keep_unknown_types() {
+        let config = Config { allowed_types: vec!["cf".to_string(), "ICPC".to_string()], ..Config::default() };
+        let filters = Filters::from_config(&config);
+        let typed = |contest_type: Option<&str>| Contest { contest_type: contest_type.map(str::to_string), ..contest(1, "Round", 0) };
+
+        assert_eq!(filters.exclusion(&typed(Some("CF"))), None);
+        assert_eq!(filters.exclusion(&typed(Some("ICPC"))), None);
+        assert_eq!(filters.exclusion(&typed(Some("IOI"))), Some(Exclusion::TypeNotAllowed("IOI".to_string())));
+        assert_eq!(filters.exclusion(&typed(None)), None);
+    }
+
+    #[test]
+    fn no_allowed_types_allow_all() {
+        let filters = Filters::from_config(&Config::default());
+
+        assert_eq!(filters.exclusion(&Contest { contest_type: Some("IOI".to_string()), ..contest(1, "Round", 0) }), None);
+    }
+
+    #[test]
+    fn unwatched_contests_are_excluded() {
+        let config = Config { watch: vec!["Educational".to_string()], ..Config::default() };
+        let filters = Filters::from_config(&config);
+
+        assert_eq!(filters.exclusion(&contest(1, "Codeforces Round 1000 (Div. 2)", 0)), Some(Exclusion::NotWatched));
+        assert_eq!(filters.exclusion(&contest(2, "Educational Codeforces Round 178", 0)), None);
+    }
+
+    #[test]
+    fn ids_up_to_high_water_mark_are_excluded() {
+        let filters = Filters { high_water_mark: Some(2100), ..Filters::from_config(&Config::default()) };
+
+        assert_eq!(filters.exclusion(&contest(2099, "Old", 0)), Some(Exclusion::BelowHighWaterMark(2100)));
+        assert_eq!(filters.exclusion(&contest(2100, "Saved", 0)), Some(Exclusion::BelowHighWaterMark(2100)));
+        assert_eq!(filters.exclusion(&contest(2101, "New", 0)), None);
+    }
+
+    #[test]
+    fn high_water_mark_only_applies_to_codeforces() {
+        let filters = Filters { high_water_mark: Some(2100), ..Filters::from_config(&Config::default()) };
+
+        assert_eq!(filters.exclusion(&contest(5, "abc400", 0).with_source("https://atcoder.jp")), None);
+    }
+
+    #[test]
+    fn no_high_water_mark_keeps_low_ids() {
+        let filters = Filters::from_config(&Config::default());
+
+        assert_eq!(filters.exclusion(&contest(1, "Codeforces Beta Round 1", 0)), None);
+    }
+
+    #[test]
+    fn pinned_contest_bypasses_exclusions() {
+        let config = Config {
+            watch: vec!["Educational".to_string()],
+            max_duration_seconds: Some(3600),
+            pinned: vec![PinnedContest::Id(2100)],
+            ..Config::default()
+        };
+        let filters = Filters::from_config(&config);
+        let marathon = |id| Contest { duration_seconds: Some(86400), ..contest(id, "Marathon Round", 0) };
+
+        assert_eq!(filters.exclusion(&marathon(2100)), None);
+        assert!(filters.checks(&marathon(2100)).is_empty());
+        assert_eq!(filters.exclusion(&marathon(2101)), Some(Exclusion::NotWatched));
+    }
+
+    #[test]
+    fn pin_of_other_source_needs_same_source() {
+        let filters = Filters::from_config(&Config {
+            watch: vec!["Educational".to_string()],
+            pinned: vec![PinnedContest::new(400, Some("https://atcoder.jp/"))],
+            ..Config::default()
+        });
+
+        assert_eq!(filters.exclusion(&contest(400, "abc400", 0).with_source("https://atcoder.jp")), None);
+        assert_eq!(filters.exclusion(&contest(400, "Codeforces Round 400", 0)), Some(Exclusion::NotWatched));
+    }
+}
@@ -0,0 +1,36 @@
+use crate::config::config;
+use crate::local::log_warn;
+use crate::notify::http_client;
+
+/// Signal sent to the check of healthcheck_url in the config.
+pub enum Ping<'a> {
+    /// The run started.
+    Start,
+    /// The run completed without errors.
+    Success,
+    /// The run logged this error.
+    Fail(&'a str),
+}
+
+/// Pings the healthchecks.io (or compatible) check of healthcheck_url,
+/// so the check alerts if the job stops running or fails.
+///
+/// Failed runs send their first error as body. Does nothing without
+/// healthcheck_url. A failing ping is logged as warning, it never fails
+/// the run itself.
+pub fn ping(ping: Ping) {
+    let Some(url) = &config().healthcheck_url else { return };
+    let url = url.trim_end_matches('/');
+
+    let request = match ping {
+        Ping::Start => http_client().post(format!("{}/start", url)),
+        Ping::Success => http_client().post(url),
+        Ping::Fail(error) => http_client().post(format!("{}/fail", url)).body(error.to_string()),
+    };
+
+    match request.send() {
+        Ok(response) if response.status().is_success() => {}
+        Ok(response) => log_warn(&format!("Healthcheck ping failed with {}.", response.status())),
+        Err(e) => log_warn(&format!("Healthcheck ping failed. {}", e.without_url())),
+    }
+}
@@ -0,0 +1,105 @@
+use crate::config::config;
+use crate::contest::Contest;
+use crate::local::log_error;
+use crate::paths::history_path;
+
+use serde::Serialize;
+use std::fs::OpenOptions;
+use std::io::Write;
+
+/// What happened to the reminder of a contest.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Action {
+    Created,
+    Updated,
+    Deleted,
+}
+
+/// One line of history.jsonl.
+#[derive(Serialize, Debug)]
+struct HistoryEntry<'a> {
+    /// Local time of the action in RFC 3339.
+    timestamp: String,
+    action: Action,
+    id: usize,
+    name: &'a str,
+    start_time_seconds: Option<i64>,
+    /// Base URL of the API the contest was fetched from, telling apart
+    /// contests of several sources with the same id.
+    source: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    page_url: Option<&'a str>,
+}
+
+/// Appends the action to history.jsonl if history is enabled in the config.
+///
+/// Every entry is written with a single write to a file opened in append
+/// mode, so lines are never interleaved or partially overwritten.
+/// The file is never rotated.
+pub fn record(action: Action, contest: &Contest) {
+    if !config().history { return }
+
+    let line = match history_line(action, contest, chrono::Local::now().to_rfc3339()) {
+        Ok(line) => line,
+        Err(e) => {
+            log_error(&format!("Failed to serialize history entry. {}", e));
+            return;
+        }
+    };
+
+    let result = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(history_path())
+        .and_then(|mut file| file.write_all(line.as_bytes()));
+
+    if let Err(e) = result {
+        log_error(&format!("Failed to append to history file. {}", e));
+    }
+}
+
+/// Serializes the action as line of history.jsonl, ending with a newline.
+fn history_line(action: Action, contest: &Contest, timestamp: String) -> serde_json::Result<String> {
+    let entry = HistoryEntry {
+        timestamp,
+        action,
+        id: contest.id,
+        name: &contest.name,
+        start_time_seconds: contest.start_time_seconds,
+        source: contest.source_url(),
+        page_url: contest.page_url.as_deref(),
+    };
+
+    serde_json::to_string(&entry).map(|json| json + "\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contest::tests::contest;
+    use crate::contest::CODEFORCES_GYM;
+
+    #[test]
+    fn one_json_line_per_action() {
+        let timestamp = "2025-04-25T16:35:00+02:00".to_string();
+        let created = history_line(Action::Created, &contest(2100, "Codeforces Round 1020 (Div. 2)", 1_745_591_700), timestamp.clone()).unwrap();
+        let deleted = history_line(Action::Deleted, &Contest { start_time_seconds: None, ..contest(2101, "Round \"B\"", 0) }, timestamp.clone()).unwrap();
+        let gym = Contest {
+            source: Some(CODEFORCES_GYM.to_string()),
+            page_url: Some("https://codeforces.com/gym/105800".to_string()),
+            ..contest(105800, "ICPC Mashup", 1_745_591_700)
+        };
+        let updated = history_line(Action::Updated, &gym, timestamp).unwrap();
+
+        assert_eq!(created, concat!(
+            r#"{"timestamp":"2025-04-25T16:35:00+02:00","action":"created","id":2100,"#,
+            r#""name":"Codeforces Round 1020 (Div. 2)","start_time_seconds":1745591700,"#,
+            r#""source":"https://codeforces.com/api"}"#, "\n"));
+        assert!(updated.trim_end().ends_with(r#","source":"https://codeforces.com/gym","page_url":"https://codeforces.com/gym/105800"}"#));
+        assert_eq!(deleted.lines().count(), 1);
+        assert!(deleted.contains(r#""action":"deleted""#));
+        assert!(deleted.contains(r#""name":"Round \"B\"""#));
+        assert!(deleted.contains(r#""start_time_seconds":null"#));
+    }
+}
@@ -0,0 +1,42 @@
+use crate::local::log_error;
+
+use std::process::Command;
+
+/// Runs the post_run_hook command with sh after a completed run.
+///
+/// The counts of the run are passed as CFR_CREATED, CFR_DELETED and
+/// CFR_UPCOMING. A failing hook is logged, but does not change the exit code.
+pub fn run_post_run_hook(command: &str, created: usize, deleted: usize, upcoming: usize) {
+    let result = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .env("CFR_CREATED", created.to_string())
+        .env("CFR_DELETED", deleted.to_string())
+        .env("CFR_UPCOMING", upcoming.to_string())
+        .output();
+
+    match result {
+        Ok(output) if output.status.success() => {}
+        Ok(output) => log_error(&format!("Post run hook {} failed with {}. Error: {}",
+            command, output.status, String::from_utf8_lossy(&output.stderr).trim())),
+        Err(e) => log_error(&format!("Failed to run post run hook {}. {}", command, e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn hook_receives_the_counts() {
+        let path = std::env::temp_dir().join("codeforces-reminder-test-hook.txt");
+        let command = format!(r#"printf '%s %s %s' "$CFR_CREATED" "$CFR_DELETED" "$CFR_UPCOMING" > '{}'"#, path.display());
+
+        run_post_run_hook(&command, 2, 1, 7);
+
+        let received = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(received, "2 1 7");
+    }
+}
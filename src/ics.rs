@@ -0,0 +1,441 @@
+use crate::contest::Contest;
+use crate::local::write_atomically;
+use crate::reminder::{contest_lead_times, due_time, reminder_name};
+
+use chrono::{Local, NaiveDateTime, TimeZone, Utc};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Maximum length of a content line in octets before it is folded (RFC 5545).
+const MAX_LINE_OCTETS: usize = 75;
+
+/// VEVENTs added, removed and changed between two calendars, by UID.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct EventDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<String>,
+}
+
+impl EventDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Formats the contests as calendar with one VEVENT per contest with a start time.
+///
+/// Events are sorted by start time, then id, and contain nothing that changes
+/// between runs, so the same contests always give the same file.
+pub fn format_ics(contests: &[Contest]) -> String {
+    format_calendar(contests, false)
+}
+
+/// Formats the contests like format_ics, with a VALARM per lead time
+/// in every VEVENT, due when its reminder would be.
+pub fn format_ics_with_alarms(contests: &[Contest]) -> String {
+    format_calendar(contests, true)
+}
+
+fn format_calendar(contests: &[Contest], alarms: bool) -> String {
+    let mut contests: Vec<&Contest> = contests.iter().filter(|contest| contest.start_time_seconds.is_some()).collect();
+    contests.sort_by_key(|contest| (contest.start_time_seconds, contest.id));
+
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//codeforces-reminder//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+    ];
+
+    for contest in contests {
+        lines.extend(event_lines(contest, alarms));
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+
+    let mut ics = String::new();
+    for line in lines {
+        let _ = write!(ics, "{}\r\n", fold(&line));
+    }
+    ics
+}
+
+/// Content lines of the VEVENT of a contest with a start time,
+/// with its VALARMs if alarms is set.
+fn event_lines(contest: &Contest, alarms: bool) -> Vec<String> {
+    let start_seconds = contest.start_time_seconds.unwrap_or_default();
+    let start = format_utc(start_seconds);
+    let mut lines = vec![
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{}", uid(contest)),
+        // DTSTAMP is required, the start keeps it stable between runs.
+        format!("DTSTAMP:{}", start),
+        format!("DTSTART:{}", start),
+    ];
+
+    if let Some(duration) = contest.duration_seconds {
+        lines.push(format!("DURATION:PT{}S", duration));
+    }
+
+    lines.push(format!("SUMMARY:{}", escape(&contest.name)));
+    if let Some(description) = contest.description() {
+        lines.push(format!("DESCRIPTION:{}", escape(description)));
+    }
+    lines.push(format!("URL:{}", contest.url()));
+
+    if alarms {
+        for lead_time in contest_lead_times(contest) {
+            // Quiet hours can move a reminder closer to the start, never past it.
+            let before = start_seconds - due_time(contest, start_seconds, lead_time);
+            lines.extend([
+                "BEGIN:VALARM".to_string(),
+                "ACTION:DISPLAY".to_string(),
+                format!("DESCRIPTION:{}", escape(&reminder_name(contest))),
+                format!("TRIGGER:-PT{}S", before),
+                "END:VALARM".to_string(),
+            ]);
+        }
+    }
+    lines.push("END:VEVENT".to_string());
+
+    lines
+}
+
+/// UID of the VEVENT of a contest: its id at the host of its source.
+pub fn uid(contest: &Contest) -> String {
+    format!("{}@{}", contest.id, contest.source_host())
+}
+
+/// Formats seconds since Unix epoch as UTC date-time, e.g. "20250425T143500Z".
+/// Times chrono cannot represent fall back to the Unix epoch.
+fn format_utc(seconds: i64) -> String {
+    Utc.timestamp_opt(seconds, 0).single().unwrap_or_default().format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Escapes a TEXT value.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Reverses escape, turning escaped newlines into spaces.
+pub fn unescape(text: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => unescaped.push(' '),
+            Some(escaped) => unescaped.push(escaped),
+            None => unescaped.push(c),
+        }
+    }
+    unescaped
+}
+
+/// Folds a content line into lines of at most MAX_LINE_OCTETS octets,
+/// never splitting a character.
+fn fold(line: &str) -> String {
+    let mut folded = String::new();
+    let mut length = 0;
+
+    for c in line.chars() {
+        // Continuation lines start with a space, which counts towards the limit.
+        if length + c.len_utf8() > MAX_LINE_OCTETS {
+            folded.push_str("\r\n ");
+            length = 1;
+        }
+        folded.push(c);
+        length += c.len_utf8();
+    }
+    folded
+}
+
+/// Returns the VEVENTs of a calendar by UID, with their unfolded content lines.
+pub fn parse_events(ics: &str) -> BTreeMap<String, Vec<String>> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in ics.lines() {
+        match line.strip_prefix(' ').or_else(|| line.strip_prefix('\t')) {
+            Some(continuation) if !lines.is_empty() => lines.last_mut().unwrap().push_str(continuation),
+            _ => lines.push(line.to_string()),
+        }
+    }
+
+    let mut events = BTreeMap::new();
+    let mut event: Option<Vec<String>> = None;
+
+    for line in lines {
+        match line.as_str() {
+            "BEGIN:VEVENT" => event = Some(Vec::new()),
+            "END:VEVENT" => {
+                if let Some(event) = event.take() {
+                    if let Some(uid) = event.iter().find_map(|line| line.strip_prefix("UID:")) {
+                        events.insert(uid.to_string(), event.clone());
+                    }
+                }
+            }
+            _ => {
+                if let Some(event) = &mut event {
+                    event.push(line);
+                }
+            }
+        }
+    }
+
+    events
+}
+
+/// Returns the value of the first content line of the property name,
+/// ignoring its parameters.
+pub fn property<'a>(lines: &'a [String], name: &str) -> Option<&'a str> {
+    lines.iter().find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        (key.split(';').next() == Some(name)).then_some(value)
+    })
+}
+
+/// Parses a DATE-TIME value in UTC (with Z) or local time, None for dates.
+///
+/// Times with a TZID are taken as local time, the time zone is not resolved.
+pub fn parse_date_time(value: &str) -> Option<i64> {
+    if let Some(utc) = value.strip_suffix('Z') {
+        return NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok().map(|time| time.and_utc().timestamp())
+    }
+
+    let time = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    Local.from_local_datetime(&time).earliest().map(|time| time.timestamp())
+}
+
+/// Parses a DURATION value like "PT1H30M" or "P1D" into seconds,
+/// None if it is malformed or does not fit an i64.
+pub fn parse_duration(value: &str) -> Option<i64> {
+    let (sign, value) = match value.strip_prefix('-') {
+        Some(value) => (-1, value),
+        None => (1, value.strip_prefix('+').unwrap_or(value)),
+    };
+
+    let mut seconds: i64 = 0;
+    let mut number = String::new();
+    let mut units = 0;
+
+    for c in value.strip_prefix('P')?.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' => {}
+            unit => {
+                let n: i64 = number.parse().ok()?;
+                number.clear();
+                let unit_seconds = match unit {
+                    'W' => 7 * 86400,
+                    'D' => 86400,
+                    'H' => 3600,
+                    'M' => 60,
+                    'S' => 1,
+                    _ => return None,
+                };
+                seconds = n.checked_mul(unit_seconds).and_then(|n| seconds.checked_add(n))?;
+                units += 1;
+            }
+        }
+    }
+
+    // A number without unit, e.g. "PT15", or no number at all is malformed.
+    if !number.is_empty() || units == 0 { return None }
+    Some(sign * seconds)
+}
+
+/// Compares the VEVENTs of two calendars by UID.
+pub fn diff_events(old: &str, new: &str) -> EventDiff {
+    let old = parse_events(old);
+    let new = parse_events(new);
+
+    EventDiff {
+        added: new.keys().filter(|uid| !old.contains_key(*uid)).cloned().collect(),
+        removed: old.keys().filter(|uid| !new.contains_key(*uid)).cloned().collect(),
+        changed: new.iter()
+            .filter(|(uid, event)| old.get(*uid).is_some_and(|old| old != *event))
+            .map(|(uid, _)| uid.clone())
+            .collect(),
+    }
+}
+
+/// Writes the calendar of the contests to path, unless the file
+/// already has the same content. Returns true if it was written.
+pub fn write_ics(path: &Path, contests: &[Contest]) -> io::Result<bool> {
+    let ics = format_ics(contests);
+
+    if fs::read_to_string(path).is_ok_and(|existing| existing == ics) {
+        return Ok(false);
+    }
+
+    write_atomically(path, &ics)?;
+    Ok(true)
+}
+
+/// Writes the calendar of the contests with alarms to path,
+/// or to stdout if path is "-".
+pub fn export_ics(path: &Path, contests: &[Contest]) -> io::Result<()> {
+    let ics = format_ics_with_alarms(contests);
+
+    if path == Path::new("-") {
+        print!("{}", ics);
+        return Ok(())
+    }
+    write_atomically(path, &ics)
+}
+
+/// Formats which VEVENTs writing the contests to path would add,
+/// remove or change, without writing.
+pub fn ics_diff_report(path: &Path, contests: &[Contest]) -> io::Result<String> {
+    let existing = match fs::read_to_string(path) {
+        Ok(existing) => existing,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e),
+    };
+
+    let diff = diff_events(&existing, &format_ics(contests));
+    let name_of = |uid: &str| {
+        contests.iter().find(|contest| self::uid(contest) == uid).map_or(String::new(), |contest| format!(" {}", contest.name))
+    };
+
+    let mut report = String::new();
+    if diff.is_empty() {
+        let _ = writeln!(report, "{} is up to date.", path.display());
+    }
+    for uid in &diff.added {
+        let _ = writeln!(report, "  + {}{}", uid, name_of(uid));
+    }
+    for uid in &diff.removed {
+        let _ = writeln!(report, "  - {}", uid);
+    }
+    for uid in &diff.changed {
+        let _ = writeln!(report, "  ~ {}{}", uid, name_of(uid));
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contest::tests::contest;
+
+    #[test]
+    fn diff_matches_events_by_uid() {
+        let old = format_ics(&[contest(2100, "Round 1020", 1_000), contest(2101, "Round 1021", 2_000)]);
+        let new = format_ics(&[contest(2101, "Round 1021", 3_000), contest(2102, "Round 1022", 4_000)]);
+
+        assert_eq!(diff_events(&old, &new), EventDiff {
+            added: vec!["2102@codeforces.com".to_string()],
+            removed: vec!["2100@codeforces.com".to_string()],
+            changed: vec!["2101@codeforces.com".to_string()],
+        });
+        assert!(diff_events(&new, &new).is_empty());
+    }
+
+    #[test]
+    fn calendar_does_not_depend_on_contest_order() {
+        let contests = [contest(2102, "Round 1022", 4_000), contest(2100, "Round 1020", 1_000), contest(2101, "Round 1021", 1_000)];
+        let mut reversed = contests.clone();
+        reversed.reverse();
+
+        let ics = format_ics(&contests);
+
+        assert_eq!(ics, format_ics(&reversed));
+        let uids: Vec<&str> = ics.lines().filter_map(|line| line.strip_prefix("UID:")).collect();
+        assert_eq!(uids, ["2100@codeforces.com", "2101@codeforces.com", "2102@codeforces.com"]);
+    }
+
+    #[test]
+    fn writes_only_changed_calendars() {
+        let path = std::env::temp_dir().join("codeforces-reminder-test-calendar.ics");
+        let _ = fs::remove_file(&path);
+        let contests = [contest(2101, "Round 1021", 2_000)];
+
+        let first = write_ics(&path, &contests).unwrap();
+        let second = write_ics(&path, &contests).unwrap();
+        let changed = write_ics(&path, &[contest(2101, "Round 1021", 3_000)]).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert!(first);
+        assert!(!second);
+        assert!(changed);
+    }
+
+    #[test]
+    fn event_has_start_duration_and_url() {
+        let ics = format_ics(&[contest(2101, "Round 1021; Div. 2, rated", 1745850900)]);
+        let events = parse_events(&ics);
+        let event = &events["2101@codeforces.com"];
+
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(ics.ends_with("END:VEVENT\r\nEND:VCALENDAR\r\n"));
+        assert_eq!(property(event, "DTSTART"), Some("20250428T143500Z"));
+        assert_eq!(property(event, "DURATION"), Some("PT7200S"));
+        assert_eq!(property(event, "SUMMARY"), Some(r"Round 1021\; Div. 2\, rated"));
+        assert_eq!(unescape(property(event, "SUMMARY").unwrap()), "Round 1021; Div. 2, rated");
+        assert_eq!(property(event, "URL"), Some("https://codeforces.com/contests/2101"));
+    }
+
+    #[test]
+    fn contests_without_start_are_left_out() {
+        let ics = format_ics(&[Contest { start_time_seconds: None, ..contest(2101, "Round 1021", 0) }]);
+
+        assert!(parse_events(&ics).is_empty());
+    }
+
+    #[test]
+    fn long_lines_are_folded_and_unfolded() {
+        let name = "Ünïcödé ".repeat(20);
+        let ics = format_ics(&[contest(2101, &name, 0)]);
+
+        assert!(ics.split("\r\n").all(|line| line.len() <= MAX_LINE_OCTETS), "{}", ics);
+        let events = parse_events(&ics);
+        assert_eq!(property(&events["2101@codeforces.com"], "SUMMARY"), Some(name.as_str()));
+    }
+
+    #[test]
+    fn alarm_per_lead_time() {
+        use crate::config::tests::with_config;
+        use crate::config::Config;
+
+        let config = Config { lead_times_seconds: vec![3600, 600], ..Config::default() };
+        let ics = with_config(config, || format_ics_with_alarms(&[contest(2101, "Round 1021", 1745850900)]));
+
+        let triggers: Vec<&str> = ics.lines().filter_map(|line| line.strip_prefix("TRIGGER:")).collect();
+        assert_eq!(triggers, ["-PT3600S", "-PT600S"]);
+        assert_eq!(ics.matches("BEGIN:VALARM").count(), 2);
+        assert!(!format_ics(&[contest(2101, "Round 1021", 1745850900)]).contains("VALARM"));
+    }
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("PT1H30M"), Some(5400));
+        assert_eq!(parse_duration("P1D"), Some(86400));
+        assert_eq!(parse_duration("P1W"), Some(7 * 86400));
+        assert_eq!(parse_duration("P1DT2H3M4S"), Some(86400 + 7200 + 180 + 4));
+        assert_eq!(parse_duration("+PT15M"), Some(900));
+        assert_eq!(parse_duration("-PT15M"), Some(-900));
+    }
+
+    #[test]
+    fn malformed_or_overflowing_durations_are_none() {
+        for duration in ["", "1H", "P", "PT", "PT15", "PT1H30", "PTH", "PT1X", "PT1.5H"] {
+            assert_eq!(parse_duration(duration), None, "{}", duration);
+        }
+        let seconds = i64::MAX.to_string();
+        assert_eq!(parse_duration(&format!("PT{}S", seconds)), Some(i64::MAX));
+        assert_eq!(parse_duration(&format!("PT{}M", seconds)), None);
+        assert_eq!(parse_duration(&format!("PT{}S1S", seconds)), None);
+        assert_eq!(parse_duration("PT99999999999999999999S"), None);
+    }
+}
@@ -0,0 +1,152 @@
+use crate::api::CodeforcesClient;
+use crate::config::LeetCodeConfig;
+use crate::contest::{slug_id, Contest, Phase};
+use crate::source::ContestSource;
+
+use reqwest::blocking::Response;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+/// Source of the LeetCode contests, added to the sources if
+/// leetcode.enabled is set in the config.
+pub const LEETCODE: &str = "https://leetcode.com";
+
+/// GraphQL endpoint answering upcomingContests.
+const GRAPHQL_URL: &str = "https://leetcode.com/graphql";
+
+/// Upcoming LeetCode contests enabled in the config, asked through
+/// GraphQL, which does not answer with an ETag.
+pub struct LeetCode<'a> {
+    config: &'a LeetCodeConfig,
+}
+
+impl<'a> LeetCode<'a> {
+    pub fn new(config: &'a LeetCodeConfig) -> Self {
+        LeetCode { config }
+    }
+}
+
+impl ContestSource for LeetCode<'_> {
+    fn url(&self) -> &str {
+        LEETCODE
+    }
+
+    fn request(&self, client: &CodeforcesClient, _etag: Option<&str>) -> reqwest::Result<Response> {
+        client.post_json(GRAPHQL_URL, &query())
+    }
+
+    fn parse(&self, body: &str) -> Result<Vec<Contest>, String> {
+        parse_contests(body, self.config)
+    }
+}
+
+#[derive(Deserialize)]
+struct GraphQlResponse {
+    data: Option<Data>,
+    #[serde(default)]
+    errors: Vec<Value>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Data {
+    upcoming_contests: Vec<UpcomingContest>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UpcomingContest {
+    title: String,
+    title_slug: String,
+    start_time: i64,
+    duration: i64,
+}
+
+/// Body of the request for the upcoming contests.
+fn query() -> Value {
+    json!({ "query": "{ upcomingContests { title titleSlug startTime duration } }" })
+}
+
+/// Returns the upcoming contests of a GraphQL response that are
+/// enabled in the config. Weekly and biweekly contests are told apart
+/// by their slug, e.g. "biweekly-contest-160".
+pub fn parse_contests(body: &str, config: &LeetCodeConfig) -> Result<Vec<Contest>, String> {
+    let response: GraphQlResponse = serde_json::from_str(body)
+        .map_err(|e| format!("Could not parse LeetCode contest JSON. {}", e))?;
+
+    let Some(data) = response.data else {
+        return Err(format!("LeetCode response without data. Errors: {}", Value::from(response.errors)))
+    };
+
+    Ok(data.upcoming_contests
+        .into_iter()
+        .filter(|contest| {
+            if contest.title_slug.starts_with("biweekly-") {
+                config.biweekly
+            } else if contest.title_slug.starts_with("weekly-") {
+                config.weekly
+            } else {
+                config.other
+            }
+        })
+        .map(|contest| Contest {
+            id: slug_id(LEETCODE, &contest.title_slug),
+            name: contest.title,
+            contest_type: None,
+            phase: Phase::Before,
+            start_time_seconds: Some(contest.start_time),
+            duration_seconds: Some(contest.duration),
+            description: None,
+            scraped_description: None,
+            reminder_ids: Vec::new(),
+            delivery_ids: BTreeMap::new(),
+            failed_deliveries: Vec::new(),
+            source: Some(LEETCODE.to_string()),
+            lead_time_override: None,
+            page_url: Some(format!("{}/contest/{}", LEETCODE, contest.title_slug)),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RESPONSE: &str = r#"{"data": {"upcomingContests": [
+        {"title": "Weekly Contest 447", "titleSlug": "weekly-contest-447", "startTime": 1746325800, "duration": 5400},
+        {"title": "Biweekly Contest 156", "titleSlug": "biweekly-contest-156", "startTime": 1746887400, "duration": 5400},
+        {"title": "Sponsored Contest", "titleSlug": "sponsored-contest", "startTime": 1747000000, "duration": 3600}
+    ]}}"#;
+
+    fn names(contests: &[Contest]) -> Vec<&str> {
+        contests.iter().map(|contest| contest.name.as_str()).collect()
+    }
+
+    #[test]
+    fn parses_upcoming_contests() {
+        let contests = parse_contests(RESPONSE, &LeetCodeConfig::default()).unwrap();
+
+        assert_eq!(names(&contests), ["Weekly Contest 447", "Biweekly Contest 156", "Sponsored Contest"]);
+        let weekly = &contests[0];
+        assert_eq!(weekly.id, slug_id(LEETCODE, "weekly-contest-447"));
+        assert_eq!(weekly.start_time_seconds, Some(1746325800));
+        assert_eq!(weekly.duration_seconds, Some(5400));
+        assert_eq!(weekly.page_url.as_deref(), Some("https://leetcode.com/contest/weekly-contest-447"));
+        assert_eq!(weekly.source_url(), LEETCODE);
+    }
+
+    #[test]
+    fn keeps_only_enabled_kinds() {
+        let config = LeetCodeConfig { enabled: true, weekly: false, biweekly: true, other: false };
+
+        assert_eq!(names(&parse_contests(RESPONSE, &config).unwrap()), ["Biweekly Contest 156"]);
+    }
+
+    #[test]
+    fn errors_without_data() {
+        let error = parse_contests(r#"{"data": null, "errors": [{"message": "rate limited"}]}"#, &LeetCodeConfig::default());
+
+        assert!(error.unwrap_err().contains("rate limited"));
+    }
+}
@@ -1,70 +1,338 @@
+use crate::config::config;
 use crate::contest::Contest;
 use crate::paths::contest_path;
-use crate::paths::log_file;
+use crate::paths::{log_file, open_log_file};
+use crate::paths::log_path;
 
+use serde::{Serialize, Deserialize};
 use std::collections::HashSet;
 use std::io::{BufWriter, Write};
 use std::fs::{self, File};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+/// Number of errors logged by this run.
+static ERROR_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// Set by --cron, so errors are printed to stderr as well.
+static ECHO_ERRORS: AtomicBool = AtomicBool::new(false);
+
+/// Called with the first error logged after on_first_error.
+static ERROR_HOOK: OnceLock<fn(&str)> = OnceLock::new();
+
+/// Called with every error logged after on_error.
+static ERROR_REPORTER: OnceLock<fn(&str)> = OnceLock::new();
+
+/// Set once the error hook ran, so it runs at most once per run,
+/// even if it logs errors itself.
+static ERROR_HOOK_CALLED: AtomicBool = AtomicBool::new(false);
 
 /// Deserializes and returns the locally saved contests in contests.json.
 ///
 /// This function panics if contests.json exists, but fails
 /// to read it or parse it.
 pub fn fetch_local_upcoming_contests() -> HashSet<Contest> {
-    let path = contest_path();
-
-    if !path.exists() { return HashSet::new() }
-
-    let contents = match fs::read_to_string(path) {
-        Ok(c) => c,
+    match read_local_upcoming_contests() {
+        Ok(contests) => contests,
         Err(e) => {
-            log_error(&format!("Failed to read local contests file: {}", e));
+            log_error(&format!("Failed to {}", e));
             std::process::exit(1);
         }
-    };
+    }
+}
 
-    match serde_json::from_str(&contents) {
-        Ok(data) => data,
-        Err(e) => {
-            log_error(&format!("Failed to parse local contests JSON: {}", e));
-            std::process::exit(1);
+/// Deserializes the locally saved contests in contests.json,
+/// none if it does not exist yet.
+pub fn read_local_upcoming_contests() -> std::io::Result<HashSet<Contest>> {
+    let path = contest_path();
+
+    if !path.exists() { return Ok(HashSet::new()) }
+
+    let contents = fs::read_to_string(path)
+        .map_err(|e| std::io::Error::new(e.kind(), format!("read local contests file: {}", e)))?;
+
+    serde_json::from_str(&contents)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, format!("parse local contests JSON: {}", e)))
+}
+
+/// Severity of a log message, from most to least severe.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+}
+
+impl Level {
+    fn label(&self) -> &'static str {
+        match self {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
         }
     }
 }
 
 /// Function used to log errors.
 ///
+/// Errors are always logged, regardless of log_level in the config,
+/// and printed to stderr as well after echo_errors.
+/// The log is synced afterwards, as errors are often followed by an exit.
+/// Every error is passed to the reporter of on_error, the first
+/// one to the hook of on_first_error as well.
 /// Panics if it fails to write or read.
 pub fn log_error(msg: &str) {
-    let mut file = log_file(); 
+    ERROR_COUNT.fetch_add(1, Ordering::Relaxed);
+    if ECHO_ERRORS.load(Ordering::Relaxed) {
+        eprintln!("codeforces-reminder: {}", msg);
+    }
+    write_log(Level::Error, msg);
+    flush_log();
+
+    if let Some(reporter) = ERROR_REPORTER.get() {
+        reporter(msg);
+    }
+    if let Some(hook) = ERROR_HOOK.get() {
+        if !ERROR_HOOK_CALLED.swap(true, Ordering::Relaxed) {
+            hook(msg);
+        }
+    }
+}
+
+/// Calls hook with the first error logged from now on, before the
+/// program exits on it.
+pub fn on_first_error(hook: fn(&str)) {
+    let _ = ERROR_HOOK.set(hook);
+}
+
+/// Calls reporter with every error logged from now on.
+/// The reporter must not log errors itself.
+pub fn on_error(reporter: fn(&str)) {
+    let _ = ERROR_REPORTER.set(reporter);
+}
+
+/// Prints every error logged from now on to stderr as well.
+pub fn echo_errors() {
+    ECHO_ERRORS.store(true, Ordering::Relaxed);
+}
+
+/// Returns the number of errors logged by this run so far.
+pub fn error_count() -> usize {
+    ERROR_COUNT.load(Ordering::Relaxed)
+}
+
+/// Logs a warning if log_level in the config allows it.
+pub fn log_warn(msg: &str) {
+    if config().log_level >= Level::Warn {
+        write_log(Level::Warn, msg);
+    }
+}
+
+/// Logs an informational message if log_level in the config allows it.
+pub fn log_info(msg: &str) {
+    if config().log_level >= Level::Info {
+        write_log(Level::Info, msg);
+    }
+}
+
+/// Writes a line to the log.
+///
+/// Panics if it fails to write or read.
+fn write_log(level: Level, msg: &str) {
+    write_line(log_file(), level, msg).expect("Could not write to file");
+}
+
+/// Writes a line to writer while holding its lock,
+/// so concurrent lines never interleave.
+fn write_line(writer: &Mutex<impl Write>, level: Level, msg: &str) -> std::io::Result<()> {
+    let line = format!("{:?}: {}: {}\n", chrono::offset::Local::now(), level.label(), msg);
+    let mut writer = writer.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
 
-    file.write_all(format!("{:?}: {}\n", chrono::offset::Local::now(), msg).as_bytes()).expect("Could not write to file");
+    writer.write_all(line.as_bytes())
+}
+
+/// Syncs the log file to disk, so no line is lost on exit.
+///
+/// Does nothing if nothing was logged. Failing to sync is ignored,
+/// there is nowhere left to report it.
+pub fn flush_log() {
+    if let Some(file) = open_log_file() {
+        let file = file.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let _ = file.sync_all();
+    }
+}
+
+/// Removes all but the last `keep` lines of error_log.txt
+/// and returns the number of removed lines.
+pub fn prune_log(keep: usize) -> std::io::Result<usize> {
+    let path = log_path();
+
+    if !path.exists() { return Ok(0) }
+
+    let contents = fs::read_to_string(&path)?;
+    let (kept, removed) = last_lines(&contents, keep);
+
+    write_atomically(&path, &kept)?;
+    Ok(removed)
+}
+
+/// Returns the last `keep` lines of contents and the number of lines before them.
+fn last_lines(contents: &str, keep: usize) -> (String, usize) {
+    let lines: Vec<&str> = contents.lines().collect();
+    let removed = lines.len().saturating_sub(keep);
+
+    let mut kept = lines[removed..].join("\n");
+    if !kept.is_empty() { kept.push('\n'); }
+
+    (kept, removed)
 }
 
 /// Serializes the contests and tries to save them locally.
 ///
+/// The JSON is pretty printed unless compact_json is set in the config,
+/// fetch_local_upcoming_contests reads both.
+///
 /// This function is guaranteed to either succeed in saving the new contests, or
 /// keeping the old locally saved contests.
 pub fn save_contests_locally(contests: &Vec<Contest>) -> std::io::Result<()> {
-    let serialized = serde_json::to_string_pretty(&contests)?;
-    save_contests_atomically(&serialized)
+    let serialized = serialize_contests(contests, config().compact_json)?;
+    write_atomically(contest_path(), &serialized)
+}
+
+/// Serializes the contests as saved in contests.json, on a single line if compact.
+fn serialize_contests(contests: &Vec<Contest>, compact: bool) -> serde_json::Result<String> {
+    if compact {
+        serde_json::to_string(contests)
+    } else {
+        serde_json::to_string_pretty(contests)
+    }
 }
 
-/// Function to save contests locally.
-/// It saves contests by writing to a temporary file and then overwriting the
-/// contests.json atomically (using the filesystem) to preserve old contests in case
+/// Function to save files locally.
+/// It saves data by writing to a temporary file and then overwriting the
+/// file at path atomically (using the filesystem) to preserve the old content in case
 /// of failure.
-fn save_contests_atomically(data: &str) -> std::io::Result<()> {
-    let path = contest_path();
+///
+/// The temporary file is removed if any step fails, so no partial
+/// .tmp file is left behind.
+///
+/// The temporary file gets file_mode from the config before any data is written.
+pub fn write_atomically(path: &Path, data: &str) -> std::io::Result<()> {
     let temp_path = path.with_extension("tmp");
 
-    let file = File::create(&temp_path)?;
+    let result = write_and_rename(data, &temp_path, path);
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
+
+fn write_and_rename(data: &str, temp_path: &Path, path: &Path) -> std::io::Result<()> {
+    let file = File::create(temp_path)?;
+    set_file_mode(&file, config().file_mode)?;
     let mut writer = BufWriter::new(file);
 
     writer.write_all(data.as_bytes())?;
     writer.flush()?;
     writer.get_ref().sync_all()?; 
 
-    fs::rename(&temp_path, path)?; 
+    fs::rename(temp_path, path)?; 
     Ok(())
 }
+
+/// Sets the permissions of a file created by the program to mode.
+///
+/// Does nothing on systems other than Unix.
+pub fn set_file_mode(file: &File, mode: u32) -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(fs::Permissions::from_mode(mode))
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = (file, mode);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contest::tests::contest;
+
+    #[test]
+    fn pruning_keeps_last_lines() {
+        let log = "one\ntwo\nthree\nfour\nfive\n";
+
+        assert_eq!(last_lines(log, 2), ("four\nfive\n".to_string(), 3));
+        assert_eq!(last_lines(log, 0), (String::new(), 5));
+        assert_eq!(last_lines(log, 10), (log.to_string(), 0));
+        assert_eq!(last_lines("", 3), (String::new(), 0));
+    }
+
+    #[test]
+    fn both_formats_round_trip() {
+        let contests = vec![
+            contest(1, "Codeforces Round 1000 (Div. 2)", 1_000),
+            Contest { reminder_ids: vec!["x-apple-reminder://A".to_string()], ..contest(2, "Educational Round", 2_000) },
+        ];
+
+        for compact in [false, true] {
+            let json = serialize_contests(&contests, compact).unwrap();
+            assert_eq!(json.contains('\n'), !compact);
+
+            let read: Vec<Contest> = serde_json::from_str(&json).unwrap();
+            assert_eq!(read, contests);
+            assert_eq!(read[1].reminder_ids, contests[1].reminder_ids);
+            assert!(read.iter().zip(&contests).all(|(read, saved)| read.same_content(saved)));
+        }
+    }
+
+    #[test]
+    fn concurrent_lines_do_not_interleave() {
+        let log = Mutex::new(Vec::new());
+
+        std::thread::scope(|scope| {
+            for thread in 0..8 {
+                let log = &log;
+                scope.spawn(move || {
+                    for line in 0..100 {
+                        write_line(log, Level::Warn, &format!("thread {} line {} {}", thread, line, "x".repeat(500))).unwrap();
+                    }
+                });
+            }
+        });
+
+        let log = String::from_utf8(log.into_inner().unwrap()).unwrap();
+        assert_eq!(log.lines().count(), 800);
+        for line in log.lines() {
+            let (_, msg) = line.split_once(": WARN: thread ").unwrap_or_else(|| panic!("malformed line {}", line));
+            assert!(msg.ends_with(&"x".repeat(500)), "interleaved line {}", line);
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn atomic_writes_get_the_configured_mode() {
+        use crate::config::{Config, DEFAULT_FILE_MODE};
+        use crate::config::tests::with_config;
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join("codeforces-reminder-test-mode");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("contests.json");
+
+        let config = Config { file_mode: 0o640, ..Config::default() };
+        with_config(config, || write_atomically(&path, "[]")).unwrap();
+
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(mode & 0o777, 0o640);
+        assert_eq!(DEFAULT_FILE_MODE, 0o600);
+    }
+}
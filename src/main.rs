@@ -7,130 +7,926 @@
 //! and get upcoming contests through 
 //! Codeforces's API. Finished contests (i.e. contests saved locally but 
 //! not present in the upcoming contests retrieved from Codeforces) 
-//! will be removed, while new ones will set new
+//! will be removed together with their reminders, changed ones will update
+//! their reminders, while new ones will set new
 //! reminders and again be saved to the local contests.
 //!
 //! Also logs errors to error_log.txt in the same directory to facilitate monitoring 
 //! when running this binary using cron or another scheduler.
 
+mod api;
+use api::{fetch_current_upcoming_contests, read_upcoming_contests};
+
+mod archive;
+
+mod atcoder;
+
+mod cli;
+use cli::{PinCommand, WatchCommand};
+
+mod clist;
+
+mod codechef;
+
+mod config;
+use config::{config, save_config, PinnedContest};
+
+mod conflict;
+
 mod contest;
-use contest::{Contest, ContestResponse, Phase};
+use contest::Contest;
+mod obsidian;
+
+mod org;
+
 mod paths;
 
+mod diff;
+use diff::{compute_contest_diff, format_diff_report, ContestDiff};
+
+mod explain;
+
+mod feed;
+
+mod filter;
+use filter::skip_filtered;
+
+mod healthcheck;
+use healthcheck::Ping;
+
+mod history;
+use history::Action;
+
+mod hook;
+
+mod ics;
+
+mod leetcode;
+
 mod local;
 use local::fetch_local_upcoming_contests;
-use local::log_error;
+use local::{log_error, log_warn};
 use local::save_contests_locally;
 
-use reqwest::blocking::{get, Response};
-use std::process::Command;
-use chrono::{Utc, TimeZone};
-use std::collections::HashSet;
+mod markdown;
+
+mod metrics;
 
+mod notes;
 
+mod notify;
+use notify::{delivered, Notifier};
+
+mod reminder;
+use reminder::Osascript;
+
+mod scrape;
+use scrape::fetch_contest_description;
+
+mod sentry;
+
+mod serve;
+
+mod shutdown;
+
+mod source;
+
+mod stats;
+
+mod status;
+use status::timed;
+
+mod summary;
+
+mod verify;
+
+use std::time::Duration;
 
 fn main() {
+    let args = cli::parse_args();
+    if args.cron {
+        local::echo_errors();
+    }
+    if let Some(profile) = args.profile.clone() {
+        paths::set_profile(profile);
+    }
+    sentry::init();
+
+    if args.show_config {
+        show_config();
+        return;
+    }
+
+    if let Some(command) = args.watch {
+        watch(command);
+        return;
+    }
+
+    if let Some(command) = args.pin {
+        pin(command, args.source.as_deref());
+        return;
+    }
+
+    if let Some(keep) = args.prune_logs {
+        match local::prune_log(keep) {
+            Ok(removed) => println!("Removed {} lines from {}", removed, paths::log_path().display()),
+            Err(e) => {
+                eprintln!("Failed to prune log file. {}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    if let Some(id) = args.explain {
+        explain::explain_command(id, args.source.as_deref(), args.force);
+        return;
+    }
+
+    if let Some((id, lead_time)) = args.snooze {
+        snooze(notify::configured(&Osascript).as_ref(), id, args.source.as_deref(), lead_time);
+        return;
+    }
+
+    if args.ics_diff {
+        ics_diff();
+        return;
+    }
+
+    if let Some(path) = &args.export_ics {
+        export_ics(path);
+        return;
+    }
+
+    if let Some(path) = &args.render_markdown {
+        render_markdown(path.as_deref());
+        return;
+    }
+
+    if args.google_auth {
+        google_auth();
+        return;
+    }
+
+    if args.outlook_auth {
+        outlook_auth();
+        return;
+    }
+
+    if let Some(address) = &args.serve {
+        shutdown::install_handler();
+        if let Err(e) = serve::serve(address) {
+            log_error(&format!("Failed to serve the calendar feed on {}. {}", address, e));
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if args.verify_cache {
+        verify::verify_cache_command();
+        return;
+    }
+
+    if args.stats {
+        stats::stats_command();
+        return;
+    }
+
+    if args.next {
+        next();
+        return;
+    }
+
+    if args.open_next {
+        open_next();
+        return;
+    }
+
+    if args.digest {
+        digest();
+        return;
+    }
+
+    // Summaries are Reminders items, other backends would never see them.
+    if config().daily_summary && notify::configured_names().iter().any(|name| *name != "reminders") {
+        log_error(&format!("daily_summary only works with the reminders notifier, not {}.",
+            notify::configured_names().join(", ")));
+        std::process::exit(1);
+    }
+
+    if let Some(name) = &config().failure_notifier {
+        // Fails on unknown backends now rather than on the first error.
+        notify::backend(name, &Osascript);
+    }
+    local::on_first_error(run_failed);
+    // --diff only previews the sync, it is not a run of the job.
+    if !args.diff {
+        healthcheck::ping(Ping::Start);
+    }
+
+    let mut fetch_time = Duration::ZERO;
+    let mut reminders_time = Duration::ZERO;
+
     let local_contests   = fetch_local_upcoming_contests();
-    let current_upcoming = fetch_current_upcoming_contests(); 
+    let current_upcoming = timed(&mut fetch_time, || {
+        if args.stdin {
+            read_upcoming_contests(std::io::stdin().lock())
+        } else {
+            fetch_current_upcoming_contests(args.force, !args.diff)
+        }
+    });
 
-    let new_contests = current_upcoming
+    let mut diff = compute_contest_diff(&local_contests, &current_upcoming);
+    skip_filtered(&mut diff);
+
+    if keep_if_none_upcoming(&mut diff, current_upcoming.len(), args.allow_empty) {
+        log_warn(&format!("No upcoming contests online, keeping the {} saved ones. \
+            Use --allow-empty if they really are all finished.", local_contests.len()));
+    }
+
+    if args.diff {
+        print!("{}", format_diff_report(&diff));
+        return;
+    }
+
+    shutdown::install_handler();
+    let changed = diff.changed.len();
+    let finished = diff.finished.clone();
+
+    if config().archive_finished {
+        archive::archive(&diff.finished);
+    }
+
+    let notifier = notify::configured(&Osascript);
+    let (local_upcoming, created_contests) = if config().daily_summary {
+        (summary::sync_daily_summaries(&Osascript, diff, &mut reminders_time), Vec::new())
+    } else {
+        sync_reminders(notifier.as_ref(), diff, &mut reminders_time)
+    };
+
+    if let Some(last_run) = status::last_run_seconds() {
+        notifier.remind_due(&local_upcoming, last_run, chrono::Utc::now().timestamp());
+    }
+
+    if let Err(e) = save_contests_locally(&local_upcoming) {
+        log_error(&format!("Failed to save local contests atomically. Error: {}", e));
+    }
+
+    if let Some(path) = &config().ics_path {
+        if let Err(e) = ics::write_ics(path, &local_upcoming) {
+            log_error(&format!("Failed to write calendar to {}. {}", path.display(), e));
+        }
+    }
+
+    if let Some(path) = &config().org_path {
+        if let Err(e) = org::write_org(path, &local_upcoming) {
+            log_error(&format!("Failed to write Org file to {}. {}", path.display(), e));
+        }
+    }
+
+    if let Some(vault) = &config().obsidian {
+        if let Err(e) = obsidian::append_to_daily_notes(vault, &local_upcoming) {
+            log_error(&format!("Failed to add contests to daily notes in {}. {}", vault.vault_path.display(), e));
+        }
+    }
+
+    // Only saved contests raise the mark, skipped ones get checked again next run.
+    let max_contest_id = local_upcoming
         .iter()
-        .filter(|contest| !local_contests.contains(contest))
-        .cloned()
-        .collect::<Vec<Contest>>();
+        .filter(|contest| contest.is_from_codeforces_api())
+        .map(|contest| contest.id)
+        .max();
+    let created_contests: Vec<&Contest> = local_upcoming
+        .iter()
+        .filter(|contest| created_contests.contains(contest))
+        .collect();
+    let created = created_contests.len();
 
-    let mut local_upcoming = local_contests
-        .into_iter()
-        .filter(|contest| current_upcoming.contains(contest))
-        .collect::<Vec<Contest>>();
+    if config().banner && created > 0 {
+        reminder::display_banner(&Osascript, &created_contests);
+    }
+    let deleted = finished
+        .iter()
+        .filter(|contest| !local_upcoming.contains(contest))
+        .count();
+    let status = status::record_timings(fetch_time, reminders_time, max_contest_id, created);
+
+    if config().notify_summary {
+        if let Some(text) = run_summary(created, changed, deleted) {
+            if let Err(error) = notifier.message("codeforces-reminder", &text) {
+                if let Some(reason) = error.reason {
+                    log_error(&format!("{} failed to send the run summary. {}", notifier.name(), reason));
+                }
+            }
+        }
+    }
+
+    if let Some(path) = &config().metrics_path {
+        let success = local::error_count() == 0;
+        if let Err(e) = metrics::write_metrics(path, local_upcoming.len(), &status, success) {
+            log_error(&format!("Failed to write metrics to {}. {}", path.display(), e));
+        }
+    }
+
+    if let Some(command) = &config().post_run_hook {
+        hook::run_post_run_hook(command, created, deleted, local_upcoming.len());
+    }
+
+    if args.cron {
+        if let Some(line) = cron_summary(created, changed, deleted, local_upcoming.len(), local::error_count()) {
+            println!("{}", line);
+        }
+    }
+
+    if local::error_count() == 0 {
+        healthcheck::ping(Ping::Success);
+    }
+
+    local::flush_log();
+}
+
+/// Reports the first error of a run, which often exits right after.
+fn run_failed(error: &str) {
+    healthcheck::ping(Ping::Fail(error));
+    notify_failure(error);
+}
+
+/// Sends a message about an error through failure_notifier in the config.
+/// Failing to send it is only logged.
+fn notify_failure(error: &str) {
+    let Some(name) = &config().failure_notifier else { return };
+    let notifier = notify::backend(name, &Osascript);
+
+    let text = format!("{}\nSee {} for details.", error, paths::log_path().display());
+    if let Err(error) = notifier.message("codeforces-reminder failed", &text) {
+        if let Some(reason) = error.reason {
+            log_error(&format!("{} failed to send the failure notification. {}", notifier.name(), reason));
+        }
+    }
+}
+
+/// Summary of a run sent through the notifier, None if the run changed nothing.
+fn run_summary(created: usize, changed: usize, deleted: usize) -> Option<String> {
+    if created + changed + deleted == 0 { return None }
+
+    let plural = |count: usize| if count == 1 { "" } else { "s" };
+    let mut parts = vec![format!("{} new contest{} scheduled", created, plural(created))];
+    if changed > 0 {
+        parts.push(format!("{} updated", changed));
+    }
+    parts.push(format!("{} expired removed", deleted));
+    Some(parts.join(", "))
+}
+
+/// Keeps the finished contests of diff saved if no contest is upcoming
+/// online, unless allow_empty is set. An empty contest list (e.g. during
+/// maintenance) would delete every reminder otherwise.
+///
+/// Returns true if the finished contests were kept.
+fn keep_if_none_upcoming(diff: &mut ContestDiff, upcoming: usize, allow_empty: bool) -> bool {
+    if upcoming > 0 || diff.finished.is_empty() || allow_empty { return false }
+
+    diff.unchanged.append(&mut diff.finished);
+    true
+}
+
+/// Summary line printed by --cron, None if the run changed nothing
+/// and logged no error, so cron sends no mail.
+fn cron_summary(created: usize, changed: usize, deleted: usize, saved: usize, errors: usize) -> Option<String> {
+    if created + changed + deleted + errors == 0 { return None }
+
+    Some(format!("codeforces-reminder: {} created, {} changed, {} deleted, {} saved, {} errors",
+        created, changed, deleted, saved, errors))
+}
+
+/// Withdraws, updates and delivers the notifications of the contests
+/// in diff using the notifier.
+///
+/// Returns the contests to save locally and the new contests
+/// that were delivered.
+fn sync_reminders(notifier: &dyn Notifier, diff: ContestDiff, reminders_time: &mut Duration) -> (Vec<Contest>, Vec<Contest>) {
+    let mut local_upcoming = diff.unchanged;
+    let mut delivered_contests = Vec::new();
+
+    // Backends that failed to deliver a saved contest are notified again.
+    for contest in local_upcoming.iter_mut().filter(|contest| !contest.failed_deliveries.is_empty()) {
+        let _context = sentry::contest_context(contest);
+        timed(reminders_time, || {
+            let result = notifier.retry(contest);
+            delivered(notifier.name(), "retry", contest, result)
+        });
+    }
+
+    // Contests whose reminder could not be deleted are kept to retry next run.
+    let mut finished = diff.finished.into_iter();
+    while let Some(contest) = finished.next() {
+        if shutdown::interrupted() {
+            // The reminders of the contests not handled yet still exist.
+            local_upcoming.push(contest);
+            local_upcoming.extend(finished);
+            local_upcoming.extend(diff.changed.into_iter().map(|(local, _)| local));
+            shutdown::save_and_exit(&local_upcoming);
+        }
+        let _context = sentry::contest_context(&contest);
+        if timed(reminders_time, || delivered(notifier.name(), "withdraw", &contest, notifier.withdraw(&contest))) {
+            history::record(Action::Deleted, &contest);
+        } else {
+            local_upcoming.push(contest);
+        }
+    }
+
+    // Changed contests keep their local version if the reminder could not
+    // be updated, so the update is retried next run.
+    let mut changed = diff.changed.into_iter();
+    while let Some((local, upcoming)) = changed.next() {
+        if shutdown::interrupted() {
+            local_upcoming.push(local);
+            local_upcoming.extend(changed.map(|(local, _)| local));
+            shutdown::save_and_exit(&local_upcoming);
+        }
+        let _context = sentry::contest_context(&local);
+        let mut contest = local.updated_from(upcoming);
+        let updated = timed(reminders_time, || {
+            let result = notifier.update(&mut contest);
+            delivered(notifier.name(), "update", &contest, result)
+        });
+        if updated {
+            history::record(Action::Updated, &contest);
+            local_upcoming.push(contest);
+        } else {
+            local_upcoming.push(local);
+        }
+    }
 
-    for mut contest in new_contests.into_iter() {
-        if let Some(start) = contest.start_time_seconds.as_mut() {
-            *start -= 1800; // Set reminder 30 minutes earlier
+    for mut contest in diff.new.into_iter() {
+        if shutdown::interrupted() {
+            shutdown::save_and_exit(&local_upcoming);
+        }
+        let _context = sentry::contest_context(&contest);
+        // Only Codeforces pages link announcements.
+        if config().scrape_descriptions && contest.description.is_none() && contest.page_url.is_none() {
+            contest.scraped_description = fetch_contest_description(&contest);
+        }
+        let created = timed(reminders_time, || {
+            let result = notifier.notify(&mut contest);
+            delivered(notifier.name(), "notify", &contest, result)
+        });
+        if !created {
+            // Left unsaved, so the next run sees the contest as new and tries again.
+            continue;
         }
-        create_reminder(&contest);
+        history::record(Action::Created, &contest);
+        delivered_contests.push(contest.clone());
         local_upcoming.push(contest);
+
+        // Saving right away means a crash before the end of the run
+        // can not lead to the reminder being created again.
+        if config().save_incrementally {
+            if let Err(e) = save_contests_locally(&local_upcoming) {
+                log_error(&format!("Failed to save local contests atomically. Error: {}", e));
+            }
+        }
     }
 
-    if let Err(e) = save_contests_locally(&local_upcoming) {
+    (local_upcoming, delivered_contests)
+}
+
+/// Sets the lead time of the saved contest with the given id, of source
+/// if given, updates its reminder and saves the override so later runs keep it.
+///
+/// Terminates with exit code 1 if the contest is not saved locally, the id
+/// is ambiguous or its reminder can not be updated.
+fn snooze(notifier: &dyn Notifier, id: usize, source: Option<&str>, lead_time: i64) {
+    let mut contests: Vec<Contest> = fetch_local_upcoming_contests().into_iter().collect();
+
+    let source = match contest::source_of_id(&contests, id, source) {
+        Ok(source) => source,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    let Some(contest) = contests.iter_mut().find(|contest| contest.is(id, source.as_deref())) else {
+        log_error(&format!("Can not snooze contest {}, it is not saved locally.", id));
+        std::process::exit(1);
+    };
+
+    contest.lead_time_override = Some(lead_time);
+    let result = notifier.update(contest);
+    if !delivered(notifier.name(), "update", contest, result) {
+        log_error(&format!("Failed to snooze contest {}, reminder was not updated.", id));
+        std::process::exit(1);
+    }
+
+    history::record(Action::Updated, contest);
+    println!("Snoozed {}, id: {} to {} seconds before its start", contest.name, contest.id, lead_time);
+
+    if let Err(e) = save_contests_locally(&contests) {
         log_error(&format!("Failed to save local contests atomically. Error: {}", e));
+        std::process::exit(1);
     }
 }
 
-/// Retrieves upcoming contests 
-/// using Codeforces's API as a HashSet.
+/// Authorizes the google-calendar notifier in the browser.
 ///
-/// Terminates and logs errors if it fails to retrieve the data 
-/// or fails to deserialize the JSON.
-fn fetch_current_upcoming_contests() -> HashSet<Contest> {
-    let url = "https://codeforces.com/api/contest.list?gym=false";
+/// Terminates with exit code 1 if google_calendar is not set or
+/// access is not granted.
+fn google_auth() {
+    let Some(google_calendar) = &config().google_calendar else {
+        eprintln!("No google_calendar in the config.");
+        std::process::exit(1);
+    };
 
-    let response: Response = match get(url) {
-        Ok(response) => response,
+    if let Err(e) = notify::authorize_google_calendar(google_calendar) {
+        eprintln!("Failed to authorize Google Calendar. {}", e);
+        std::process::exit(1);
+    }
+}
+
+/// Authorizes the outlook notifier with a device code.
+///
+/// Terminates with exit code 1 if outlook is not set or
+/// access is not granted.
+fn outlook_auth() {
+    let Some(outlook) = &config().outlook else {
+        eprintln!("No outlook in the config.");
+        std::process::exit(1);
+    };
+
+    if let Err(e) = notify::authorize_outlook(outlook) {
+        eprintln!("Failed to authorize Outlook. {}", e);
+        std::process::exit(1);
+    }
+}
+
+/// Prints the saved contests as Markdown table, or writes it to path.
+///
+/// Terminates with exit code 1 if it can not be written.
+fn render_markdown(path: Option<&std::path::Path>) {
+    let contests: Vec<Contest> = fetch_local_upcoming_contests().into_iter().collect();
+    let markdown = markdown::format_markdown(&contests, chrono::Utc::now().timestamp());
+
+    let Some(path) = path else {
+        print!("{}", markdown);
+        return;
+    };
+    if let Err(e) = local::write_atomically(path, &markdown) {
+        eprintln!("Failed to write Markdown to {}. {}", path.display(), e);
+        std::process::exit(1);
+    }
+}
+
+/// Writes the saved contests as calendar with alarms to path.
+///
+/// Terminates with exit code 1 if it can not be written.
+fn export_ics(path: &std::path::Path) {
+    let contests: Vec<Contest> = fetch_local_upcoming_contests().into_iter().collect();
+
+    if let Err(e) = ics::export_ics(path, &contests) {
+        eprintln!("Failed to write calendar to {}. {}", path.display(), e);
+        std::process::exit(1);
+    }
+}
+
+/// Prints which VEVENTs writing the saved contests to ics_path would add,
+/// remove or change, without writing.
+///
+/// Terminates with exit code 1 if ics_path is not set or can not be read.
+fn ics_diff() {
+    let Some(path) = &config().ics_path else {
+        eprintln!("No ics_path in the config.");
+        std::process::exit(1);
+    };
+
+    let contests: Vec<Contest> = fetch_local_upcoming_contests().into_iter().collect();
+
+    match ics::ics_diff_report(path, &contests) {
+        Ok(report) => print!("{}", report),
         Err(e) => {
-            log_error(&format!("Could not retrieve online contest list. {}", e));
-            std::process::exit(1); 
+            eprintln!("Failed to read {}. {}", path.display(), e);
+            std::process::exit(1);
         }
+    }
+}
+
+/// Prints the effective config and where contests, logs and
+/// the config are stored.
+fn show_config() {
+    print!("{}", config_report());
+}
+
+/// Returns what show_config prints.
+fn config_report() -> String {
+    let config_path = paths::config_path();
+    let config_state = if config_path.exists() { "" } else { " (not found, using defaults)" };
+    let data_dir = match paths::base_dir_path() {
+        Ok(dir) => dir.display().to_string(),
+        Err(e) => format!("unknown. {}", e),
     };
 
-    let response: ContestResponse = match response.json() {
-        Ok(response) => response,
-        Err(e) => {
-            log_error(&format!("Could not parse online contest JSON. {}", e));
-            std::process::exit(1); 
+    let mut report = format!("Profile: {}\n", paths::profile());
+    report += &format!("Data directory: {}\n", data_dir);
+    report += &format!("Contests file: {}\n", paths::contest_path().display());
+    report += &format!("Log file: {}\n", paths::log_path().display());
+    report += &format!("Config file: {}{}\n", config_path.display(), config_state);
+    report += "Config (secrets shown as ***):\n";
+    match config::redacted(config()).and_then(|value| serde_json::to_string_pretty(&value)) {
+        Ok(json) => report += &format!("{}\n", json),
+        Err(e) => report += &format!("Failed to serialize config: {}\n", e),
+    }
+    report
+}
+
+/// Prints name, local start time and countdown of the soonest
+/// locally saved contest on one line, e.g. for status bars.
+fn next() {
+    let contests = fetch_local_upcoming_contests();
+    let now = chrono::Utc::now().timestamp();
+
+    match contest::next_contest(&contests, now) {
+        Some(contest) => {
+            let start = contest.start_time_seconds.unwrap_or(now);
+            println!("{} | {} | in {}", contest.name, contest::format_local_time(start), contest::format_duration(start - now));
         }
+        None => println!("No upcoming contests."),
+    }
+}
+
+/// Sends one message listing the saved contests starting in the next
+/// seven days through the configured notifier, e.g. weekly from cron.
+///
+/// Terminates with exit code 1 if it can not be sent.
+fn digest() {
+    let now = chrono::Utc::now().timestamp();
+    let week_end = now + 7 * 24 * 60 * 60;
+
+    let mut contests: Vec<Contest> = fetch_local_upcoming_contests()
+        .into_iter()
+        .filter(|contest| contest.start_time_seconds.is_some_and(|start| start > now && start <= week_end))
+        .collect();
+    contests.sort_by_key(|contest| (contest.start_time_seconds, contest.id));
+
+    let text = if contests.is_empty() {
+        "No contests in the coming week.".to_string()
+    } else {
+        contests
+            .iter()
+            .map(|contest| {
+                let start = contest.start_time_seconds.unwrap_or(now);
+                let duration = contest.duration_seconds.map(|duration| format!(" ({})", contest::format_duration(duration)));
+                format!("{} {}{}", contest::format_local_time(start), contest.name, duration.unwrap_or_default())
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
     };
 
-    if response.status != "OK" {
-        let comment = response.comment.unwrap_or_else(|| "No comment.".to_string());
-        log_error(&format!("Codeforces response status FAILED. Comment: {}.", comment));
+    let notifier = notify::configured(&Osascript);
+    if let Err(error) = notifier.message("Contests this week", &text) {
+        eprintln!("{} failed to send the digest. {}", notifier.name(), error.reason.unwrap_or_default());
         std::process::exit(1);
     }
+    println!("Sent {} contests through {}.", contests.len(), notifier.name());
+}
 
-    response.result
-        .into_iter()
-        .filter(|contest| contest.phase == Phase::Before)
-        .collect::<HashSet<Contest>>()
+/// Opens the page of the soonest locally saved contest in the browser.
+///
+/// Prints a message and returns if there is no upcoming contest,
+/// terminates with exit code 1 if the browser can not be opened.
+fn open_next() {
+    let contests = fetch_local_upcoming_contests();
+
+    let Some(contest) = contest::next_contest(&contests, chrono::Utc::now().timestamp()) else {
+        println!("No upcoming contests.");
+        return;
+    };
+
+    let opener = if cfg!(target_os = "macos") { "open" } else { "xdg-open" };
+    let url = contest.url();
+
+    match std::process::Command::new(opener).arg(&url).status() {
+        Ok(status) if status.success() => println!("Opened {}, id: {}", contest.name, contest.id),
+        Ok(status) => {
+            eprintln!("{} {} failed with {}", opener, url, status);
+            std::process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("Failed to run {} {}. {}", opener, url, e);
+            std::process::exit(1);
+        }
+    }
 }
 
-/// Creates a reminder using osascript run as a command.
+/// Adds, removes or lists the watch rules saved in config.json.
 ///
-/// This function ignores contests without a starting time 
-/// (field start_time_seconds in struct Contest).
+/// Terminates with exit code 2 when adding an invalid regex
+/// and 1 when removing a rule that does not exist.
+fn watch(command: WatchCommand) {
+    let mut config = config().clone();
+
+    match command {
+        WatchCommand::Add(rule) => {
+            if let Err(e) = regex::Regex::new(&rule) {
+                eprintln!("Invalid watch rule {}. {}", rule, e);
+                std::process::exit(2);
+            }
+            if config.watch.contains(&rule) {
+                println!("Already watching {}", rule);
+                return;
+            }
+            config.watch.push(rule);
+        }
+        WatchCommand::Remove(rule) => {
+            let Some(index) = config.watch.iter().position(|r| *r == rule) else {
+                eprintln!("No watch rule {}", rule);
+                std::process::exit(1);
+            };
+            config.watch.remove(index);
+        }
+        WatchCommand::List => {
+            if config.watch.is_empty() {
+                println!("No watch rules, every contest gets a reminder.");
+            }
+            for rule in &config.watch {
+                println!("{}", rule);
+            }
+            return;
+        }
+    }
+
+    if let Err(e) = save_config(&config) {
+        log_error(&format!("Failed to save config. Error: {}", e));
+        eprintln!("Failed to save config. Error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+/// Adds or removes a pinned contest id in config.json.
 ///
-/// Will not terminate if it fails to set a reminder, but will log the failure.
-fn create_reminder(contest: &Contest) {
-    let Some(start) = contest.start_time_seconds else {
-        log_error(&format!("Contest without start time: {}, {}", contest.id, contest.name));
-        return
-    };
+/// Terminates with exit code 1 when unpinning an id that is not pinned.
+fn pin(command: PinCommand, source: Option<&str>) {
+    let mut config = config().clone();
 
-    let time = Utc.timestamp_opt(start, 0)
-        .unwrap()
-        .with_timezone(&chrono::Local)
-        .format("%d/%m/%Y %H:%M %Z")
-        .to_string();
+    match command {
+        PinCommand::Pin(id) => {
+            let pinned = PinnedContest::new(id, source);
+            if config.pinned.contains(&pinned) {
+                println!("Contest {} is already pinned", pinned);
+                return;
+            }
+            config.pinned.push(pinned);
+        }
+        PinCommand::Unpin(id) => {
+            let pinned = PinnedContest::new(id, source);
+            let Some(index) = config.pinned.iter().position(|entry| *entry == pinned) else {
+                eprintln!("Contest {} is not pinned", pinned);
+                std::process::exit(1);
+            };
+            config.pinned.remove(index);
+        }
+    }
 
-    let apple_script = format!(
-        r#"
-        with timeout of 30 seconds 
-            tell application "Reminders"
-                set newReminder to make new reminder with properties {{name:"{}, id: {}", body:"{}"}}
-                set due date of newReminder to date "{}"
-            end tell
-        end timeout"#, contest.name, contest.id, contest.description.as_deref().unwrap_or(""), time);
+    if let Err(e) = save_config(&config) {
+        log_error(&format!("Failed to save config. Error: {}", e));
+        eprintln!("Failed to save config. Error: {}", e);
+        std::process::exit(1);
+    }
+}
 
-    let status = Command::new("osascript")
-        .arg("-e")
-        .arg(apple_script)
-        .status();
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    if let Err(ref e) = status {
-        log_error(&format!("Failed to run osascript for contest {}, id: {}. Error: {}", contest.name, contest.id, e));
-        return;
+    #[test]
+    fn cron_is_silent_without_changes_or_errors() {
+        assert_eq!(cron_summary(0, 0, 0, 12, 0), None);
     }
 
-    if !status.unwrap().success() {
-        log_error(&format!("Failed to add reminder for Contest {}, id: {}", contest.name, contest.id));
+    #[test]
+    fn cron_summarizes_changes_and_errors() {
+        assert_eq!(cron_summary(2, 1, 3, 12, 0).as_deref(),
+            Some("codeforces-reminder: 2 created, 1 changed, 3 deleted, 12 saved, 0 errors"));
+        assert_eq!(cron_summary(0, 0, 0, 12, 1).as_deref(),
+            Some("codeforces-reminder: 0 created, 0 changed, 0 deleted, 12 saved, 1 errors"));
+    }
+
+    fn diff_without_upcoming(saved: &[Contest]) -> ContestDiff {
+        compute_contest_diff(&saved.iter().cloned().collect(), &std::collections::HashSet::new())
+    }
+
+    #[test]
+    fn empty_contest_list_keeps_saved_contests() {
+        let mut diff = diff_without_upcoming(&[contest::tests::contest(2101, "Round 1021", 0)]);
+
+        assert!(keep_if_none_upcoming(&mut diff, 0, false));
+        assert!(diff.finished.is_empty());
+        assert_eq!(diff.unchanged.len(), 1);
+    }
+
+    #[test]
+    fn allow_empty_deletes_saved_contests() {
+        let mut diff = diff_without_upcoming(&[contest::tests::contest(2101, "Round 1021", 0)]);
+
+        assert!(!keep_if_none_upcoming(&mut diff, 0, true));
+        assert_eq!(diff.finished.len(), 1);
+    }
+
+    #[test]
+    fn finished_contests_are_deleted_if_others_are_upcoming() {
+        let mut diff = diff_without_upcoming(&[contest::tests::contest(2101, "Round 1021", 0)]);
+
+        assert!(!keep_if_none_upcoming(&mut diff, 1, false));
+        assert_eq!(diff.finished.len(), 1);
+        assert!(!keep_if_none_upcoming(&mut ContestDiff::default(), 0, false));
+    }
+
+    /// Creates the reminder of the first contest and, when asked to create
+    /// the next one, records what a crash at that point would leave saved.
+    struct CrashAfterFirst {
+        calls: std::cell::Cell<usize>,
+        saved_at_crash: std::cell::RefCell<Option<std::collections::HashSet<Contest>>>,
+    }
+
+    impl Notifier for CrashAfterFirst {
+        fn name(&self) -> &'static str { "crash" }
+
+        fn notify(&self, contest: &mut Contest) -> Result<(), notify::NotifyError> {
+            self.calls.set(self.calls.get() + 1);
+            if self.calls.get() == 1 {
+                contest.reminder_ids = vec!["x-apple-reminder://1".to_string()];
+            } else {
+                *self.saved_at_crash.borrow_mut() = Some(local::fetch_local_upcoming_contests());
+            }
+            Ok(())
+        }
+
+        fn message(&self, _: &str, _: &str) -> Result<(), notify::NotifyError> { Ok(()) }
+    }
+
+    #[test]
+    fn crash_after_first_reminder_does_not_create_it_again() {
+        let upcoming: std::collections::HashSet<Contest> =
+            [contest::tests::contest(2101, "Round 1021", 1_000), contest::tests::contest(2102, "Round 1022", 2_000)].into();
+        let notifier = CrashAfterFirst { calls: Default::default(), saved_at_crash: Default::default() };
+        let config = config::Config { save_incrementally: true, ..config::Config::default() };
+
+        let mut reminders_time = Duration::ZERO;
+
+        config::tests::with_config(config, || {
+            let diff = compute_contest_diff(&Default::default(), &upcoming);
+            sync_reminders(&notifier, diff, &mut reminders_time);
+        });
+        let _ = std::fs::remove_file(paths::contest_path());
+
+        let saved = notifier.saved_at_crash.into_inner().unwrap();
+        let next_run = compute_contest_diff(&saved, &upcoming);
+        assert_eq!(next_run.new.iter().map(|contest| contest.id).collect::<Vec<_>>(), [2102]);
+        assert_eq!(next_run.unchanged.iter().map(|contest| contest.id).collect::<Vec<_>>(), [2101]);
+    }
+
+    /// Fails like Reminders without automation access until authorized.
+    struct Authorization {
+        authorized: std::cell::Cell<bool>,
+    }
+
+    impl Notifier for Authorization {
+        fn name(&self) -> &'static str { "authorization" }
+
+        fn notify(&self, contest: &mut Contest) -> Result<(), notify::NotifyError> {
+            if !self.authorized.get() {
+                return Err(notify::NotifyError::new("Not authorized to send Apple events to Reminders. (-1743)"));
+            }
+            contest.reminder_ids = vec![format!("x-apple-reminder://{}", contest.id)];
+            Ok(())
+        }
+
+        fn message(&self, _: &str, _: &str) -> Result<(), notify::NotifyError> { Ok(()) }
+    }
+
+    #[test]
+    fn contest_denied_in_one_run_is_created_in_the_next() {
+        let upcoming: std::collections::HashSet<Contest> = [contest::tests::contest(2101, "Round 1021", 1_000)].into();
+        let notifier = Authorization { authorized: std::cell::Cell::new(false) };
+        let mut reminders_time = Duration::ZERO;
+
+        let (denied, delivered) = sync_reminders(&notifier, compute_contest_diff(&Default::default(), &upcoming), &mut reminders_time);
+        assert!(denied.is_empty());
+        assert!(delivered.is_empty());
+
+        notifier.authorized.set(true);
+        let next_run = compute_contest_diff(&denied.into_iter().collect(), &upcoming);
+        let (saved, delivered) = sync_reminders(&notifier, next_run, &mut reminders_time);
+        assert_eq!(saved.len(), 1);
+        assert_eq!(saved[0].reminder_ids, ["x-apple-reminder://2101"]);
+        assert_eq!(delivered.iter().map(|contest| contest.id).collect::<Vec<_>>(), [2101]);
+    }
+
+    #[test]
+    fn show_config_prints_the_data_directory() {
+        let data_dir = paths::data_dir_from(Some(std::env::temp_dir().join("codeforces-reminder-test").into()), None).unwrap();
+
+        let report = config_report();
+
+        assert!(report.contains(&format!("Data directory: {}\n", data_dir.display())));
+        assert!(report.contains(&format!("Contests file: {}\n", data_dir.join("contests.json").display())));
     }
 }
@@ -0,0 +1,61 @@
+use crate::contest::{format_duration, format_local_time, Contest};
+
+use std::fmt::Write;
+
+/// Formats the contests with a start time as Markdown table with their
+/// name, local start time, countdown from now and link, soonest first.
+pub fn format_markdown(contests: &[Contest], now: i64) -> String {
+    let mut contests: Vec<&Contest> = contests.iter().filter(|contest| contest.start_time_seconds.is_some()).collect();
+    contests.sort_by_key(|contest| (contest.start_time_seconds, contest.id));
+
+    let mut markdown = String::from("| Contest | Start | Starts in | Link |\n| --- | --- | --- | --- |\n");
+
+    for contest in contests {
+        let start = contest.start_time_seconds.unwrap_or_default();
+        let countdown = if start > now { format_duration(start - now) } else { "running".to_string() };
+        let url = contest.url();
+
+        let _ = writeln!(markdown, "| {} | {} | {} | [{}]({}) |",
+            cell(&contest.name), format_local_time(start), countdown, cell(&url), url);
+    }
+    markdown
+}
+
+/// Escapes pipes, which would end the table cell.
+fn cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contest::tests::contest;
+
+    #[test]
+    fn table_row_per_contest_soonest_first() {
+        let contests = [
+            contest(2102, "Round 1022", 1_000 + 7200),
+            Contest { start_time_seconds: None, ..contest(2103, "Round 1023", 0) },
+            contest(2101, "Round | 1021", 1_000 + 5400),
+            contest(2100, "Round 1020", 900),
+        ];
+        let mut reversed = contests.clone();
+        reversed.reverse();
+
+        let markdown = format_markdown(&contests, 1_000);
+
+        assert_eq!(markdown, format!("\
+| Contest | Start | Starts in | Link |
+| --- | --- | --- | --- |
+| Round 1020 | {} | running | [https://codeforces.com/contests/2100](https://codeforces.com/contests/2100) |
+| Round \\| 1021 | {} | 1 hour 30 minutes | [https://codeforces.com/contests/2101](https://codeforces.com/contests/2101) |
+| Round 1022 | {} | 2 hours | [https://codeforces.com/contests/2102](https://codeforces.com/contests/2102) |
+", format_local_time(900), format_local_time(6400), format_local_time(8200)));
+        assert_eq!(markdown, format_markdown(&reversed, 1_000));
+    }
+
+    #[test]
+    fn cells_stay_on_one_line() {
+        assert_eq!(cell("Round 1\n| Div. 2"), "Round 1 \\| Div. 2");
+    }
+}
@@ -0,0 +1,68 @@
+use crate::local::write_atomically;
+use crate::status::Status;
+
+use std::fmt::Write;
+use std::path::Path;
+
+/// Writes the metrics of the last run in the Prometheus text format
+/// (e.g. for the textfile collector of the node exporter).
+///
+/// The file is replaced atomically, so collectors never read half of it.
+pub fn write_metrics(path: &Path, upcoming: usize, status: &Status, success: bool) -> std::io::Result<()> {
+    let last_run = chrono::Utc::now().timestamp();
+    write_atomically(path, &format_metrics(upcoming, status.reminders_created_total, last_run, success))
+}
+
+/// Formats the metrics with HELP and TYPE lines for every metric.
+fn format_metrics(upcoming: usize, created_total: u64, last_run: i64, success: bool) -> String {
+    let metrics: [(&str, &str, &str, String); 4] = [
+        ("cfr_upcoming_contests", "gauge", "Contests saved locally after the last run.", upcoming.to_string()),
+        ("cfr_reminders_created_total", "counter", "Reminders created by all runs.", created_total.to_string()),
+        ("cfr_last_run_timestamp", "gauge", "Unix time the last run finished.", last_run.to_string()),
+        ("cfr_last_run_success", "gauge", "1 if the last run logged no errors, 0 otherwise.", (success as u8).to_string()),
+    ];
+
+    let mut output = String::new();
+    for (name, kind, help, value) in metrics {
+        let _ = writeln!(output, "# HELP {} {}", name, help);
+        let _ = writeln!(output, "# TYPE {} {}", name, kind);
+        let _ = writeln!(output, "{} {}", name, value);
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_prometheus_exposition() {
+        assert_eq!(format_metrics(3, 42, 1745850900, true), "\
+# HELP cfr_upcoming_contests Contests saved locally after the last run.
+# TYPE cfr_upcoming_contests gauge
+cfr_upcoming_contests 3
+# HELP cfr_reminders_created_total Reminders created by all runs.
+# TYPE cfr_reminders_created_total counter
+cfr_reminders_created_total 42
+# HELP cfr_last_run_timestamp Unix time the last run finished.
+# TYPE cfr_last_run_timestamp gauge
+cfr_last_run_timestamp 1745850900
+# HELP cfr_last_run_success 1 if the last run logged no errors, 0 otherwise.
+# TYPE cfr_last_run_success gauge
+cfr_last_run_success 1
+");
+    }
+
+    #[test]
+    fn every_sample_follows_its_type() {
+        let metrics = format_metrics(0, 0, 0, false);
+        let lines: Vec<&str> = metrics.lines().collect();
+
+        for sample in lines.iter().filter(|line| !line.starts_with('#')) {
+            let (name, value) = sample.split_once(' ').unwrap();
+            assert!(value.parse::<f64>().is_ok(), "{}", sample);
+            assert!(lines.iter().any(|line| line.starts_with(&format!("# TYPE {} ", name))), "{}", sample);
+        }
+        assert!(metrics.ends_with("cfr_last_run_success 0\n"));
+    }
+}
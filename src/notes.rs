@@ -0,0 +1,109 @@
+use crate::contest::Contest;
+use crate::local::log_error;
+use crate::paths::notes_path;
+
+use regex::Regex;
+use serde::Deserialize;
+use std::fs;
+use std::sync::OnceLock;
+
+static NOTES: OnceLock<Vec<(Regex, String)>> = OnceLock::new();
+
+/// One entry of notes.json.
+#[derive(Deserialize, Debug)]
+struct Note {
+    /// Regex matched against the contest name.
+    pattern: String,
+    /// Text added to the reminder body.
+    note: String,
+}
+
+/// Returns the note of the first entry in notes.json
+/// whose pattern matches the name of the contest.
+pub fn note_for(contest: &Contest) -> Option<&'static str> {
+    first_match(NOTES.get_or_init(load_notes), contest)
+}
+
+/// Returns the note of the first of notes whose pattern matches the name of the contest.
+fn first_match<'a>(notes: &'a [(Regex, String)], contest: &Contest) -> Option<&'a str> {
+    notes
+        .iter()
+        .find(|(pattern, _)| pattern.is_match(&contest.name))
+        .map(|(_, note)| note.as_str())
+}
+
+/// Deserializes notes.json and compiles its patterns.
+///
+/// A missing file means no notes. An unreadable file is logged and
+/// ignored, as are entries with an invalid pattern.
+fn load_notes() -> Vec<(Regex, String)> {
+    let path = notes_path();
+
+    if !path.exists() { return Vec::new() }
+
+    let notes: Vec<Note> = match fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|contents| serde_json::from_str(&contents).map_err(|e| e.to_string())) {
+        Ok(notes) => notes,
+        Err(e) => {
+            log_error(&format!("Ignoring unreadable notes file. {}", e));
+            return Vec::new();
+        }
+    };
+
+    compile(notes)
+}
+
+/// Compiles the patterns of the notes, skipping and logging invalid ones.
+fn compile(notes: Vec<Note>) -> Vec<(Regex, String)> {
+    notes
+        .into_iter()
+        .filter_map(|note| match Regex::new(&note.pattern) {
+            Ok(pattern) => Some((pattern, note.note)),
+            Err(e) => {
+                log_error(&format!("Skipping note with invalid pattern {}. {}", note.pattern, e));
+                None
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contest::tests::contest;
+
+    fn notes(json: &str) -> Vec<(Regex, String)> {
+        compile(serde_json::from_str(json).unwrap())
+    }
+
+    #[test]
+    fn first_matching_note_wins() {
+        let notes = notes(r#"[
+            {"pattern": "Educational", "note": "Bring the DSU template"},
+            {"pattern": "Round", "note": "Sleep early"}
+        ]"#);
+
+        assert_eq!(first_match(&notes, &contest(1, "Educational Codeforces Round 178", 0)), Some("Bring the DSU template"));
+        assert_eq!(first_match(&notes, &contest(2, "Codeforces Round 1021 (Div. 2)", 0)), Some("Sleep early"));
+    }
+
+    #[test]
+    fn no_note_without_match() {
+        let notes = notes(r#"[{"pattern": "Educational", "note": "Bring the DSU template"}]"#);
+
+        assert_eq!(first_match(&notes, &contest(1, "Kotlin Heroes: Practice", 0)), None);
+        assert_eq!(first_match(&[], &contest(1, "Educational Codeforces Round 178", 0)), None);
+    }
+
+    #[test]
+    fn invalid_patterns_are_skipped() {
+        let notes = notes(r#"[
+            {"pattern": "(Educational", "note": "Never shown"},
+            {"pattern": "Educational", "note": "Bring the DSU template"}
+        ]"#);
+
+        assert_eq!(notes.len(), 1);
+        assert_eq!(first_match(&notes, &contest(1, "Educational Codeforces Round 178", 0)), Some("Bring the DSU template"));
+    }
+}
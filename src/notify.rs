@@ -0,0 +1,416 @@
+use crate::config::config;
+use crate::contest::{format_duration, format_local_time, Contest};
+use crate::local::log_error;
+use crate::reminder::{contest_lead_times, due_time, registration_due, registration_name, reminder_name};
+use crate::reminder::{CommandRunner, Reminders};
+
+use chrono::{Local, TimeZone};
+use reqwest::blocking::{Client, RequestBuilder};
+use serde_json::{json, Value};
+use std::process::Command;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+mod caldav;
+use caldav::Caldav;
+
+mod calendar;
+use calendar::Calendar;
+
+mod discord;
+use discord::Discord;
+
+mod fan_out;
+use fan_out::FanOut;
+
+mod email;
+use email::Email;
+
+mod google_calendar;
+use google_calendar::GoogleCalendar;
+pub use google_calendar::authorize as authorize_google_calendar;
+
+mod gotify;
+use gotify::Gotify;
+
+mod matrix;
+use matrix::Matrix;
+
+mod notify_send;
+use notify_send::NotifySend;
+
+mod notion;
+use notion::Notion;
+
+mod ntfy;
+use ntfy::Ntfy;
+
+mod oauth;
+
+mod omnifocus;
+use omnifocus::OmniFocus;
+
+mod outlook;
+use outlook::Outlook;
+pub use outlook::authorize as authorize_outlook;
+
+mod pushbullet;
+use pushbullet::Pushbullet;
+
+mod pushover;
+use pushover::Pushover;
+
+mod shortcuts;
+use shortcuts::Shortcuts;
+
+mod slack;
+use slack::Slack;
+
+mod telegram;
+use telegram::Telegram;
+
+mod things;
+use things::Things;
+
+mod toast;
+use toast::Toast;
+
+mod todoist;
+use todoist::Todoist;
+
+#[cfg(feature = "twilio")]
+mod twilio;
+#[cfg(feature = "twilio")]
+use twilio::Twilio;
+
+mod webhook;
+use webhook::Webhook;
+
+/// How long backends wait for their HTTP APIs.
+const HTTP_TIMEOUT: Duration = Duration::from_secs(30);
+
+static HTTP_CLIENT: OnceLock<Client> = OnceLock::new();
+
+/// Names of the backends that can be set as notifier in the config.
+const NOTIFIERS: [&str; 23] = [
+    "reminders", "notify-send", "toast", "telegram", "discord", "slack", "email", "ntfy", "gotify", "pushover",
+    "pushbullet", "matrix", "webhook", "twilio", "shortcuts", "things", "omnifocus", "todoist", "notion",
+    "google-calendar", "caldav", "calendar", "outlook",
+];
+
+/// Why a notifier could not deliver the notifications of a contest.
+#[derive(Debug)]
+pub struct NotifyError {
+    /// Reason of the failure, None if the backend already logged it,
+    /// e.g. the osascript error.
+    pub reason: Option<String>,
+}
+
+impl NotifyError {
+    /// Failure the backend already logged.
+    pub fn logged() -> Self {
+        NotifyError { reason: None }
+    }
+
+    /// Failure for this reason, logged by the sync.
+    pub fn new(reason: impl Into<String>) -> Self {
+        NotifyError { reason: Some(reason.into()) }
+    }
+}
+
+/// Delivers the notifications of contests, e.g. as macOS Reminders.
+///
+/// The sync calls notify for new contests, update for changed ones and
+/// withdraw for finished ones. Backends that keep track of what they
+/// delivered save its ids in contest.reminder_ids, so notify and update
+/// take the contest mutably.
+pub trait Notifier {
+    /// Name of the backend used in logs.
+    fn name(&self) -> &'static str;
+
+    /// Delivers the notifications of a new contest.
+    fn notify(&self, contest: &mut Contest) -> Result<(), NotifyError>;
+
+    /// Delivers a message that is not about a single contest right away,
+    /// e.g. the summary of a run.
+    fn message(&self, title: &str, text: &str) -> Result<(), NotifyError>;
+
+    /// Brings the notifications of a changed contest up to date.
+    /// Does nothing by default, for backends that can not change
+    /// what they delivered.
+    fn update(&self, contest: &mut Contest) -> Result<(), NotifyError> {
+        let _ = contest;
+        Ok(())
+    }
+
+    /// Removes the notifications of a contest that is no longer upcoming.
+    /// Does nothing by default, for backends that can not take back
+    /// what they delivered.
+    fn withdraw(&self, contest: &Contest) -> Result<(), NotifyError> {
+        let _ = contest;
+        Ok(())
+    }
+
+    /// Notifies the backends in contest.failed_deliveries again, keeping
+    /// those that fail again. Does nothing by default, for backends that
+    /// deliver through a single notifier and never save a failed contest.
+    fn retry(&self, contest: &mut Contest) -> Result<(), NotifyError> {
+        let _ = contest;
+        Ok(())
+    }
+
+    /// Delivers a notification of a saved contest titled title that became
+    /// due since the last run, for backends that can not schedule what
+    /// they deliver. Does nothing by default.
+    fn remind(&self, contest: &Contest, title: &str) -> Result<(), NotifyError> {
+        let _ = (contest, title);
+        Ok(())
+    }
+
+    /// Calls remind for every notification of the contests (see scheduled)
+    /// that became due after since and until now.
+    fn remind_due(&self, contests: &[Contest], since: i64, now: i64) {
+        for contest in contests {
+            let Ok(notifications) = scheduled(contest) else { continue };
+            let _context = crate::sentry::contest_context(contest);
+
+            for (_, title) in notifications.into_iter().filter(|(due, _)| since < *due && *due <= now) {
+                let result = self.remind(contest, &title);
+                delivered(self.name(), "remind of", contest, result);
+            }
+        }
+    }
+}
+
+/// Returns the notifiers in the config: all of notifiers if there are any,
+/// otherwise notifier, or the default of the platform: Reminders on macOS,
+/// toast on Windows and notify-send everywhere else.
+///
+/// The runner is used by the backends scripting macOS apps, e.g. Reminders.
+/// Terminates and logs errors if the config names an unknown backend.
+pub fn configured(runner: &dyn CommandRunner) -> Box<dyn Notifier + '_> {
+    if !config().notifiers.is_empty() {
+        return Box::new(FanOut::new(runner, &config().notifiers))
+    }
+
+    backend(config().notifier.as_deref().unwrap_or(default_backend()), runner)
+}
+
+/// Names of the backends configured returns, in the order of notifiers.
+pub fn configured_names() -> Vec<&'static str> {
+    if !config().notifiers.is_empty() {
+        return config().notifiers.iter().map(|settings| settings.backend.as_str()).collect()
+    }
+
+    vec![config().notifier.as_deref().unwrap_or(default_backend())]
+}
+
+/// Backend used if the config names none.
+fn default_backend() -> &'static str {
+    if cfg!(target_os = "macos") {
+        "reminders"
+    } else if cfg!(windows) {
+        "toast"
+    } else {
+        "notify-send"
+    }
+}
+
+/// Returns the backend with this name, see NOTIFIERS.
+///
+/// Terminates and logs errors if the name is unknown or the config
+/// lacks the settings of the backend.
+pub fn backend<'a>(name: &str, runner: &'a dyn CommandRunner) -> Box<dyn Notifier + 'a> {
+    match name {
+        "reminders" => Box::new(Reminders::new(runner)),
+        "notify-send" => Box::new(NotifySend),
+        "toast" => Box::new(Toast),
+        "telegram" => Box::new(Telegram::new(section("telegram", &config().telegram))),
+        "discord" => Box::new(Discord::new(section::<String>("discord_webhook_url", &config().discord_webhook_url))),
+        "slack" => Box::new(Slack::new(section("slack", &config().slack))),
+        "email" => Box::new(Email::new(section("email", &config().email))),
+        "ntfy" => Box::new(Ntfy::new(section("ntfy", &config().ntfy))),
+        "gotify" => Box::new(Gotify::new(section("gotify", &config().gotify))),
+        "pushover" => Box::new(Pushover::new(section("pushover", &config().pushover))),
+        "pushbullet" => Box::new(Pushbullet::new(section::<String>("pushbullet_token", &config().pushbullet_token))),
+        "matrix" => Box::new(Matrix::new(section("matrix", &config().matrix))),
+        "webhook" => Box::new(Webhook::new(section("webhook", &config().webhook))),
+        "shortcuts" => Box::new(Shortcuts::new(section::<String>("shortcut_name", &config().shortcut_name))),
+        "things" => Box::new(Things::new(runner, config().things_project.as_deref())),
+        "omnifocus" => Box::new(OmniFocus::new(runner, config().omnifocus_project.as_deref())),
+        "todoist" => Box::new(Todoist::new(section("todoist", &config().todoist))),
+        "notion" => Box::new(Notion::new(section("notion", &config().notion))),
+        "google-calendar" => Box::new(GoogleCalendar::new(section("google_calendar", &config().google_calendar))),
+        "caldav" => Box::new(Caldav::new(section("caldav", &config().caldav))),
+        "calendar" => Box::new(Calendar::new(runner, &config().calendar_name)),
+        "outlook" => Box::new(Outlook::new(section("outlook", &config().outlook))),
+        #[cfg(feature = "twilio")]
+        "twilio" => Box::new(Twilio::new(section("twilio", &config().twilio))),
+        #[cfg(not(feature = "twilio"))]
+        "twilio" => {
+            log_error("The twilio notifier needs a build with the twilio feature (cargo build --features twilio).");
+            std::process::exit(1);
+        }
+        other => {
+            log_error(&format!("Unknown notifier {}, expected one of {}.", other, NOTIFIERS.join(", ")));
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Returns the config field of a backend, named name.
+///
+/// Terminates and logs errors if the backend is chosen without one.
+fn section<'a, T>(name: &str, section: &'a Option<T>) -> &'a T {
+    section.as_ref().unwrap_or_else(|| {
+        log_error(&format!("The notifier needs {} in the config.", name));
+        std::process::exit(1);
+    })
+}
+
+/// Message announcing a contest: its name, local start time with
+/// countdown, duration and link.
+pub fn announcement(contest: &Contest) -> String {
+    let mut lines = vec![contest.name.clone()];
+
+    if let Some(start) = contest.start_time_seconds {
+        let countdown = start - chrono::Utc::now().timestamp();
+        lines.push(format!("Starts {} (in {})", format_local_time(start), format_duration(countdown.max(0))));
+    }
+    if let Some(duration) = contest.duration_seconds {
+        lines.push(format!("Lasts {}", format_duration(duration)));
+    }
+    lines.push(contest.url());
+
+    lines.join("\n")
+}
+
+/// JSON describing an event ("new", "changed" or "finished") of a contest:
+/// the event, the contest as saved in contests.json, its local start time
+/// in RFC 3339 and its link.
+pub fn contest_event(event: &str, contest: &Contest) -> Value {
+    let start_local = contest.start_time_seconds
+        .and_then(|start| Local.timestamp_opt(start, 0).single())
+        .map(|start| start.to_rfc3339());
+
+    json!({
+        "event": event,
+        "contest": contest,
+        "start_local": start_local,
+        "url": contest.url(),
+    })
+}
+
+/// HTTP client of the backends, sending the User-Agent from the config.
+///
+/// Terminates and logs errors if the client can not be built.
+pub fn http_client() -> &'static Client {
+    HTTP_CLIENT.get_or_init(|| {
+        match Client::builder().user_agent(&config().user_agent).timeout(HTTP_TIMEOUT).build() {
+            Ok(client) => client,
+            Err(e) => {
+                log_error(&format!("Could not create HTTP client. {}", e));
+                std::process::exit(1);
+            }
+        }
+    })
+}
+
+/// Sends the request of a backend and returns the response body.
+///
+/// Fails with the status and body of unsuccessful responses. The URL is
+/// left out of errors, as it often holds a token.
+pub fn send(request: RequestBuilder) -> Result<String, NotifyError> {
+    let response = request.send().map_err(|e| NotifyError::new(format!("Request failed. {}", e.without_url())))?;
+    let status = response.status();
+    let body = response.text().map_err(|e| NotifyError::new(format!("Failed to read response. {}", e.without_url())))?;
+
+    if !status.is_success() {
+        return Err(NotifyError::new(format!("Request failed with {}. Response: {}", status, body.trim())))
+    }
+    Ok(body)
+}
+
+/// Due times and titles of the notifications of a contest, like the reminders:
+/// one per lead time and one before registration closes if it requires one.
+///
+/// Fails for contests without a start time.
+pub fn scheduled(contest: &Contest) -> Result<Vec<(i64, String)>, NotifyError> {
+    let Some(start) = contest.start_time_seconds else {
+        return Err(NotifyError::new("Contest without start time"))
+    };
+
+    let mut notifications: Vec<(i64, String)> = contest_lead_times(contest)
+        .into_iter()
+        .map(|lead_time| (due_time(contest, start, lead_time), reminder_name(contest)))
+        .collect();
+    if let Some(due) = registration_due(contest) {
+        notifications.push((due, registration_name(contest)));
+    }
+    Ok(notifications)
+}
+
+/// Runs a command of a backend, failing with its stderr
+/// if it can not be run or exits unsuccessfully.
+pub fn run_command(command: &mut Command) -> Result<(), NotifyError> {
+    let program = command.get_program().to_string_lossy().into_owned();
+
+    match command.output() {
+        Ok(output) if output.status.success() => Ok(()),
+        Ok(output) => Err(NotifyError::new(format!("{} failed with {}. Error: {}",
+            program, output.status, String::from_utf8_lossy(&output.stderr).trim()))),
+        Err(e) => Err(NotifyError::new(format!("Failed to run {}. {}", program, e))),
+    }
+}
+
+/// Runs the AppleScript of a backend with the runner and returns what it
+/// printed, failing with its stderr if it can not be run or fails.
+pub fn run_apple_script(runner: &dyn CommandRunner, apple_script: &str) -> Result<String, NotifyError> {
+    match runner.run(apple_script) {
+        Ok(output) if output.status.success() => Ok(String::from_utf8_lossy(&output.stdout).trim().to_string()),
+        Ok(output) => Err(NotifyError::new(format!("osascript failed with {}. Error: {}",
+            output.status, String::from_utf8_lossy(&output.stderr).trim()))),
+        Err(e) => Err(NotifyError::new(format!("Failed to run osascript. {}", e))),
+    }
+}
+
+/// Returns whether a delivery succeeded, logging the error unless
+/// the backend already did.
+pub fn delivered(notifier: &str, action: &str, contest: &Contest, result: Result<(), NotifyError>) -> bool {
+    let Err(error) = result else { return true };
+
+    if let Some(reason) = error.reason {
+        log_error(&format!("{} failed to {} {}, id: {}. {}", notifier, action, contest.name, contest.id, reason));
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::tests::with_config;
+    use crate::config::{Config, NotifierSettings};
+
+    fn settings(backend: &str) -> NotifierSettings {
+        NotifierSettings { backend: backend.to_string(), lead_times_seconds: None, contest_type: None, pattern: None }
+    }
+
+    #[test]
+    fn configured_names_prefer_notifiers() {
+        let config = Config {
+            notifier: Some("telegram".to_string()),
+            notifiers: vec![settings("reminders"), settings("ntfy")],
+            ..Config::default()
+        };
+
+        assert_eq!(with_config(config, configured_names), ["reminders", "ntfy"]);
+    }
+
+    #[test]
+    fn configured_names_fall_back_to_notifier_and_default() {
+        let config = Config { notifier: Some("discord".to_string()), ..Config::default() };
+
+        assert_eq!(with_config(config, configured_names), ["discord"]);
+        assert_eq!(with_config(Config::default(), configured_names), [default_backend()]);
+    }
+}
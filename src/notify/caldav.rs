@@ -0,0 +1,84 @@
+use crate::config::CaldavConfig;
+use crate::contest::Contest;
+use crate::ics::format_ics_with_alarms;
+use crate::notify::{http_client, send, Notifier, NotifyError};
+
+use reqwest::blocking::RequestBuilder;
+
+/// Notifier saving an event per new contest in a CalDAV calendar, e.g. of
+/// Nextcloud, Fastmail or iCloud.
+///
+/// Every event is its own resource, the calendar --export-ics would write
+/// for the contest alone. Their URLs are saved in contest.reminder_ids, so
+/// changed contests replace their event and finished ones delete it.
+pub struct Caldav<'a> {
+    config: &'a CaldavConfig,
+}
+
+impl<'a> Caldav<'a> {
+    pub fn new(config: &'a CaldavConfig) -> Self {
+        Caldav { config }
+    }
+
+    fn authorized(&self, request: RequestBuilder) -> RequestBuilder {
+        request.basic_auth(&self.config.username, Some(&self.config.password))
+    }
+
+    fn put(&self, url: &str, contest: &Contest) -> Result<(), NotifyError> {
+        let request = http_client()
+            .put(url)
+            .header("Content-Type", "text/calendar; charset=utf-8")
+            .body(format_ics_with_alarms(std::slice::from_ref(contest)));
+
+        send(self.authorized(request))?;
+        Ok(())
+    }
+}
+
+/// Name of the resource of a contest, unique across sources.
+fn resource_name(contest: &Contest) -> String {
+    let host: String = contest.source_host()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '.' { c } else { '-' })
+        .collect();
+
+    format!("codeforces-reminder-{}-{}.ics", host, contest.id)
+}
+
+impl Notifier for Caldav<'_> {
+    fn name(&self) -> &'static str {
+        "CalDAV"
+    }
+
+    /// The calendar only holds contests, messages are dropped.
+    fn message(&self, title: &str, text: &str) -> Result<(), NotifyError> {
+        let _ = (title, text);
+        Ok(())
+    }
+
+    fn notify(&self, contest: &mut Contest) -> Result<(), NotifyError> {
+        let url = format!("{}/{}", self.config.calendar_url.trim_end_matches('/'), resource_name(contest));
+        self.put(&url, contest)?;
+
+        contest.reminder_ids = vec![url];
+        Ok(())
+    }
+
+    fn update(&self, contest: &mut Contest) -> Result<(), NotifyError> {
+        for url in &contest.reminder_ids {
+            self.put(url, contest)?;
+        }
+        Ok(())
+    }
+
+    /// Deletes the events, skipping the ones that are already gone.
+    fn withdraw(&self, contest: &Contest) -> Result<(), NotifyError> {
+        for url in &contest.reminder_ids {
+            match send(self.authorized(http_client().delete(url))) {
+                Err(error) if error.reason.as_deref().is_some_and(|reason| reason.contains("404 Not Found")) => {}
+                result => { result?; }
+            }
+        }
+        Ok(())
+    }
+}
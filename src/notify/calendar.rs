@@ -0,0 +1,89 @@
+use crate::contest::Contest;
+use crate::notify::{run_apple_script, Notifier, NotifyError};
+use crate::reminder::{apple_script_date, display_notification, escape, reminder_body, CommandRunner};
+
+/// Notifier creating a Calendar.app event from the start to the end of
+/// every new contest, so it blocks the time and shows as busy. List it
+/// in notifiers next to reminders to get both.
+///
+/// The events have no alarms of their own. Their uids are saved in
+/// contest.reminder_ids, so changed contests update their event and
+/// finished ones delete it.
+pub struct Calendar<'a> {
+    runner: &'a dyn CommandRunner,
+    calendar: &'a str,
+}
+
+impl<'a> Calendar<'a> {
+    pub fn new(runner: &'a dyn CommandRunner, calendar: &'a str) -> Self {
+        Calendar { runner, calendar }
+    }
+
+    /// Runs the commands inside the calendar, creating it if missing.
+    fn run(&self, dates: &str, commands: &str) -> Result<String, NotifyError> {
+        run_apple_script(self.runner, &format!(
+            r#"
+            with timeout of 30 seconds
+                {}
+                tell application "Calendar"
+                    if not (exists calendar "{1}") then make new calendar with properties {{name:"{1}"}}
+                    tell calendar "{1}"
+                        {2}
+                    end tell
+                end tell
+            end timeout"#, dates, escape(self.calendar), commands))
+    }
+}
+
+/// AppleScript setting startDate and endDate to the start and end of the contest.
+fn dates(contest: &Contest) -> Result<String, NotifyError> {
+    let Some(start) = contest.start_time_seconds else {
+        return Err(NotifyError::new("Contest without start time"))
+    };
+    let end = start + contest.duration_seconds.unwrap_or_default();
+
+    Ok(format!("{}\n                {}", apple_script_date("startDate", start), apple_script_date("endDate", end)))
+}
+
+impl Notifier for Calendar<'_> {
+    fn name(&self) -> &'static str {
+        "Calendar"
+    }
+
+    /// Shows the message as Notification Center banner, not as event.
+    fn message(&self, title: &str, text: &str) -> Result<(), NotifyError> {
+        if display_notification(self.runner, title, "", text) { Ok(()) } else { Err(NotifyError::logged()) }
+    }
+
+    fn notify(&self, contest: &mut Contest) -> Result<(), NotifyError> {
+        let uid = self.run(&dates(contest)?, &format!(
+            r#"set newEvent to make new event with properties {{summary:"{}", description:"{}", url:"{}", start date:startDate, end date:endDate}}
+                        return uid of newEvent"#,
+            escape(&contest.name), escape(&reminder_body(contest)), escape(&contest.url())))?;
+
+        contest.reminder_ids = vec![uid];
+        Ok(())
+    }
+
+    fn update(&self, contest: &mut Contest) -> Result<(), NotifyError> {
+        for uid in &contest.reminder_ids {
+            self.run(&dates(contest)?, &format!(
+                r#"repeat with contestEvent in (every event whose uid is "{}")
+                            set summary of contestEvent to "{}"
+                            set description of contestEvent to "{}"
+                            set start date of contestEvent to startDate
+                            set end date of contestEvent to endDate
+                        end repeat"#,
+                escape(uid), escape(&contest.name), escape(&reminder_body(contest))))?;
+        }
+        Ok(())
+    }
+
+    /// Deletes the events, skipping the ones the user already deleted.
+    fn withdraw(&self, contest: &Contest) -> Result<(), NotifyError> {
+        for uid in &contest.reminder_ids {
+            self.run("", &format!(r#"delete (every event whose uid is "{}")"#, escape(uid)))?;
+        }
+        Ok(())
+    }
+}
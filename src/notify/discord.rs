@@ -0,0 +1,82 @@
+use crate::contest::{format_duration, Contest};
+use crate::notify::{http_client, send, Notifier, NotifyError};
+
+use chrono::{TimeZone, Utc};
+use serde_json::{json, Value};
+
+/// Color of the embeds, Codeforces blue.
+const EMBED_COLOR: u32 = 0x1f8acb;
+
+/// Notifier posting an embed to a Discord webhook for every new contest.
+///
+/// The ids of the messages are saved in contest.reminder_ids, so the
+/// message of a changed contest is edited. Messages of finished contests
+/// are kept.
+pub struct Discord<'a> {
+    webhook_url: &'a str,
+}
+
+impl<'a> Discord<'a> {
+    pub fn new(webhook_url: &'a str) -> Self {
+        Discord { webhook_url }
+    }
+}
+
+impl Notifier for Discord<'_> {
+    fn name(&self) -> &'static str {
+        "Discord"
+    }
+
+    fn message(&self, title: &str, text: &str) -> Result<(), NotifyError> {
+        let embed = json!({ "title": title, "description": text, "color": EMBED_COLOR });
+        send(http_client().post(self.webhook_url).json(&json!({ "embeds": [embed] })))?;
+        Ok(())
+    }
+
+    fn notify(&self, contest: &mut Contest) -> Result<(), NotifyError> {
+        // wait=true makes Discord answer with the message, including its id.
+        let url = format!("{}?wait=true", self.webhook_url);
+        let request = http_client().post(url).json(&json!({ "embeds": [embed(contest)] }));
+
+        let response: Value = serde_json::from_str(&send(request)?)
+            .map_err(|e| NotifyError::new(format!("Failed to parse Discord response. {}", e)))?;
+        let Some(message_id) = response["id"].as_str() else {
+            return Err(NotifyError::new(format!("Discord response without message id: {}", response)))
+        };
+
+        contest.reminder_ids = vec![message_id.to_string()];
+        Ok(())
+    }
+
+    fn update(&self, contest: &mut Contest) -> Result<(), NotifyError> {
+        for message_id in &contest.reminder_ids {
+            let url = format!("{}/messages/{}", self.webhook_url, message_id);
+            send(http_client().patch(url).json(&json!({ "embeds": [embed(contest)] })))?;
+        }
+        Ok(())
+    }
+}
+
+/// Embed of a contest with its start as Discord timestamp, which every
+/// reader sees in their own time zone, and its duration.
+fn embed(contest: &Contest) -> Value {
+    let mut fields = Vec::new();
+
+    if let Some(start) = contest.start_time_seconds {
+        fields.push(json!({ "name": "Start", "value": format!("<t:{0}:F> (<t:{0}:R>)", start), "inline": true }));
+    }
+    if let Some(duration) = contest.duration_seconds {
+        fields.push(json!({ "name": "Duration", "value": format_duration(duration), "inline": true }));
+    }
+
+    let mut embed = json!({
+        "title": contest.name,
+        "url": contest.url(),
+        "color": EMBED_COLOR,
+        "fields": fields,
+    });
+    if let Some(start) = contest.start_time_seconds.and_then(|start| Utc.timestamp_opt(start, 0).single()) {
+        embed["timestamp"] = json!(start.to_rfc3339());
+    }
+    embed
+}
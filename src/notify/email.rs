@@ -0,0 +1,80 @@
+use crate::config::EmailConfig;
+use crate::contest::{format_local_time, Contest};
+use crate::notify::{announcement, Notifier, NotifyError};
+
+use lettre::message::header::ContentType;
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{Message, SmtpTransport, Transport};
+
+/// Notifier sending an email per new contest over SMTP.
+pub struct Email<'a> {
+    config: &'a EmailConfig,
+}
+
+impl<'a> Email<'a> {
+    pub fn new(config: &'a EmailConfig) -> Self {
+        Email { config }
+    }
+
+    /// Connection to smtp_host with TLS, STARTTLS if starttls is set,
+    /// logging in if a username is set.
+    fn transport(&self) -> Result<SmtpTransport, NotifyError> {
+        let config = self.config;
+        let relay = if config.starttls {
+            SmtpTransport::starttls_relay(&config.smtp_host)
+        } else {
+            SmtpTransport::relay(&config.smtp_host)
+        };
+        let mut builder = relay.map_err(|e| NotifyError::new(format!("Invalid SMTP host {}. {}", config.smtp_host, e)))?;
+
+        if let Some(port) = config.smtp_port {
+            builder = builder.port(port);
+        }
+        if let Some(username) = &config.username {
+            let password = config.password.clone().unwrap_or_default();
+            builder = builder.credentials(Credentials::new(username.clone(), password));
+        }
+        Ok(builder.build())
+    }
+
+    /// Sends a plain text email from the from address to every address in to.
+    fn mail(&self, subject: &str, body: &str) -> Result<(), NotifyError> {
+        let mut builder = Message::builder().from(mailbox(&self.config.from)?).subject(subject);
+        for to in &self.config.to {
+            builder = builder.to(mailbox(to)?);
+        }
+
+        let message = builder
+            .header(ContentType::TEXT_PLAIN)
+            .body(body.to_string())
+            .map_err(|e| NotifyError::new(format!("Failed to build email. {}", e)))?;
+
+        self.transport()?
+            .send(&message)
+            .map_err(|e| NotifyError::new(format!("Failed to send email via {}. {}", self.config.smtp_host, e)))?;
+        Ok(())
+    }
+}
+
+impl Notifier for Email<'_> {
+    fn name(&self) -> &'static str {
+        "Email"
+    }
+
+    fn message(&self, title: &str, text: &str) -> Result<(), NotifyError> {
+        self.mail(title, text)
+    }
+
+    fn notify(&self, contest: &mut Contest) -> Result<(), NotifyError> {
+        let subject = match contest.start_time_seconds {
+            Some(start) => format!("{} starts {}", contest.name, format_local_time(start)),
+            None => contest.name.clone(),
+        };
+        self.mail(&subject, &announcement(contest))
+    }
+}
+
+fn mailbox(address: &str) -> Result<Mailbox, NotifyError> {
+    address.parse().map_err(|e| NotifyError::new(format!("Invalid email address {}. {}", address, e)))
+}
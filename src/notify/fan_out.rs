@@ -0,0 +1,274 @@
+use crate::config::NotifierSettings;
+use crate::contest::Contest;
+use crate::local::log_error;
+use crate::notify::{backend, delivered, Notifier, NotifyError};
+use crate::reminder::{rule_matches, with_lead_times, CommandRunner};
+
+/// One of several notifiers, with the lead times and filter of its settings.
+struct Dispatched<'a> {
+    notifier: Box<dyn Notifier + 'a>,
+    settings: &'a NotifierSettings,
+    /// The first notifier keeps its ids in contest.reminder_ids, so contests
+    /// saved before there were several notifiers keep their reminders.
+    primary: bool,
+}
+
+impl Dispatched<'_> {
+    fn matches(&self, contest: &Contest) -> bool {
+        rule_matches(contest, self.settings.contest_type.as_deref(), self.settings.pattern.as_deref(), "notifier")
+    }
+
+    /// Runs f with the lead times of the notifier and the ids it delivered
+    /// in contest.reminder_ids, saving them in contest.delivery_ids after.
+    fn run<T>(&self, contest: &mut Contest, f: impl FnOnce(&mut Contest) -> T) -> T {
+        let lead_times = self.settings.lead_times_seconds.as_deref();
+        if self.primary {
+            return with_lead_times(lead_times, || f(contest))
+        }
+
+        let key = &self.settings.backend;
+        let own_ids = contest.delivery_ids.remove(key).unwrap_or_default();
+        let primary_ids = std::mem::replace(&mut contest.reminder_ids, own_ids);
+
+        let result = with_lead_times(lead_times, || f(contest));
+
+        let own_ids = std::mem::replace(&mut contest.reminder_ids, primary_ids);
+        if !own_ids.is_empty() {
+            contest.delivery_ids.insert(key.clone(), own_ids);
+        }
+        result
+    }
+}
+
+/// Notifier delivering through every notifier in notifiers of the config.
+///
+/// Each notifier only gets the contests matching its type and pattern, with
+/// its own lead times. The failures of every notifier are logged. A contest
+/// delivered by some of them keeps the others to retry next run.
+pub struct FanOut<'a> {
+    notifiers: Vec<Dispatched<'a>>,
+}
+
+impl<'a> FanOut<'a> {
+    /// Terminates and logs errors if a backend is unknown or lacks its settings.
+    pub fn new(runner: &'a dyn CommandRunner, settings: &'a [NotifierSettings]) -> Self {
+        let notifiers = settings
+            .iter()
+            .enumerate()
+            .map(|(i, settings)| Dispatched { notifier: backend(&settings.backend, runner), settings, primary: i == 0 })
+            .collect();
+
+        FanOut { notifiers }
+    }
+
+    /// Calls f for every notifier matching the contest whose backend is
+    /// selected and returns the backends that failed.
+    fn each(
+        &self,
+        action: &str,
+        contest: &mut Contest,
+        selected: impl Fn(&str) -> bool,
+        f: impl Fn(&dyn Notifier, &mut Contest) -> Result<(), NotifyError>,
+    ) -> Vec<String> {
+        let mut failed = Vec::new();
+
+        for dispatched in &self.notifiers {
+            let backend = &dispatched.settings.backend;
+            if !dispatched.matches(contest) || !selected(backend) { continue }
+
+            let notifier = dispatched.notifier.as_ref();
+            let result = dispatched.run(contest, |contest| f(notifier, contest));
+            if !delivered(notifier.name(), action, contest, result) {
+                failed.push(backend.clone());
+            }
+        }
+
+        failed
+    }
+}
+
+/// Fails if any backend failed. Their errors are logged already.
+fn all_delivered(failed: &[String]) -> Result<(), NotifyError> {
+    if failed.is_empty() { Ok(()) } else { Err(NotifyError::logged()) }
+}
+
+impl Notifier for FanOut<'_> {
+    fn name(&self) -> &'static str {
+        "notifiers"
+    }
+
+    /// Sends the message through every notifier, regardless of their filters.
+    fn message(&self, title: &str, text: &str) -> Result<(), NotifyError> {
+        let mut failed = false;
+
+        for dispatched in &self.notifiers {
+            let notifier = dispatched.notifier.as_ref();
+            if let Err(error) = notifier.message(title, text) {
+                failed = true;
+                if let Some(reason) = error.reason {
+                    log_error(&format!("{} failed to send message {}. {}", notifier.name(), title, reason));
+                }
+            }
+        }
+
+        if failed { Err(NotifyError::logged()) } else { Ok(()) }
+    }
+
+    /// Fails only if every matching notifier failed, so the contest is
+    /// notified again as new. Otherwise the failed backends are kept in
+    /// contest.failed_deliveries for retry.
+    fn notify(&self, contest: &mut Contest) -> Result<(), NotifyError> {
+        let matching = self.notifiers.iter().filter(|dispatched| dispatched.matches(contest)).count();
+        let failed = self.each("notify", contest, |_| true, |notifier, contest| notifier.notify(contest));
+
+        if !failed.is_empty() && failed.len() == matching {
+            return Err(NotifyError::logged())
+        }
+        contest.failed_deliveries = failed;
+        Ok(())
+    }
+
+    /// Skips the backends that never delivered the contest, retry notifies them.
+    fn update(&self, contest: &mut Contest) -> Result<(), NotifyError> {
+        let pending = contest.failed_deliveries.clone();
+        let failed = self.each("update", contest, |backend| !pending.iter().any(|p| p == backend), |notifier, contest| notifier.update(contest));
+        all_delivered(&failed)
+    }
+
+    fn withdraw(&self, contest: &Contest) -> Result<(), NotifyError> {
+        let delivered_to = |backend: &str| !contest.failed_deliveries.iter().any(|p| p == backend);
+        let failed = self.each("withdraw", &mut contest.clone(), delivered_to, |notifier, contest| notifier.withdraw(contest));
+        all_delivered(&failed)
+    }
+
+    fn retry(&self, contest: &mut Contest) -> Result<(), NotifyError> {
+        let pending = std::mem::take(&mut contest.failed_deliveries);
+        let failed = self.each("notify", contest, |backend| pending.iter().any(|p| p == backend), |notifier, contest| notifier.notify(contest));
+        contest.failed_deliveries = failed;
+        all_delivered(&contest.failed_deliveries)
+    }
+
+    fn remind(&self, contest: &Contest, title: &str) -> Result<(), NotifyError> {
+        let failed = self.each("remind of", &mut contest.clone(), |_| true, |notifier, contest| notifier.remind(contest, title));
+        all_delivered(&failed)
+    }
+
+    fn remind_due(&self, contests: &[Contest], since: i64, now: i64) {
+        for dispatched in &self.notifiers {
+            let matching: Vec<Contest> = contests.iter().filter(|contest| dispatched.matches(contest)).cloned().collect();
+            with_lead_times(dispatched.settings.lead_times_seconds.as_deref(), || {
+                dispatched.notifier.remind_due(&matching, since, now)
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contest::tests::contest;
+
+    /// Delivers a notification with the id "{name}-{n}", seeing the ids it delivered before.
+    struct Numbered(&'static str);
+
+    impl Notifier for Numbered {
+        fn name(&self) -> &'static str { self.0 }
+
+        fn notify(&self, contest: &mut Contest) -> Result<(), NotifyError> {
+            contest.reminder_ids.push(format!("{}-{}", self.0, contest.reminder_ids.len() + 1));
+            Ok(())
+        }
+
+        fn message(&self, _: &str, _: &str) -> Result<(), NotifyError> { Ok(()) }
+    }
+
+    /// Fails to notify while down is set, counting the attempts.
+    struct Flaky { down: std::cell::Cell<bool>, attempts: std::cell::Cell<usize> }
+
+    impl Notifier for &Flaky {
+        fn name(&self) -> &'static str { "ntfy" }
+
+        fn notify(&self, contest: &mut Contest) -> Result<(), NotifyError> {
+            self.attempts.set(self.attempts.get() + 1);
+            if self.down.get() {
+                return Err(NotifyError::new("Connection refused".to_string()))
+            }
+            contest.reminder_ids.push("ntfy-1".to_string());
+            Ok(())
+        }
+
+        fn message(&self, _: &str, _: &str) -> Result<(), NotifyError> { Ok(()) }
+    }
+
+    fn settings(backend: &str, contest_type: Option<&str>) -> NotifierSettings {
+        NotifierSettings { backend: backend.to_string(), lead_times_seconds: None, contest_type: contest_type.map(str::to_string), pattern: None }
+    }
+
+    #[test]
+    fn secondary_notifiers_keep_their_own_ids() {
+        let (primary_settings, secondary_settings) = (settings("reminders", None), settings("ntfy", None));
+        let fan_out = FanOut { notifiers: vec![
+            Dispatched { notifier: Box::new(Numbered("reminders")), settings: &primary_settings, primary: true },
+            Dispatched { notifier: Box::new(Numbered("ntfy")), settings: &secondary_settings, primary: false },
+        ] };
+        let mut contest = contest(2101, "Round 1021", 0);
+
+        fan_out.notify(&mut contest).unwrap();
+        fan_out.notify(&mut contest).unwrap();
+
+        assert_eq!(contest.reminder_ids, ["reminders-1", "reminders-2"]);
+        assert_eq!(contest.delivery_ids["ntfy"], ["ntfy-1", "ntfy-2"]);
+    }
+
+    #[test]
+    fn notifiers_only_get_matching_contests() {
+        let (all, icpc) = (settings("reminders", None), settings("ntfy", Some("ICPC")));
+        let fan_out = FanOut { notifiers: vec![
+            Dispatched { notifier: Box::new(Numbered("reminders")), settings: &all, primary: true },
+            Dispatched { notifier: Box::new(Numbered("ntfy")), settings: &icpc, primary: false },
+        ] };
+        let mut round = contest(2101, "Round 1021", 0);
+        let mut training = Contest { contest_type: Some("ICPC".to_string()), ..contest(2102, "Training", 0) };
+
+        fan_out.notify(&mut round).unwrap();
+        fan_out.notify(&mut training).unwrap();
+
+        assert!(!round.delivery_ids.contains_key("ntfy"));
+        assert_eq!(training.delivery_ids["ntfy"], ["ntfy-1"]);
+    }
+
+    #[test]
+    fn only_failed_notifiers_are_retried() {
+        let (primary_settings, secondary_settings) = (settings("reminders", None), settings("ntfy", None));
+        let flaky = Flaky { down: std::cell::Cell::new(true), attempts: std::cell::Cell::new(0) };
+        let fan_out = FanOut { notifiers: vec![
+            Dispatched { notifier: Box::new(Numbered("reminders")), settings: &primary_settings, primary: true },
+            Dispatched { notifier: Box::new(&flaky), settings: &secondary_settings, primary: false },
+        ] };
+        let mut contest = contest(2101, "Round 1021", 0);
+
+        fan_out.notify(&mut contest).unwrap();
+        assert_eq!(contest.reminder_ids, ["reminders-1"]);
+        assert_eq!(contest.failed_deliveries, ["ntfy"]);
+
+        assert!(fan_out.retry(&mut contest).is_err());
+        flaky.down.set(false);
+        fan_out.retry(&mut contest).unwrap();
+
+        assert_eq!(flaky.attempts.get(), 3);
+        assert_eq!(contest.reminder_ids, ["reminders-1"]);
+        assert_eq!(contest.delivery_ids["ntfy"], ["ntfy-1"]);
+        assert!(contest.failed_deliveries.is_empty());
+    }
+
+    #[test]
+    fn contest_fails_if_every_notifier_failed() {
+        let ntfy_settings = settings("ntfy", None);
+        let flaky = Flaky { down: std::cell::Cell::new(true), attempts: std::cell::Cell::new(0) };
+        let fan_out = FanOut { notifiers: vec![
+            Dispatched { notifier: Box::new(&flaky), settings: &ntfy_settings, primary: true },
+        ] };
+
+        assert!(fan_out.notify(&mut contest(2101, "Round 1021", 0)).is_err());
+    }
+}
@@ -0,0 +1,216 @@
+use crate::config::GoogleCalendarConfig;
+use crate::contest::Contest;
+use crate::notify::oauth::{load_token, random_string, request_token, save_token, Pkce, Token, TokenResponse};
+use crate::notify::{http_client, send, Notifier, NotifyError};
+use crate::paths::google_token_path;
+use crate::reminder::{contest_lead_times, due_time, reminder_body, reminder_name};
+
+use chrono::{TimeZone, Utc};
+use reqwest::blocking::RequestBuilder;
+use reqwest::Url;
+use serde_json::{json, Value};
+use std::cell::RefCell;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+
+const AUTH_URL: &str = "https://accounts.google.com/o/oauth2/v2/auth";
+const TOKEN_URL: &str = "https://oauth2.googleapis.com/token";
+const CALENDARS_URL: &str = "https://www.googleapis.com/calendar/v3/calendars";
+
+/// Access to the events of the calendars, nothing else.
+const SCOPE: &str = "https://www.googleapis.com/auth/calendar.events";
+
+/// Notifier inserting an event spanning every new contest into a Google
+/// Calendar, with a popup reminder per lead time.
+///
+/// Authorize it once with --google-auth, which saves the OAuth tokens in
+/// the data directory. The ids of the events are saved in
+/// contest.reminder_ids, so changed contests update their event and
+/// finished ones delete it.
+pub struct GoogleCalendar<'a> {
+    config: &'a GoogleCalendarConfig,
+    token: RefCell<Option<Token>>,
+}
+
+impl<'a> GoogleCalendar<'a> {
+    pub fn new(config: &'a GoogleCalendarConfig) -> Self {
+        GoogleCalendar { config, token: RefCell::new(None) }
+    }
+
+    /// URL of the events of the calendar, or of the event with this id.
+    fn events_url(&self, event_id: Option<&str>) -> Url {
+        // Calendar ids are email addresses, so segments have to be percent-encoded.
+        let mut url = Url::parse(CALENDARS_URL).expect("CALENDARS_URL is valid");
+        if let Ok(mut segments) = url.path_segments_mut() {
+            segments.extend([self.config.calendar_id.as_str(), "events"]).extend(event_id);
+        }
+        url
+    }
+
+    /// Returns a valid access token, refreshing and saving it if it expired.
+    fn access_token(&self) -> Result<String, NotifyError> {
+        let mut cached = self.token.borrow_mut();
+
+        let mut token = match cached.take() {
+            Some(token) => token,
+            None => load_token(&google_token_path(), "--google-auth")?,
+        };
+        if token.is_expired() {
+            let response = request_token(TOKEN_URL, &[
+                ("client_id", self.config.client_id.as_str()),
+                ("client_secret", self.config.client_secret.as_str()),
+                ("refresh_token", token.refresh_token.as_str()),
+                ("grant_type", "refresh_token"),
+            ])?;
+            token.refreshed(response);
+            save_token(&google_token_path(), &token)?;
+        }
+
+        let access_token = token.access_token.clone();
+        *cached = Some(token);
+        Ok(access_token)
+    }
+
+    fn send(&self, request: RequestBuilder) -> Result<String, NotifyError> {
+        send(request.bearer_auth(self.access_token()?))
+    }
+}
+
+impl Notifier for GoogleCalendar<'_> {
+    fn name(&self) -> &'static str {
+        "Google Calendar"
+    }
+
+    /// The calendar only holds contests, messages are dropped.
+    fn message(&self, title: &str, text: &str) -> Result<(), NotifyError> {
+        let _ = (title, text);
+        Ok(())
+    }
+
+    fn notify(&self, contest: &mut Contest) -> Result<(), NotifyError> {
+        let response: Value = serde_json::from_str(&self.send(http_client().post(self.events_url(None)).json(&event(contest)?))?)
+            .map_err(|e| NotifyError::new(format!("Failed to parse Google Calendar response. {}", e)))?;
+        let Some(event_id) = response["id"].as_str() else {
+            return Err(NotifyError::new(format!("Google Calendar response without event id: {}", response)))
+        };
+
+        contest.reminder_ids = vec![event_id.to_string()];
+        Ok(())
+    }
+
+    fn update(&self, contest: &mut Contest) -> Result<(), NotifyError> {
+        let event = event(contest)?;
+        for event_id in &contest.reminder_ids {
+            self.send(http_client().put(self.events_url(Some(event_id))).json(&event))?;
+        }
+        Ok(())
+    }
+
+    /// Deletes the events, skipping the ones the user already deleted.
+    fn withdraw(&self, contest: &Contest) -> Result<(), NotifyError> {
+        for event_id in &contest.reminder_ids {
+            match self.send(http_client().delete(self.events_url(Some(event_id)))) {
+                Err(error) if error.reason.as_deref().is_some_and(|reason| reason.contains("410 Gone")) => {}
+                result => { result?; }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Event from the start to the end of the contest, reminding with a popup
+/// when each of its reminders would be due.
+fn event(contest: &Contest) -> Result<Value, NotifyError> {
+    let Some(start) = contest.start_time_seconds else {
+        return Err(NotifyError::new("Contest without start time"))
+    };
+    let end = start + contest.duration_seconds.unwrap_or_default();
+    let date_time = |seconds: i64| Utc.timestamp_opt(seconds, 0).single().map(|time| time.to_rfc3339());
+
+    let overrides: Vec<Value> = contest_lead_times(contest)
+        .into_iter()
+        .map(|lead_time| json!({ "method": "popup", "minutes": (start - due_time(contest, start, lead_time)) / 60 }))
+        .collect();
+
+    Ok(json!({
+        "summary": reminder_name(contest),
+        "description": reminder_body(contest),
+        "start": { "dateTime": date_time(start) },
+        "end": { "dateTime": date_time(end) },
+        "source": { "title": contest.name, "url": contest.url() },
+        "reminders": { "useDefault": false, "overrides": overrides },
+    }))
+}
+
+/// Lets the user grant access to their calendar in the browser and saves
+/// the OAuth tokens in google_token.json.
+///
+/// Google redirects the browser to a server on a free local port, which
+/// receives the authorization code. Redirects without the random state of
+/// this flow are rejected, and the code is only exchanged together with
+/// the PKCE verifier. Fails with the reason if the flow does not complete.
+pub fn authorize(config: &GoogleCalendarConfig) -> Result<(), String> {
+    let listener = TcpListener::bind("127.0.0.1:0").map_err(|e| format!("Failed to listen for the redirect. {}", e))?;
+    let port = listener.local_addr().map_err(|e| e.to_string())?.port();
+    let redirect_uri = format!("http://127.0.0.1:{}", port);
+    let state = random_string()?;
+    let pkce = Pkce::new()?;
+
+    let auth_url = Url::parse_with_params(AUTH_URL, &[
+        ("client_id", config.client_id.as_str()),
+        ("redirect_uri", redirect_uri.as_str()),
+        ("response_type", "code"),
+        ("scope", SCOPE),
+        // Offline access with consent makes Google always return a refresh token.
+        ("access_type", "offline"),
+        ("prompt", "consent"),
+        ("state", state.as_str()),
+        ("code_challenge", pkce.challenge.as_str()),
+        ("code_challenge_method", "S256"),
+    ]).map_err(|e| e.to_string())?;
+    println!("Open this URL to allow access to your calendar:\n{}", auth_url);
+
+    let code = loop {
+        let (mut stream, _) = listener.accept().map_err(|e| format!("Failed to accept the redirect. {}", e))?;
+        let mut request_line = String::new();
+        BufReader::new(&stream).read_line(&mut request_line).map_err(|e| format!("Failed to read the redirect. {}", e))?;
+
+        let target = request_line.split_whitespace().nth(1).unwrap_or("/");
+        let redirect = Url::parse(&format!("{}{}", redirect_uri, target)).map_err(|e| e.to_string())?;
+        let param = |name: &str| redirect.query_pairs().find(|(key, _)| key == name).map(|(_, value)| value.into_owned());
+
+        // Other requests, e.g. for the favicon or a forged redirect, are
+        // answered and the flow keeps waiting for the real one.
+        if param("state").as_deref() != Some(state.as_str()) {
+            respond(&mut stream, "400 Bad Request", "Unexpected request, this is not the redirect of the authorization.");
+            continue;
+        }
+
+        let Some(code) = param("code") else {
+            respond(&mut stream, "200 OK", "Access was not granted.");
+            return Err(format!("Google did not grant access: {}", param("error").unwrap_or_else(|| "no code".to_string())))
+        };
+        respond(&mut stream, "200 OK", "Access granted, you can close this tab.");
+        break code;
+    };
+
+    request_token(TOKEN_URL, &[
+        ("code", code.as_str()),
+        ("code_verifier", pkce.verifier.as_str()),
+        ("client_id", config.client_id.as_str()),
+        ("client_secret", config.client_secret.as_str()),
+        ("redirect_uri", redirect_uri.as_str()),
+        ("grant_type", "authorization_code"),
+    ])
+        .and_then(TokenResponse::into_token)
+        .and_then(|token| save_token(&google_token_path(), &token))
+        .map_err(|e| e.reason.unwrap_or_default())?;
+    println!("Saved the tokens in {}", google_token_path().display());
+    Ok(())
+}
+
+/// Answers a request to the redirect server with a plain text page.
+fn respond(stream: &mut TcpStream, status: &str, page: &str) {
+    let _ = write!(stream, "HTTP/1.1 {}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status, page.len(), page);
+}
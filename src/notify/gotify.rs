@@ -0,0 +1,52 @@
+use crate::config::GotifyConfig;
+use crate::contest::Contest;
+use crate::notify::{announcement, http_client, send, Notifier, NotifyError};
+use crate::reminder::rule_matches;
+
+use serde_json::{json, Value};
+
+/// Notifier pushing a message to a Gotify server for every new contest.
+///
+/// The priority comes from the first rule in priorities matching the
+/// contest, default_priority if none matches.
+pub struct Gotify<'a> {
+    config: &'a GotifyConfig,
+}
+
+impl<'a> Gotify<'a> {
+    pub fn new(config: &'a GotifyConfig) -> Self {
+        Gotify { config }
+    }
+
+    fn priority(&self, contest: &Contest) -> u8 {
+        self.config.priorities
+            .iter()
+            .find(|rule| rule_matches(contest, rule.contest_type.as_deref(), rule.pattern.as_deref(), "Gotify priority"))
+            .map_or(self.config.default_priority, |rule| rule.priority)
+    }
+
+    fn push(&self, message: Value) -> Result<(), NotifyError> {
+        let url = format!("{}/message", self.config.url.trim_end_matches('/'));
+        send(http_client().post(url).header("X-Gotify-Key", &self.config.app_token).json(&message))?;
+        Ok(())
+    }
+}
+
+impl Notifier for Gotify<'_> {
+    fn name(&self) -> &'static str {
+        "Gotify"
+    }
+
+    fn message(&self, title: &str, text: &str) -> Result<(), NotifyError> {
+        self.push(json!({ "title": title, "message": text, "priority": self.config.default_priority }))
+    }
+
+    fn notify(&self, contest: &mut Contest) -> Result<(), NotifyError> {
+        self.push(json!({
+            "title": contest.name,
+            "message": announcement(contest),
+            "priority": self.priority(contest),
+            "extras": { "client::notification": { "click": { "url": contest.url() } } },
+        }))
+    }
+}
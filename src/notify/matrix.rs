@@ -0,0 +1,97 @@
+use crate::config::MatrixConfig;
+use crate::contest::{format_duration, format_local_time, Contest};
+use crate::notify::{announcement, http_client, send, Notifier, NotifyError};
+
+use reqwest::Url;
+use serde_json::{json, Value};
+
+/// Notifier posting a notice to a Matrix room for every new contest.
+///
+/// The ids of the events are saved in contest.reminder_ids, so the notice
+/// of a changed contest is edited. Notices of finished contests are kept.
+pub struct Matrix<'a> {
+    config: &'a MatrixConfig,
+}
+
+impl<'a> Matrix<'a> {
+    pub fn new(config: &'a MatrixConfig) -> Self {
+        Matrix { config }
+    }
+
+    /// Sends a message event to the room and returns its id.
+    /// The transaction id starts with prefix, e.g. the contest id.
+    fn send_event(&self, prefix: &str, content: &Value) -> Result<String, NotifyError> {
+        // Room ids like "!abc:example.org" have to be percent-encoded.
+        let mut url = Url::parse(&self.config.homeserver)
+            .map_err(|e| NotifyError::new(format!("Invalid homeserver {}. {}", self.config.homeserver, e)))?;
+        let transaction = format!("cfr-{}-{}", prefix, chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default());
+        url.path_segments_mut()
+            .map_err(|_| NotifyError::new(format!("Invalid homeserver {}", self.config.homeserver)))?
+            .pop_if_empty()
+            .extend(["_matrix", "client", "v3", "rooms", &self.config.room_id, "send", "m.room.message", &transaction]);
+
+        let request = http_client().put(url).bearer_auth(&self.config.access_token).json(content);
+        let response: Value = serde_json::from_str(&send(request)?)
+            .map_err(|e| NotifyError::new(format!("Failed to parse Matrix response. {}", e)))?;
+
+        match response["event_id"].as_str() {
+            Some(event_id) => Ok(event_id.to_string()),
+            None => Err(NotifyError::new(format!("Matrix response without event id: {}", response))),
+        }
+    }
+}
+
+impl Notifier for Matrix<'_> {
+    fn name(&self) -> &'static str {
+        "Matrix"
+    }
+
+    fn message(&self, title: &str, text: &str) -> Result<(), NotifyError> {
+        let content = json!({ "msgtype": "m.notice", "body": format!("{}\n{}", title, text) });
+        self.send_event("message", &content)?;
+        Ok(())
+    }
+
+    fn notify(&self, contest: &mut Contest) -> Result<(), NotifyError> {
+        let event_id = self.send_event(&contest.id.to_string(), &notice(contest))?;
+        contest.reminder_ids = vec![event_id];
+        Ok(())
+    }
+
+    fn update(&self, contest: &mut Contest) -> Result<(), NotifyError> {
+        for event_id in &contest.reminder_ids {
+            // Clients without edit support show the fallback body with a "* " prefix.
+            let mut content = notice(contest);
+            content["body"] = json!(format!("* {}", content["body"].as_str().unwrap_or_default()));
+            content["m.new_content"] = notice(contest);
+            content["m.relates_to"] = json!({ "rel_type": "m.replace", "event_id": event_id });
+
+            self.send_event(&contest.id.to_string(), &content)?;
+        }
+        Ok(())
+    }
+}
+
+/// Notice with a plain text body and the name linked to the contest page
+/// in the formatted body.
+fn notice(contest: &Contest) -> Value {
+    let mut html = vec![format!("<b><a href=\"{}\">{}</a></b>", contest.url(), escape_html(&contest.name))];
+
+    if let Some(start) = contest.start_time_seconds {
+        html.push(format!("Starts {}", format_local_time(start)));
+    }
+    if let Some(duration) = contest.duration_seconds {
+        html.push(format!("Lasts {}", format_duration(duration)));
+    }
+
+    json!({
+        "msgtype": "m.notice",
+        "body": announcement(contest),
+        "format": "org.matrix.custom.html",
+        "formatted_body": html.join("<br>"),
+    })
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
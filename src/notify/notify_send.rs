@@ -0,0 +1,100 @@
+use crate::contest::Contest;
+use crate::notify::{run_command, scheduled, Notifier, NotifyError};
+use crate::reminder::reminder_body;
+
+use std::process::Command;
+
+/// Exit code of systemctl for units that are not loaded,
+/// e.g. timers that already elapsed.
+const UNIT_NOT_LOADED: i32 = 5;
+
+/// Notifier showing desktop notifications with notify-send, the default
+/// backend on Linux.
+///
+/// Every notification is scheduled as a transient systemd user timer
+/// running notify-send when it is due. The timer units are saved in
+/// contest.reminder_ids, so they can be stopped again.
+pub struct NotifySend;
+
+impl Notifier for NotifySend {
+    fn name(&self) -> &'static str {
+        "notify-send"
+    }
+
+    fn message(&self, title: &str, text: &str) -> Result<(), NotifyError> {
+        run_command(Command::new("notify-send").args(notify_send_args(title, text)))
+    }
+
+    fn notify(&self, contest: &mut Contest) -> Result<(), NotifyError> {
+        let notifications = scheduled(contest)?;
+
+        let now = chrono::Utc::now().timestamp();
+        let body = reminder_body(contest);
+        let mut units = Vec::new();
+
+        for (due, title) in notifications {
+            let unit = unit_name(contest, due);
+            let delay = due - now;
+
+            // Notifications already due are shown right away.
+            if delay <= 0 {
+                run_command(Command::new("notify-send").args(notify_send_args(&title, &body)))?;
+                continue;
+            }
+
+            let mut command = Command::new("systemd-run");
+            command
+                .arg("--user")
+                .arg(format!("--unit={}", unit))
+                .arg(format!("--on-active={}s", delay))
+                .arg("--timer-property=AccuracySec=1s");
+            for variable in ["DISPLAY", "WAYLAND_DISPLAY", "DBUS_SESSION_BUS_ADDRESS"] {
+                if let Ok(value) = std::env::var(variable) {
+                    command.arg(format!("--setenv={}={}", variable, value));
+                }
+            }
+            command.arg("notify-send").args(notify_send_args(&title, &body));
+
+            run_command(&mut command)?;
+            units.push(format!("{}.timer", unit));
+        }
+
+        contest.reminder_ids = units;
+        Ok(())
+    }
+
+    fn update(&self, contest: &mut Contest) -> Result<(), NotifyError> {
+        self.withdraw(contest)?;
+        self.notify(contest)
+    }
+
+    fn withdraw(&self, contest: &Contest) -> Result<(), NotifyError> {
+        for unit in &contest.reminder_ids {
+            let output = Command::new("systemctl")
+                .args(["--user", "stop", unit])
+                .output()
+                .map_err(|e| NotifyError::new(format!("Failed to run systemctl. {}", e)))?;
+
+            if !output.status.success() && output.status.code() != Some(UNIT_NOT_LOADED) {
+                return Err(NotifyError::new(format!("systemctl stop {} failed with {}. Error: {}",
+                    unit, output.status, String::from_utf8_lossy(&output.stderr).trim())))
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Name of the timer unit of the notification of a contest due at due,
+/// unique across sources and lead times.
+fn unit_name(contest: &Contest, due: i64) -> String {
+    let host: String = contest.source_host()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+
+    format!("codeforces-reminder-{}-{}-{}", host, contest.id, due)
+}
+
+fn notify_send_args(title: &str, body: &str) -> [String; 3] {
+    ["--app-name=codeforces-reminder".to_string(), title.to_string(), body.to_string()]
+}
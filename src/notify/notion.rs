@@ -0,0 +1,112 @@
+use crate::config::NotionConfig;
+use crate::contest::{Contest, Phase};
+use crate::notify::{http_client, send, Notifier, NotifyError};
+
+use chrono::{TimeZone, Utc};
+use reqwest::blocking::RequestBuilder;
+use serde_json::{json, Value};
+
+const PAGES_URL: &str = "https://api.notion.com/v1/pages";
+
+/// Version of the Notion API the requests are written for.
+const NOTION_VERSION: &str = "2022-06-28";
+
+/// Notifier adding a row to a Notion database for every new contest.
+///
+/// The database needs the properties Name (title), Start (date), Duration
+/// (number, in minutes), URL (url) and Status (select). The ids of the pages
+/// are saved in contest.reminder_ids, so changed contests update their row
+/// and finished ones get the status Finished.
+pub struct Notion<'a> {
+    config: &'a NotionConfig,
+}
+
+impl<'a> Notion<'a> {
+    pub fn new(config: &'a NotionConfig) -> Self {
+        Notion { config }
+    }
+
+    fn send(&self, request: RequestBuilder, page: &Value) -> Result<String, NotifyError> {
+        send(request.bearer_auth(&self.config.token).header("Notion-Version", NOTION_VERSION).json(page))
+    }
+
+    /// Sets the properties of the pages of the contest.
+    fn update_pages(&self, contest: &Contest, status: &str) -> Result<(), NotifyError> {
+        for page_id in &contest.reminder_ids {
+            let url = format!("{}/{}", PAGES_URL, page_id);
+            self.send(http_client().patch(url), &json!({ "properties": properties(contest, status) }))?;
+        }
+        Ok(())
+    }
+}
+
+/// Status of the row of a contest, Running once it started.
+fn status(contest: &Contest) -> &'static str {
+    match contest.phase {
+        Phase::Before => "Upcoming",
+        _ => "Running",
+    }
+}
+
+fn properties(contest: &Contest, status: &str) -> Value {
+    let start = contest.start_time_seconds.and_then(|start| Utc.timestamp_opt(start, 0).single());
+    let end = contest.start_time_seconds
+        .zip(contest.duration_seconds)
+        .and_then(|(start, duration)| Utc.timestamp_opt(start + duration, 0).single());
+
+    json!({
+        "Name": { "title": [{ "text": { "content": contest.name } }] },
+        "Start": {
+            "date": start.map(|start| json!({ "start": start.to_rfc3339(), "end": end.map(|end| end.to_rfc3339()) }))
+        },
+        "Duration": { "number": contest.duration_seconds.map(|duration| duration / 60) },
+        "URL": { "url": contest.url() },
+        "Status": { "select": { "name": status } },
+    })
+}
+
+impl Notifier for Notion<'_> {
+    fn name(&self) -> &'static str {
+        "Notion"
+    }
+
+    /// The database only holds contests, messages are dropped.
+    fn message(&self, title: &str, text: &str) -> Result<(), NotifyError> {
+        let _ = (title, text);
+        Ok(())
+    }
+
+    fn notify(&self, contest: &mut Contest) -> Result<(), NotifyError> {
+        let page = json!({
+            "parent": { "database_id": self.config.database_id },
+            "properties": properties(contest, status(contest)),
+        });
+
+        let response: Value = serde_json::from_str(&self.send(http_client().post(PAGES_URL), &page)?)
+            .map_err(|e| NotifyError::new(format!("Failed to parse Notion response. {}", e)))?;
+        let Some(page_id) = response["id"].as_str() else {
+            return Err(NotifyError::new(format!("Notion response without page id: {}", response)))
+        };
+
+        contest.reminder_ids = vec![page_id.to_string()];
+        Ok(())
+    }
+
+    fn update(&self, contest: &mut Contest) -> Result<(), NotifyError> {
+        self.update_pages(contest, status(contest))
+    }
+
+    /// Marks the rows as Finished, or archives them if archive_finished
+    /// is set in the notion config.
+    fn withdraw(&self, contest: &Contest) -> Result<(), NotifyError> {
+        if !self.config.archive_finished {
+            return self.update_pages(contest, "Finished")
+        }
+
+        for page_id in &contest.reminder_ids {
+            let url = format!("{}/{}", PAGES_URL, page_id);
+            self.send(http_client().patch(url), &json!({ "archived": true }))?;
+        }
+        Ok(())
+    }
+}
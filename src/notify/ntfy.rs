@@ -0,0 +1,54 @@
+use crate::config::NtfyConfig;
+use crate::contest::Contest;
+use crate::notify::{announcement, http_client, send, Notifier, NotifyError};
+
+use serde_json::{json, Value};
+
+/// Notifier publishing a message to an ntfy topic for every new contest,
+/// on ntfy.sh or a self-hosted server.
+///
+/// Tapping the notification opens the contest page.
+pub struct Ntfy<'a> {
+    config: &'a NtfyConfig,
+}
+
+impl<'a> Ntfy<'a> {
+    pub fn new(config: &'a NtfyConfig) -> Self {
+        Ntfy { config }
+    }
+
+    fn publish(&self, message: Value) -> Result<(), NotifyError> {
+        // Publishing JSON goes to the root of the server, not the topic.
+        let mut request = http_client().post(self.config.server.trim_end_matches('/')).json(&message);
+        if let Some(token) = &self.config.token {
+            request = request.bearer_auth(token);
+        }
+
+        send(request)?;
+        Ok(())
+    }
+}
+
+impl Notifier for Ntfy<'_> {
+    fn name(&self) -> &'static str {
+        "ntfy"
+    }
+
+    fn message(&self, title: &str, text: &str) -> Result<(), NotifyError> {
+        self.publish(json!({ "topic": self.config.topic, "title": title, "message": text }))
+    }
+
+    fn notify(&self, contest: &mut Contest) -> Result<(), NotifyError> {
+        let mut message = json!({
+            "topic": self.config.topic,
+            "title": contest.name,
+            "message": announcement(contest),
+            "click": contest.url(),
+            "tags": ["calendar"],
+        });
+        if let Some(priority) = self.config.priority {
+            message["priority"] = json!(priority);
+        }
+        self.publish(message)
+    }
+}
@@ -0,0 +1,160 @@
+use crate::local::write_atomically;
+use crate::notify::{http_client, send, NotifyError};
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::Utc;
+use ring::digest::{digest, SHA256};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// Access tokens are refreshed this long before they expire.
+const EXPIRY_MARGIN_SECONDS: i64 = 60;
+
+/// OAuth tokens of a backend saved in the data directory.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Token {
+    pub access_token: String,
+    pub refresh_token: String,
+    /// Seconds since Unix epoch the access token expires at.
+    pub expires_at: i64,
+}
+
+impl Token {
+    pub fn is_expired(&self) -> bool {
+        self.expires_at - EXPIRY_MARGIN_SECONDS <= Utc::now().timestamp()
+    }
+
+    /// Takes the access token of a refresh, and the refresh token
+    /// if the server rotated it.
+    pub fn refreshed(&mut self, response: TokenResponse) {
+        self.access_token = response.access_token;
+        self.expires_at = Utc::now().timestamp() + response.expires_in;
+        if let Some(refresh_token) = response.refresh_token {
+            self.refresh_token = refresh_token;
+        }
+    }
+}
+
+/// Answer of a token endpoint.
+#[derive(Deserialize)]
+pub struct TokenResponse {
+    pub access_token: String,
+    pub expires_in: i64,
+    pub refresh_token: Option<String>,
+}
+
+impl TokenResponse {
+    /// Token of a first authorization, which has to include a refresh token.
+    pub fn into_token(self) -> Result<Token, NotifyError> {
+        let Some(refresh_token) = self.refresh_token else {
+            return Err(NotifyError::new("The token response has no refresh token."))
+        };
+        Ok(Token { access_token: self.access_token, refresh_token, expires_at: Utc::now().timestamp() + self.expires_in })
+    }
+}
+
+/// Proof key of an authorization code flow (RFC 7636). The challenge is
+/// sent with the authorization request, the verifier with the token request,
+/// so a code injected into the redirect is useless without the verifier.
+pub struct Pkce {
+    pub verifier: String,
+    /// Base64url SHA-256 of the verifier, for code_challenge_method S256.
+    pub challenge: String,
+}
+
+impl Pkce {
+    pub fn new() -> Result<Self, String> {
+        Ok(Pkce::from_verifier(random_string()?))
+    }
+
+    fn from_verifier(verifier: String) -> Self {
+        let challenge = URL_SAFE_NO_PAD.encode(digest(&SHA256, verifier.as_bytes()));
+        Pkce { verifier, challenge }
+    }
+}
+
+/// 32 random bytes as base64url, for the state and the PKCE verifier.
+pub fn random_string() -> Result<String, String> {
+    let mut bytes = [0u8; 32];
+    SystemRandom::new().fill(&mut bytes).map_err(|_| "Failed to generate random bytes.".to_string())?;
+    Ok(URL_SAFE_NO_PAD.encode(bytes))
+}
+
+/// Loads the tokens saved by the authorization option named auth_option.
+pub fn load_token(path: &Path, auth_option: &str) -> Result<Token, NotifyError> {
+    let contents = fs::read_to_string(path).map_err(|e| {
+        NotifyError::new(format!("Failed to read {}, authorize with {} first. {}", path.display(), auth_option, e))
+    })?;
+    serde_json::from_str(&contents).map_err(|e| NotifyError::new(format!("Failed to parse {}. {}", path.display(), e)))
+}
+
+pub fn save_token(path: &Path, token: &Token) -> Result<(), NotifyError> {
+    serde_json::to_string_pretty(token)
+        .map_err(std::io::Error::from)
+        .and_then(|json| write_atomically(path, &json))
+        .map_err(|e| NotifyError::new(format!("Failed to save {}. {}", path.display(), e)))
+}
+
+/// Posts the form to the token endpoint at url.
+pub fn request_token(url: &str, form: &[(&str, &str)]) -> Result<TokenResponse, NotifyError> {
+    serde_json::from_str(&send(http_client().post(url).form(form))?)
+        .map_err(|e| NotifyError::new(format!("Failed to parse token response. {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn challenge_is_s256_of_verifier() {
+        // Example of RFC 7636, appendix B.
+        let pkce = Pkce::from_verifier("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk".to_string());
+
+        assert_eq!(pkce.challenge, "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM");
+    }
+
+    #[test]
+    fn verifiers_are_random_and_url_safe() {
+        let (first, second) = (Pkce::new().unwrap(), Pkce::new().unwrap());
+
+        assert_ne!(first.verifier, second.verifier);
+        // RFC 7636 requires 43 to 128 unreserved characters.
+        assert_eq!(first.verifier.len(), 43);
+        assert!(first.verifier.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+    }
+
+    #[test]
+    fn tokens_expire_with_margin() {
+        let token = |expires_in| Token {
+            access_token: "access".to_string(),
+            refresh_token: "refresh".to_string(),
+            expires_at: Utc::now().timestamp() + expires_in,
+        };
+
+        assert!(!token(3600).is_expired());
+        assert!(token(EXPIRY_MARGIN_SECONDS - 1).is_expired());
+        assert!(token(-1).is_expired());
+    }
+
+    #[test]
+    fn refresh_keeps_refresh_token_unless_rotated() {
+        let mut token = Token { access_token: "old".to_string(), refresh_token: "refresh".to_string(), expires_at: 0 };
+
+        token.refreshed(TokenResponse { access_token: "new".to_string(), expires_in: 3600, refresh_token: None });
+        assert_eq!((token.access_token.as_str(), token.refresh_token.as_str()), ("new", "refresh"));
+        assert!(!token.is_expired());
+
+        token.refreshed(TokenResponse { access_token: "newer".to_string(), expires_in: 3600, refresh_token: Some("rotated".to_string()) });
+        assert_eq!(token.refresh_token, "rotated");
+    }
+
+    #[test]
+    fn first_token_needs_refresh_token() {
+        let response = TokenResponse { access_token: "access".to_string(), expires_in: 3600, refresh_token: None };
+
+        assert!(response.into_token().is_err());
+    }
+}
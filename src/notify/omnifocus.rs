@@ -0,0 +1,104 @@
+use crate::contest::Contest;
+use crate::notify::{run_apple_script, Notifier, NotifyError};
+use crate::reminder::{apple_script_date, contest_lead_times, display_notification, due_time, escape};
+use crate::reminder::{reminder_body, reminder_name, CommandRunner};
+
+/// Notifier creating an OmniFocus task for every new contest, scripted
+/// through AppleScript like the Reminders backend.
+///
+/// The task is due at the start of the contest and deferred until its
+/// earliest reminder would be due. Its id is saved in contest.reminder_ids,
+/// so changed contests update it and finished ones delete it.
+pub struct OmniFocus<'a> {
+    runner: &'a dyn CommandRunner,
+    project: Option<&'a str>,
+}
+
+impl<'a> OmniFocus<'a> {
+    pub fn new(runner: &'a dyn CommandRunner, project: Option<&'a str>) -> Self {
+        OmniFocus { runner, project }
+    }
+
+    fn run(&self, commands: &str) -> Result<String, NotifyError> {
+        run_apple_script(self.runner, &format!(
+            r#"
+            with timeout of 30 seconds
+                tell application "OmniFocus"
+                    tell default document
+                        {}
+                    end tell
+                end tell
+            end timeout"#, commands))
+    }
+}
+
+/// AppleScript setting deferDate and dueDate of the task of the contest.
+fn dates(contest: &Contest) -> Result<String, NotifyError> {
+    let Some(start) = contest.start_time_seconds else {
+        return Err(NotifyError::new("Contest without start time"))
+    };
+
+    let defer = contest_lead_times(contest)
+        .into_iter()
+        .map(|lead_time| due_time(contest, start, lead_time))
+        .min()
+        .unwrap_or(start);
+
+    Ok(format!("{}\n                        {}", apple_script_date("deferDate", defer), apple_script_date("dueDate", start)))
+}
+
+impl Notifier for OmniFocus<'_> {
+    fn name(&self) -> &'static str {
+        "OmniFocus"
+    }
+
+    /// Shows the message as Notification Center banner, not as task.
+    fn message(&self, title: &str, text: &str) -> Result<(), NotifyError> {
+        if display_notification(self.runner, title, "", text) { Ok(()) } else { Err(NotifyError::logged()) }
+    }
+
+    fn notify(&self, contest: &mut Contest) -> Result<(), NotifyError> {
+        let properties = format!(r#"{{name:"{}", note:"{}", defer date:deferDate, due date:dueDate}}"#,
+            escape(&reminder_name(contest)), escape(&reminder_body(contest)));
+
+        let create = match self.project {
+            Some(project) => format!(
+                r#"if not (exists flattened project "{0}") then make new project with properties {{name:"{0}"}}
+                        set newTask to make new task with properties {1} at end of tasks of flattened project "{0}""#,
+                escape(project), properties),
+            None => format!("set newTask to make new inbox task with properties {}", properties),
+        };
+
+        let id = self.run(&format!("{}\n                        {}\n                        return id of newTask",
+            dates(contest)?, create))?;
+
+        contest.reminder_ids = vec![id];
+        Ok(())
+    }
+
+    fn update(&self, contest: &mut Contest) -> Result<(), NotifyError> {
+        for id in &contest.reminder_ids {
+            self.run(&format!(
+                r#"{}
+                        set contestTask to first flattened task whose id is "{}"
+                        set name of contestTask to "{}"
+                        set note of contestTask to "{}"
+                        set defer date of contestTask to deferDate
+                        set due date of contestTask to dueDate"#,
+                dates(contest)?, escape(id), escape(&reminder_name(contest)), escape(&reminder_body(contest))))?;
+        }
+        Ok(())
+    }
+
+    /// Deletes the tasks, skipping the ones the user already deleted.
+    fn withdraw(&self, contest: &Contest) -> Result<(), NotifyError> {
+        for id in &contest.reminder_ids {
+            self.run(&format!(
+                r#"set contestTasks to (flattened tasks whose id is "{}")
+                        repeat with contestTask in contestTasks
+                            delete contestTask
+                        end repeat"#, escape(id)))?;
+        }
+        Ok(())
+    }
+}
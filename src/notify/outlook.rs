@@ -0,0 +1,198 @@
+use crate::config::OutlookConfig;
+use crate::contest::Contest;
+use crate::notify::oauth::{load_token, request_token, save_token, Token};
+use crate::notify::{http_client, send, Notifier, NotifyError};
+use crate::paths::outlook_token_path;
+use crate::reminder::{contest_lead_times, due_time, reminder_body, reminder_name};
+
+use chrono::{TimeZone, Utc};
+use reqwest::blocking::RequestBuilder;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::cell::RefCell;
+use std::time::Duration;
+
+const GRAPH_URL: &str = "https://graph.microsoft.com/v1.0";
+
+/// Access to the calendars, and refresh tokens to keep it.
+const SCOPE: &str = "offline_access Calendars.ReadWrite";
+
+/// Answer starting the device code flow.
+#[derive(Deserialize)]
+struct DeviceCode {
+    device_code: String,
+    /// Tells the user where to enter which code.
+    message: String,
+    interval: u64,
+    expires_in: u64,
+}
+
+/// Notifier creating an Outlook calendar event spanning every new contest
+/// through Microsoft Graph, e.g. in a Microsoft 365 work calendar.
+///
+/// Authorize it once with --outlook-auth, which saves the OAuth tokens in
+/// the data directory. Outlook events have a single reminder, due with
+/// the earliest one. The ids of the events are saved in contest.reminder_ids,
+/// so changed contests update their event and finished ones delete it.
+pub struct Outlook<'a> {
+    config: &'a OutlookConfig,
+    token: RefCell<Option<Token>>,
+}
+
+impl<'a> Outlook<'a> {
+    pub fn new(config: &'a OutlookConfig) -> Self {
+        Outlook { config, token: RefCell::new(None) }
+    }
+
+    fn events_url(&self) -> String {
+        match &self.config.calendar_id {
+            Some(calendar_id) => format!("{}/me/calendars/{}/events", GRAPH_URL, calendar_id),
+            None => format!("{}/me/events", GRAPH_URL),
+        }
+    }
+
+    /// Returns a valid access token, refreshing and saving it if it expired.
+    fn access_token(&self) -> Result<String, NotifyError> {
+        let mut cached = self.token.borrow_mut();
+
+        let mut token = match cached.take() {
+            Some(token) => token,
+            None => load_token(&outlook_token_path(), "--outlook-auth")?,
+        };
+        if token.is_expired() {
+            let response = request_token(&token_url(self.config), &[
+                ("client_id", self.config.client_id.as_str()),
+                ("refresh_token", token.refresh_token.as_str()),
+                ("grant_type", "refresh_token"),
+                ("scope", SCOPE),
+            ])?;
+            token.refreshed(response);
+            save_token(&outlook_token_path(), &token)?;
+        }
+
+        let access_token = token.access_token.clone();
+        *cached = Some(token);
+        Ok(access_token)
+    }
+
+    fn send(&self, request: RequestBuilder) -> Result<String, NotifyError> {
+        send(request.bearer_auth(self.access_token()?))
+    }
+}
+
+impl Notifier for Outlook<'_> {
+    fn name(&self) -> &'static str {
+        "Outlook"
+    }
+
+    /// The calendar only holds contests, messages are dropped.
+    fn message(&self, title: &str, text: &str) -> Result<(), NotifyError> {
+        let _ = (title, text);
+        Ok(())
+    }
+
+    fn notify(&self, contest: &mut Contest) -> Result<(), NotifyError> {
+        let response: Value = serde_json::from_str(&self.send(http_client().post(self.events_url()).json(&event(contest)?))?)
+            .map_err(|e| NotifyError::new(format!("Failed to parse Outlook response. {}", e)))?;
+        let Some(event_id) = response["id"].as_str() else {
+            return Err(NotifyError::new(format!("Outlook response without event id: {}", response)))
+        };
+
+        contest.reminder_ids = vec![event_id.to_string()];
+        Ok(())
+    }
+
+    fn update(&self, contest: &mut Contest) -> Result<(), NotifyError> {
+        let event = event(contest)?;
+        for event_id in &contest.reminder_ids {
+            self.send(http_client().patch(format!("{}/me/events/{}", GRAPH_URL, event_id)).json(&event))?;
+        }
+        Ok(())
+    }
+
+    /// Deletes the events, skipping the ones the user already deleted.
+    fn withdraw(&self, contest: &Contest) -> Result<(), NotifyError> {
+        for event_id in &contest.reminder_ids {
+            match self.send(http_client().delete(format!("{}/me/events/{}", GRAPH_URL, event_id))) {
+                Err(error) if error.reason.as_deref().is_some_and(|reason| reason.contains("404 Not Found")) => {}
+                result => { result?; }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Busy event from the start to the end of the contest, reminding when
+/// its earliest reminder would be due.
+fn event(contest: &Contest) -> Result<Value, NotifyError> {
+    let Some(start) = contest.start_time_seconds else {
+        return Err(NotifyError::new("Contest without start time"))
+    };
+    let end = start + contest.duration_seconds.unwrap_or_default();
+    // Graph wants the time without offset, in the given time zone.
+    let date_time = |seconds: i64| {
+        Utc.timestamp_opt(seconds, 0).single().map(|time| json!({ "dateTime": time.format("%Y-%m-%dT%H:%M:%S").to_string(), "timeZone": "UTC" }))
+    };
+
+    let lead_time = contest_lead_times(contest)
+        .into_iter()
+        .map(|lead_time| start - due_time(contest, start, lead_time))
+        .max();
+
+    Ok(json!({
+        "subject": reminder_name(contest),
+        "body": { "contentType": "text", "content": reminder_body(contest) },
+        "start": date_time(start),
+        "end": date_time(end),
+        "showAs": "busy",
+        "isReminderOn": lead_time.is_some(),
+        "reminderMinutesBeforeStart": lead_time.unwrap_or_default() / 60,
+    }))
+}
+
+fn token_url(config: &OutlookConfig) -> String {
+    format!("https://login.microsoftonline.com/{}/oauth2/v2.0/token", config.tenant)
+}
+
+/// Lets the user grant access to their calendar with the device code flow
+/// and saves the OAuth tokens in outlook_token.json.
+///
+/// Prints where to enter which code and waits until access is granted.
+/// The flow has no redirect a code could be injected into, tokens are
+/// only issued for the device code this run requested, so it needs no
+/// state nor PKCE. Fails with the reason if it is denied or the code expires.
+pub fn authorize(config: &OutlookConfig) -> Result<(), String> {
+    let url = format!("https://login.microsoftonline.com/{}/oauth2/v2.0/devicecode", config.tenant);
+    let response = send(http_client().post(url).form(&[("client_id", config.client_id.as_str()), ("scope", SCOPE)]))
+        .map_err(|e| e.reason.unwrap_or_default())?;
+    let device: DeviceCode = serde_json::from_str(&response).map_err(|e| format!("Failed to parse device code. {}", e))?;
+    println!("{}", device.message);
+
+    let mut interval = device.interval;
+    let mut waited = 0;
+
+    while waited < device.expires_in {
+        std::thread::sleep(Duration::from_secs(interval));
+        waited += interval;
+
+        let result = request_token(&token_url(config), &[
+            ("grant_type", "urn:ietf:params:oauth:grant-type:device_code"),
+            ("client_id", config.client_id.as_str()),
+            ("device_code", device.device_code.as_str()),
+        ]);
+
+        match result {
+            Ok(response) => {
+                return response.into_token()
+                    .and_then(|token| save_token(&outlook_token_path(), &token))
+                    .map(|_| println!("Saved the tokens in {}", outlook_token_path().display()))
+                    .map_err(|e| e.reason.unwrap_or_default())
+            }
+            // The user did not enter the code yet.
+            Err(error) if error.reason.as_deref().is_some_and(|reason| reason.contains("authorization_pending")) => {}
+            Err(error) if error.reason.as_deref().is_some_and(|reason| reason.contains("slow_down")) => interval += 5,
+            Err(error) => return Err(error.reason.unwrap_or_default()),
+        }
+    }
+    Err("The device code expired before access was granted.".to_string())
+}
@@ -0,0 +1,50 @@
+use crate::contest::Contest;
+use crate::notify::{announcement, http_client, send, Notifier, NotifyError};
+
+use serde_json::{json, Value};
+
+/// Pushes endpoint of the Pushbullet API.
+const PUSHES_URL: &str = "https://api.pushbullet.com/v2/pushes";
+
+/// Notifier sending link pushes to every device of a Pushbullet account:
+/// one for every new contest and one whenever a reminder of a saved
+/// contest is due.
+///
+/// Pushbullet can not schedule pushes, due pushes are sent by the first
+/// run after they became due, see Notifier::remind.
+pub struct Pushbullet<'a> {
+    access_token: &'a str,
+}
+
+impl<'a> Pushbullet<'a> {
+    pub fn new(access_token: &'a str) -> Self {
+        Pushbullet { access_token }
+    }
+
+    fn push(&self, push: Value) -> Result<(), NotifyError> {
+        send(http_client().post(PUSHES_URL).header("Access-Token", self.access_token).json(&push))?;
+        Ok(())
+    }
+
+    fn link_push(&self, title: &str, contest: &Contest) -> Result<(), NotifyError> {
+        self.push(json!({ "type": "link", "title": title, "body": announcement(contest), "url": contest.url() }))
+    }
+}
+
+impl Notifier for Pushbullet<'_> {
+    fn name(&self) -> &'static str {
+        "Pushbullet"
+    }
+
+    fn message(&self, title: &str, text: &str) -> Result<(), NotifyError> {
+        self.push(json!({ "type": "note", "title": title, "body": text }))
+    }
+
+    fn notify(&self, contest: &mut Contest) -> Result<(), NotifyError> {
+        self.link_push(&contest.name, contest)
+    }
+
+    fn remind(&self, contest: &Contest, title: &str) -> Result<(), NotifyError> {
+        self.link_push(title, contest)
+    }
+}
@@ -0,0 +1,72 @@
+use crate::config::PushoverConfig;
+use crate::contest::Contest;
+use crate::notify::{announcement, http_client, send, Notifier, NotifyError};
+
+/// Messages endpoint of the Pushover API.
+const MESSAGES_URL: &str = "https://api.pushover.net/1/messages.json";
+
+/// Priority of Pushover emergency messages, repeated until acknowledged.
+const EMERGENCY_PRIORITY: i8 = 2;
+
+/// How often (seconds) and how long emergency messages are repeated.
+const EMERGENCY_RETRY_SECONDS: u32 = 60;
+const EMERGENCY_EXPIRE_SECONDS: u32 = 1800;
+
+/// Notifier pushing messages through Pushover: one with `priority` for
+/// every new contest, and one with `due_priority` whenever a reminder of a
+/// saved contest is due.
+///
+/// Pushover can not schedule messages, due messages are pushed by the
+/// first run after they became due, see Notifier::remind.
+pub struct Pushover<'a> {
+    config: &'a PushoverConfig,
+}
+
+impl<'a> Pushover<'a> {
+    pub fn new(config: &'a PushoverConfig) -> Self {
+        Pushover { config }
+    }
+
+    /// Pushes a message, linking to the page of the contest if there is one.
+    fn push(&self, title: &str, message: &str, contest: Option<&Contest>, priority: i8) -> Result<(), NotifyError> {
+        let mut form = vec![
+            ("token", self.config.app_token.clone()),
+            ("user", self.config.user_key.clone()),
+            ("title", title.to_string()),
+            ("message", message.to_string()),
+            ("priority", priority.to_string()),
+        ];
+        if let Some(contest) = contest {
+            form.push(("url", contest.url()));
+            form.push(("url_title", "Contest page".to_string()));
+        }
+        if priority == EMERGENCY_PRIORITY {
+            form.push(("retry", EMERGENCY_RETRY_SECONDS.to_string()));
+            form.push(("expire", EMERGENCY_EXPIRE_SECONDS.to_string()));
+        }
+        if let Some(device) = &self.config.device {
+            form.push(("device", device.clone()));
+        }
+
+        send(http_client().post(MESSAGES_URL).form(&form))?;
+        Ok(())
+    }
+}
+
+impl Notifier for Pushover<'_> {
+    fn name(&self) -> &'static str {
+        "Pushover"
+    }
+
+    fn message(&self, title: &str, text: &str) -> Result<(), NotifyError> {
+        self.push(title, text, None, self.config.priority)
+    }
+
+    fn notify(&self, contest: &mut Contest) -> Result<(), NotifyError> {
+        self.push(&contest.name, &announcement(contest), Some(contest), self.config.priority)
+    }
+
+    fn remind(&self, contest: &Contest, title: &str) -> Result<(), NotifyError> {
+        self.push(title, &announcement(contest), Some(contest), self.config.due_priority)
+    }
+}
@@ -0,0 +1,55 @@
+use crate::contest::Contest;
+use crate::notify::{contest_event, run_command, Notifier, NotifyError};
+
+use serde_json::{json, Value};
+use std::fs;
+use std::process::Command;
+
+/// Notifier running an Apple Shortcut for every new, changed and finished
+/// contest, so users can build their own automations.
+///
+/// The shortcut gets the contest_event of the contest as JSON input,
+/// messages about the run as event "message" with title and text.
+pub struct Shortcuts<'a> {
+    name: &'a str,
+}
+
+impl<'a> Shortcuts<'a> {
+    pub fn new(name: &'a str) -> Self {
+        Shortcuts { name }
+    }
+
+    /// Runs the shortcut with the input, passed through a temporary file
+    /// as shortcuts run only reads input from files.
+    fn run(&self, input: Value) -> Result<(), NotifyError> {
+        let path = std::env::temp_dir().join(format!("codeforces-reminder-shortcut-{}.json", std::process::id()));
+        fs::write(&path, input.to_string())
+            .map_err(|e| NotifyError::new(format!("Failed to write shortcut input to {}. {}", path.display(), e)))?;
+
+        let result = run_command(Command::new("shortcuts").arg("run").arg(self.name).arg("--input-path").arg(&path));
+        let _ = fs::remove_file(&path);
+        result
+    }
+}
+
+impl Notifier for Shortcuts<'_> {
+    fn name(&self) -> &'static str {
+        "Shortcuts"
+    }
+
+    fn message(&self, title: &str, text: &str) -> Result<(), NotifyError> {
+        self.run(json!({ "event": "message", "title": title, "text": text }))
+    }
+
+    fn notify(&self, contest: &mut Contest) -> Result<(), NotifyError> {
+        self.run(contest_event("new", contest))
+    }
+
+    fn update(&self, contest: &mut Contest) -> Result<(), NotifyError> {
+        self.run(contest_event("changed", contest))
+    }
+
+    fn withdraw(&self, contest: &Contest) -> Result<(), NotifyError> {
+        self.run(contest_event("finished", contest))
+    }
+}
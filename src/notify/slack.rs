@@ -0,0 +1,70 @@
+use crate::config::SlackConfig;
+use crate::contest::{format_duration, format_local_time, Contest};
+use crate::notify::{http_client, send, Notifier, NotifyError};
+use crate::reminder::rule_matches;
+
+use serde_json::{json, Value};
+
+/// Notifier posting a message to a Slack incoming webhook for every
+/// new contest.
+///
+/// Each webhook posts to one channel, the webhook of the first rule in
+/// channels matching the contest is used, webhook_url if none matches.
+/// Contests without a webhook are not posted. Slack does not return ids
+/// of webhook messages, so they are never edited.
+pub struct Slack<'a> {
+    config: &'a SlackConfig,
+}
+
+impl<'a> Slack<'a> {
+    pub fn new(config: &'a SlackConfig) -> Self {
+        Slack { config }
+    }
+
+    fn webhook_url(&self, contest: &Contest) -> Option<&'a str> {
+        self.config.channels
+            .iter()
+            .find(|rule| rule_matches(contest, rule.contest_type.as_deref(), rule.pattern.as_deref(), "Slack channel"))
+            .map(|rule| rule.webhook_url.as_str())
+            .or(self.config.webhook_url.as_deref())
+    }
+}
+
+impl Notifier for Slack<'_> {
+    fn name(&self) -> &'static str {
+        "Slack"
+    }
+
+    fn message(&self, title: &str, text: &str) -> Result<(), NotifyError> {
+        // Messages about the run go to the channel of contests matching no rule.
+        let Some(url) = &self.config.webhook_url else { return Ok(()) };
+
+        send(http_client().post(url).json(&json!({ "text": format!("*{}*\n{}", title, text) })))?;
+        Ok(())
+    }
+
+    fn notify(&self, contest: &mut Contest) -> Result<(), NotifyError> {
+        let Some(url) = self.webhook_url(contest) else { return Ok(()) };
+
+        send(http_client().post(url).json(&message(contest)))?;
+        Ok(())
+    }
+}
+
+/// Message with the start formatted by Slack in the time zone of each
+/// reader, the local time of the sync is the fallback.
+fn message(contest: &Contest) -> Value {
+    let mut lines = vec![format!("*<{}|{}>*", contest.url(), contest.name)];
+
+    if let Some(start) = contest.start_time_seconds {
+        lines.push(format!("Starts <!date^{}^{{date_short_pretty}} at {{time}}|{}>", start, format_local_time(start)));
+    }
+    if let Some(duration) = contest.duration_seconds {
+        lines.push(format!("Lasts {}", format_duration(duration)));
+    }
+
+    json!({
+        "text": contest.name,
+        "blocks": [{ "type": "section", "text": { "type": "mrkdwn", "text": lines.join("\n") } }],
+    })
+}
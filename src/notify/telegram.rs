@@ -0,0 +1,73 @@
+use crate::config::TelegramConfig;
+use crate::contest::Contest;
+use crate::notify::{announcement, http_client, send, Notifier, NotifyError};
+
+use serde_json::{json, Value};
+
+/// Notifier sending a message through a Telegram bot for every new contest.
+///
+/// The ids of the messages are saved in contest.reminder_ids, so the
+/// message of a changed contest is edited. Messages of finished contests
+/// are kept.
+pub struct Telegram<'a> {
+    config: &'a TelegramConfig,
+}
+
+impl<'a> Telegram<'a> {
+    pub fn new(config: &'a TelegramConfig) -> Self {
+        Telegram { config }
+    }
+
+    fn method_url(&self, method: &str) -> String {
+        format!("https://api.telegram.org/bot{}/{}", self.config.bot_token, method)
+    }
+}
+
+impl Notifier for Telegram<'_> {
+    fn name(&self) -> &'static str {
+        "Telegram"
+    }
+
+    fn message(&self, title: &str, text: &str) -> Result<(), NotifyError> {
+        let request = http_client().post(self.method_url("sendMessage")).json(&json!({
+            "chat_id": self.config.chat_id,
+            "text": format!("{}\n{}", title, text),
+        }));
+        send(request)?;
+        Ok(())
+    }
+
+    fn notify(&self, contest: &mut Contest) -> Result<(), NotifyError> {
+        let request = http_client().post(self.method_url("sendMessage")).json(&json!({
+            "chat_id": self.config.chat_id,
+            "text": announcement(contest),
+        }));
+
+        let response: Value = serde_json::from_str(&send(request)?)
+            .map_err(|e| NotifyError::new(format!("Failed to parse Telegram response. {}", e)))?;
+        let Some(message_id) = response["result"]["message_id"].as_i64() else {
+            return Err(NotifyError::new(format!("Telegram response without message id: {}", response)))
+        };
+
+        contest.reminder_ids = vec![message_id.to_string()];
+        Ok(())
+    }
+
+    fn update(&self, contest: &mut Contest) -> Result<(), NotifyError> {
+        for message_id in &contest.reminder_ids {
+            let request = http_client().post(self.method_url("editMessageText")).json(&json!({
+                "chat_id": self.config.chat_id,
+                "message_id": message_id,
+                "text": announcement(contest),
+            }));
+
+            // Editing a message to the same text fails, e.g. if only the phase changed.
+            if let Err(error) = send(request) {
+                if !error.reason.as_deref().is_some_and(|reason| reason.contains("message is not modified")) {
+                    return Err(error)
+                }
+            }
+        }
+        Ok(())
+    }
+}
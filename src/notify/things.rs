@@ -0,0 +1,93 @@
+use crate::contest::Contest;
+use crate::notify::{run_apple_script, Notifier, NotifyError};
+use crate::reminder::{apple_script_date, display_notification, escape, reminder_body, reminder_name, CommandRunner};
+
+/// Notifier creating a Things 3 to-do for every new contest, scripted
+/// through AppleScript like the Reminders backend.
+///
+/// The to-do is due and scheduled (its when date) on the start of the
+/// contest. Its id is saved in contest.reminder_ids, so changed contests
+/// update it and finished ones delete it.
+pub struct Things<'a> {
+    runner: &'a dyn CommandRunner,
+    project: Option<&'a str>,
+}
+
+impl<'a> Things<'a> {
+    pub fn new(runner: &'a dyn CommandRunner, project: Option<&'a str>) -> Self {
+        Things { runner, project }
+    }
+
+    fn run(&self, commands: &str) -> Result<String, NotifyError> {
+        run_apple_script(self.runner, &format!(
+            r#"
+            with timeout of 30 seconds
+                tell application "Things3"
+                    {}
+                end tell
+            end timeout"#, commands))
+    }
+}
+
+impl Notifier for Things<'_> {
+    fn name(&self) -> &'static str {
+        "Things"
+    }
+
+    /// Shows the message as Notification Center banner, not as to-do.
+    fn message(&self, title: &str, text: &str) -> Result<(), NotifyError> {
+        if display_notification(self.runner, title, "", text) { Ok(()) } else { Err(NotifyError::logged()) }
+    }
+
+    fn notify(&self, contest: &mut Contest) -> Result<(), NotifyError> {
+        let Some(start) = contest.start_time_seconds else {
+            return Err(NotifyError::new("Contest without start time"))
+        };
+
+        let project = match self.project {
+            Some(project) => format!(
+                r#"if not (exists project "{0}") then make new project with properties {{name:"{0}"}}
+                    set project of newToDo to project "{0}""#, escape(project)),
+            None => String::new(),
+        };
+
+        let id = self.run(&format!(
+            r#"{}
+                    set newToDo to make new to do with properties {{name:"{}", notes:"{}"}}
+                    set due date of newToDo to startDate
+                    schedule newToDo for startDate
+                    {}
+                    return id of newToDo"#,
+            apple_script_date("startDate", start), escape(&reminder_name(contest)), escape(&reminder_body(contest)), project))?;
+
+        contest.reminder_ids = vec![id];
+        Ok(())
+    }
+
+    fn update(&self, contest: &mut Contest) -> Result<(), NotifyError> {
+        let Some(start) = contest.start_time_seconds else {
+            return Err(NotifyError::new("Contest without start time"))
+        };
+
+        for id in &contest.reminder_ids {
+            self.run(&format!(
+                r#"{}
+                    set contestToDo to to do id "{}"
+                    set name of contestToDo to "{}"
+                    set notes of contestToDo to "{}"
+                    set due date of contestToDo to startDate
+                    schedule contestToDo for startDate"#,
+                apple_script_date("startDate", start), escape(id), escape(&reminder_name(contest)), escape(&reminder_body(contest))))?;
+        }
+        Ok(())
+    }
+
+    /// Deletes the to-dos, moving them to the Trash of Things.
+    /// To-dos the user already deleted are skipped.
+    fn withdraw(&self, contest: &Contest) -> Result<(), NotifyError> {
+        for id in &contest.reminder_ids {
+            self.run(&format!(r#"if exists to do id "{0}" then delete to do id "{0}""#, escape(id)))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,110 @@
+use crate::contest::Contest;
+use crate::notify::{run_command, scheduled, Notifier, NotifyError};
+use crate::reminder::reminder_body;
+
+use std::process::Command;
+
+/// Application user model id the toasts are shown for. Toasts need the id
+/// of an installed app, Windows PowerShell is on every machine.
+const APP_ID: &str = r"{1AC14E77-02E7-4E5D-B744-2EB1AE5198B7}\WindowsPowerShell\v1.0\powershell.exe";
+
+/// Group of all scheduled toasts, so others are never removed.
+const GROUP: &str = "codeforces-reminder";
+
+/// Notifier scheduling Windows toast notifications, the default
+/// backend on Windows.
+///
+/// The toasts are scheduled through the WinRT ToastNotificationManager,
+/// called from PowerShell. Their tags are saved in contest.reminder_ids,
+/// so they can be removed from the schedule again.
+pub struct Toast;
+
+impl Notifier for Toast {
+    fn name(&self) -> &'static str {
+        "toast"
+    }
+
+    fn message(&self, title: &str, text: &str) -> Result<(), NotifyError> {
+        let script = format!("{}\n$xml = New-Object Windows.Data.Xml.Dom.XmlDocument; $xml.LoadXml({})\n$notifier.Show((New-Object Windows.UI.Notifications.ToastNotification $xml))",
+            header(), quote(&toast_xml(title, text)));
+        run_powershell(&script)
+    }
+
+    fn notify(&self, contest: &mut Contest) -> Result<(), NotifyError> {
+        let notifications = scheduled(contest)?;
+
+        let now = chrono::Utc::now().timestamp();
+        let body = reminder_body(contest);
+        let mut script = vec![header()];
+        let mut tags = Vec::new();
+
+        for (due, title) in notifications {
+            let tag = format!("{}-{}-{}", contest.source_host(), contest.id, due);
+            script.push(format!("$xml = New-Object Windows.Data.Xml.Dom.XmlDocument; $xml.LoadXml({})",
+                quote(&toast_xml(&title, &body))));
+
+            // Toasts already due are shown right away, scheduling them would fail.
+            if due <= now {
+                script.push("$notifier.Show((New-Object Windows.UI.Notifications.ToastNotification $xml))".to_string());
+                continue;
+            }
+
+            script.push(format!(
+                "$toast = New-Object Windows.UI.Notifications.ScheduledToastNotification $xml, ([DateTimeOffset]::FromUnixTimeSeconds({}))",
+                due));
+            script.push(format!("$toast.Tag = {}; $toast.Group = {}; $notifier.AddToSchedule($toast)", quote(&tag), quote(GROUP)));
+            tags.push(tag);
+        }
+
+        run_powershell(&script.join("\n"))?;
+        contest.reminder_ids = tags;
+        Ok(())
+    }
+
+    fn update(&self, contest: &mut Contest) -> Result<(), NotifyError> {
+        self.withdraw(contest)?;
+        self.notify(contest)
+    }
+
+    fn withdraw(&self, contest: &Contest) -> Result<(), NotifyError> {
+        if contest.reminder_ids.is_empty() { return Ok(()) }
+
+        let tags = contest.reminder_ids.iter().map(|tag| quote(tag)).collect::<Vec<_>>().join(", ");
+        let script = format!(
+            "{}\n$notifier.GetScheduledToastNotifications() | Where-Object {{ $_.Group -eq {} -and @({}) -contains $_.Tag }} | ForEach-Object {{ $notifier.RemoveFromSchedule($_) }}",
+            header(), quote(GROUP), tags);
+
+        run_powershell(&script)
+    }
+}
+
+/// Loads the WinRT types and creates the notifier of APP_ID.
+fn header() -> String {
+    format!(
+        "$ErrorActionPreference = 'Stop'
+[Windows.UI.Notifications.ToastNotificationManager, Windows.UI.Notifications, ContentType = WindowsRuntime] | Out-Null
+[Windows.Data.Xml.Dom.XmlDocument, Windows.Data.Xml.Dom.XmlDocument, ContentType = WindowsRuntime] | Out-Null
+$notifier = [Windows.UI.Notifications.ToastNotificationManager]::CreateToastNotifier({})", quote(APP_ID))
+}
+
+/// Toast content with the title and body as text lines.
+fn toast_xml(title: &str, body: &str) -> String {
+    format!(r#"<toast><visual><binding template="ToastGeneric"><text>{}</text><text>{}</text></binding></visual></toast>"#,
+        escape_xml(title), escape_xml(body))
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Quotes text as single quoted PowerShell string.
+fn quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "''"))
+}
+
+fn run_powershell(script: &str) -> Result<(), NotifyError> {
+    run_command(Command::new("powershell").args(["-NoProfile", "-NonInteractive", "-Command", script]))
+}
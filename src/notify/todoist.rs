@@ -0,0 +1,90 @@
+use crate::config::TodoistConfig;
+use crate::contest::Contest;
+use crate::notify::{http_client, send, Notifier, NotifyError};
+use crate::reminder::{reminder_body, reminder_name};
+
+use chrono::{TimeZone, Utc};
+use serde_json::{json, Value};
+
+const TASKS_URL: &str = "https://api.todoist.com/api/v1/tasks";
+
+/// Notifier creating a Todoist task for every new contest, due at its start.
+///
+/// The ids of the tasks are saved in contest.reminder_ids, so changed
+/// contests update their task and finished ones close it, like the
+/// Reminders backend.
+pub struct Todoist<'a> {
+    config: &'a TodoistConfig,
+}
+
+impl<'a> Todoist<'a> {
+    pub fn new(config: &'a TodoistConfig) -> Self {
+        Todoist { config }
+    }
+
+    fn post(&self, url: &str, task: Option<&Value>) -> Result<String, NotifyError> {
+        let mut request = http_client().post(url).bearer_auth(&self.config.api_token);
+        if let Some(task) = task {
+            request = request.json(task);
+        }
+        send(request)
+    }
+
+    /// Task of a contest, without the project as tasks keep theirs on update.
+    fn task(&self, contest: &Contest) -> Value {
+        let mut task = json!({ "content": reminder_name(contest), "description": reminder_body(contest) });
+        if let Some(start) = contest.start_time_seconds.and_then(|start| Utc.timestamp_opt(start, 0).single()) {
+            task["due_datetime"] = json!(start.to_rfc3339());
+        }
+        if !self.config.labels.is_empty() {
+            task["labels"] = json!(self.config.labels);
+        }
+        task
+    }
+
+    /// Creates a task in the configured project and returns its id.
+    fn create(&self, mut task: Value) -> Result<String, NotifyError> {
+        if let Some(project_id) = &self.config.project_id {
+            task["project_id"] = json!(project_id);
+        }
+
+        let response: Value = serde_json::from_str(&self.post(TASKS_URL, Some(&task))?)
+            .map_err(|e| NotifyError::new(format!("Failed to parse Todoist response. {}", e)))?;
+        let Some(id) = response["id"].as_str() else {
+            return Err(NotifyError::new(format!("Todoist response without task id: {}", response)))
+        };
+        Ok(id.to_string())
+    }
+}
+
+impl Notifier for Todoist<'_> {
+    fn name(&self) -> &'static str {
+        "Todoist"
+    }
+
+    /// Adds the message as task without due date.
+    fn message(&self, title: &str, text: &str) -> Result<(), NotifyError> {
+        self.create(json!({ "content": title, "description": text }))?;
+        Ok(())
+    }
+
+    fn notify(&self, contest: &mut Contest) -> Result<(), NotifyError> {
+        let id = self.create(self.task(contest))?;
+        contest.reminder_ids = vec![id];
+        Ok(())
+    }
+
+    fn update(&self, contest: &mut Contest) -> Result<(), NotifyError> {
+        for id in &contest.reminder_ids {
+            self.post(&format!("{}/{}", TASKS_URL, id), Some(&self.task(contest)))?;
+        }
+        Ok(())
+    }
+
+    fn withdraw(&self, contest: &Contest) -> Result<(), NotifyError> {
+        for id in &contest.reminder_ids {
+            self.post(&format!("{}/{}/close", TASKS_URL, id), None)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,58 @@
+use crate::config::TwilioConfig;
+use crate::contest::{format_local_time, Contest};
+use crate::notify::{http_client, send, Notifier, NotifyError};
+
+/// Notifier texting every new contest via Twilio, and again whenever a
+/// reminder of a saved contest is due.
+///
+/// Built with the twilio feature only. SMS can not be scheduled, due texts
+/// are sent by the first run after they became due, see Notifier::remind.
+pub struct Twilio<'a> {
+    config: &'a TwilioConfig,
+}
+
+impl<'a> Twilio<'a> {
+    pub fn new(config: &'a TwilioConfig) -> Self {
+        Twilio { config }
+    }
+
+    fn text(&self, body: &str) -> Result<(), NotifyError> {
+        let url = format!("https://api.twilio.com/2010-04-01/Accounts/{}/Messages.json", self.config.account_sid);
+
+        for to in &self.config.to {
+            let request = http_client()
+                .post(&url)
+                .basic_auth(&self.config.account_sid, Some(&self.config.auth_token))
+                .form(&[("From", self.config.from.as_str()), ("To", to.as_str()), ("Body", body)]);
+            send(request)?;
+        }
+        Ok(())
+    }
+}
+
+impl Notifier for Twilio<'_> {
+    fn name(&self) -> &'static str {
+        "Twilio"
+    }
+
+    fn message(&self, title: &str, text: &str) -> Result<(), NotifyError> {
+        self.text(&format!("{}\n{}", title, text))
+    }
+
+    fn notify(&self, contest: &mut Contest) -> Result<(), NotifyError> {
+        self.text(&sms(&contest.name, contest))
+    }
+
+    fn remind(&self, contest: &Contest, title: &str) -> Result<(), NotifyError> {
+        self.text(&sms(title, contest))
+    }
+}
+
+/// Short text with the title, local start time and link, as texts
+/// longer than one segment cost more.
+fn sms(title: &str, contest: &Contest) -> String {
+    match contest.start_time_seconds {
+        Some(start) => format!("{}, {}\n{}", title, format_local_time(start), contest.url()),
+        None => format!("{}\n{}", title, contest.url()),
+    }
+}
@@ -0,0 +1,52 @@
+use crate::config::WebhookConfig;
+use crate::contest::Contest;
+use crate::notify::{contest_event, http_client, send, Notifier, NotifyError};
+
+use serde_json::{json, Value};
+
+/// Notifier posting every new, changed and finished contest as JSON to
+/// an HTTP endpoint, e.g. of n8n, Zapier or a bot.
+///
+/// The body is the contest_event of the contest. Messages about the run
+/// are posted as event "message" with title and text.
+pub struct Webhook<'a> {
+    config: &'a WebhookConfig,
+}
+
+impl<'a> Webhook<'a> {
+    pub fn new(config: &'a WebhookConfig) -> Self {
+        Webhook { config }
+    }
+
+    fn send(&self, body: Value) -> Result<(), NotifyError> {
+        let mut request = http_client().post(&self.config.url).json(&body);
+        for (name, value) in &self.config.headers {
+            request = request.header(name, value);
+        }
+
+        send(request)?;
+        Ok(())
+    }
+}
+
+impl Notifier for Webhook<'_> {
+    fn name(&self) -> &'static str {
+        "Webhook"
+    }
+
+    fn message(&self, title: &str, text: &str) -> Result<(), NotifyError> {
+        self.send(json!({ "event": "message", "title": title, "text": text }))
+    }
+
+    fn notify(&self, contest: &mut Contest) -> Result<(), NotifyError> {
+        self.send(contest_event("new", contest))
+    }
+
+    fn update(&self, contest: &mut Contest) -> Result<(), NotifyError> {
+        self.send(contest_event("changed", contest))
+    }
+
+    fn withdraw(&self, contest: &Contest) -> Result<(), NotifyError> {
+        self.send(contest_event("finished", contest))
+    }
+}
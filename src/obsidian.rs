@@ -0,0 +1,122 @@
+use crate::config::ObsidianConfig;
+use crate::contest::{format_duration, Contest};
+
+use chrono::{Local, TimeZone};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+/// Appends a line per contest to the Obsidian daily note of the day it
+/// starts on, creating the note and its folder if missing.
+///
+/// Every line ends with a hidden comment marking its contest, contests
+/// whose marker is already in their note are skipped, so every run only
+/// adds the new ones. Lines of contests that moved to another day are left
+/// in the old note. Returns the number of added lines.
+pub fn append_to_daily_notes(config: &ObsidianConfig, contests: &[Contest]) -> io::Result<usize> {
+    let mut contests: Vec<&Contest> = contests.iter().filter(|contest| contest.start_time_seconds.is_some()).collect();
+    contests.sort_by_key(|contest| (contest.start_time_seconds, contest.id));
+
+    let mut added = 0;
+
+    for contest in contests {
+        let Some(start) = contest.start_time_seconds.and_then(|start| Local.timestamp_opt(start, 0).single()) else {
+            continue
+        };
+        let path = note_path(config, &start.format(&config.date_format).to_string());
+
+        let note = match fs::read_to_string(&path) {
+            Ok(note) => note,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e),
+        };
+        let marker = marker(contest);
+        if note.lines().any(|line| line.trim_end().ends_with(&marker)) { continue }
+
+        if let Some(folder) = path.parent() {
+            fs::create_dir_all(folder)?;
+        }
+        let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+
+        // A note not ending in a newline would get the line glued to its last one.
+        let separator = if note.is_empty() || note.ends_with('\n') { "" } else { "\n" };
+        let line = render(&config.template, contest, &start.format("%H:%M").to_string());
+        writeln!(file, "{}{} {}", separator, line, marker)?;
+        added += 1;
+    }
+    Ok(added)
+}
+
+/// Obsidian comment identifying the contest by source and id,
+/// hidden in the reading view.
+fn marker(contest: &Contest) -> String {
+    format!("%% cfr:{}/{} %%", contest.source_url(), contest.id)
+}
+
+/// Path of the daily note titled date in the vault.
+fn note_path(config: &ObsidianConfig, date: &str) -> PathBuf {
+    config.vault_path.join(&config.daily_notes_folder).join(format!("{}.md", date))
+}
+
+/// Fills in {name}, {time}, {duration} and {url} of the template.
+fn render(template: &str, contest: &Contest, time: &str) -> String {
+    template
+        .replace("{name}", &contest.name)
+        .replace("{time}", time)
+        .replace("{duration}", &contest.duration_seconds.map(format_duration).unwrap_or_default())
+        .replace("{url}", &contest.url())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contest::tests::contest;
+
+    fn vault(name: &str) -> ObsidianConfig {
+        let vault_path = std::env::temp_dir().join(name);
+        let _ = fs::remove_dir_all(&vault_path);
+        serde_json::from_value(serde_json::json!({ "vault_path": vault_path, "daily_notes_folder": "Daily" })).unwrap()
+    }
+
+    fn note_of(config: &ObsidianConfig, start: i64) -> PathBuf {
+        note_path(config, &Local.timestamp_opt(start, 0).unwrap().format(&config.date_format).to_string())
+    }
+
+    #[test]
+    fn renders_the_template() {
+        let line = render("- [ ] {time} [{name}]({url}) ({duration})", &contest(2101, "Round 1021", 0), "16:35");
+
+        assert_eq!(line, "- [ ] 16:35 [Round 1021](https://codeforces.com/contests/2101) (2 hours)");
+    }
+
+    #[test]
+    fn adds_each_contest_once() {
+        let config = vault("codeforces-reminder-test-vault-once");
+        let first = contest(2101, "Round 1021", 1745850900);
+        let second = contest(2102, "Round 1022", 1745850900 + 60);
+
+        assert_eq!(append_to_daily_notes(&config, std::slice::from_ref(&first)).unwrap(), 1);
+        assert_eq!(append_to_daily_notes(&config, &[first.clone(), second.clone()]).unwrap(), 1);
+        assert_eq!(append_to_daily_notes(&config, &[first, second]).unwrap(), 0);
+
+        let note = fs::read_to_string(note_of(&config, 1745850900)).unwrap();
+        fs::remove_dir_all(&config.vault_path).unwrap();
+        assert_eq!(note.lines().count(), 2);
+        assert!(note.lines().next().unwrap().ends_with("%% cfr:https://codeforces.com/api/2101 %%"));
+    }
+
+    #[test]
+    fn keeps_existing_note_lines_apart() {
+        let config = vault("codeforces-reminder-test-vault-existing");
+        let path = note_of(&config, 1745850900);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "# Today").unwrap();
+
+        append_to_daily_notes(&config, &[contest(2101, "Round 1021", 1745850900)]).unwrap();
+
+        let note = fs::read_to_string(&path).unwrap();
+        fs::remove_dir_all(&config.vault_path).unwrap();
+        assert!(note.starts_with("# Today\n"), "{}", note);
+        assert!(note.ends_with("%% cfr:https://codeforces.com/api/2101 %%\n"));
+    }
+}
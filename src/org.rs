@@ -0,0 +1,120 @@
+use crate::contest::{format_duration, Contest};
+use crate::ics::uid;
+use crate::local::write_atomically;
+
+use chrono::{DateTime, Local, TimeZone};
+use std::fmt::Write;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Formats the contests as Org-mode file with a TODO entry per contest
+/// with a start time, SCHEDULED from its start to its end in local time.
+///
+/// Entries are sorted by start time, then id, like the calendar.
+pub fn format_org(contests: &[Contest]) -> String {
+    let mut contests: Vec<&Contest> = contests.iter().filter(|contest| contest.start_time_seconds.is_some()).collect();
+    contests.sort_by_key(|contest| (contest.start_time_seconds, contest.id));
+
+    let mut org = String::from("#+TITLE: Contests\n");
+
+    for contest in contests {
+        let Some(start) = contest.start_time_seconds.and_then(|start| Local.timestamp_opt(start, 0).single()) else {
+            continue
+        };
+        let end = contest.duration_seconds.and_then(|duration| Local.timestamp_opt(start.timestamp() + duration, 0).single());
+
+        let _ = writeln!(org, "* TODO {}", contest.name.replace('\n', " "));
+        let _ = writeln!(org, "  SCHEDULED: {}", timestamp(start, end));
+        let _ = writeln!(org, "  :PROPERTIES:");
+        let _ = writeln!(org, "  :CONTEST_UID: {}", uid(contest));
+        let _ = writeln!(org, "  :URL: {}", contest.url());
+        if let Some(duration) = contest.duration_seconds {
+            let _ = writeln!(org, "  :DURATION: {}", format_duration(duration));
+        }
+        let _ = writeln!(org, "  :END:");
+    }
+    org
+}
+
+/// Active timestamp from start to end, a time range if both are on the same day.
+fn timestamp(start: DateTime<Local>, end: Option<DateTime<Local>>) -> String {
+    let format = |time: DateTime<Local>| time.format("%Y-%m-%d %a %H:%M").to_string();
+
+    match end {
+        Some(end) if end.date_naive() == start.date_naive() => format!("<{}-{}>", format(start), end.format("%H:%M")),
+        Some(end) => format!("<{}>--<{}>", format(start), format(end)),
+        None => format!("<{}>", format(start)),
+    }
+}
+
+/// Writes the Org file of the contests to path, unless the file
+/// already has the same content. Returns true if it was written.
+pub fn write_org(path: &Path, contests: &[Contest]) -> io::Result<bool> {
+    let org = format_org(contests);
+
+    if fs::read_to_string(path).is_ok_and(|existing| existing == org) {
+        return Ok(false);
+    }
+
+    write_atomically(path, &org)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contest::tests::contest;
+
+    fn local(day: u32, hour: u32, minute: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2025, 4, day, hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn timestamps_span_the_contest() {
+        assert_eq!(timestamp(local(28, 16, 35), Some(local(28, 18, 35))), "<2025-04-28 Mon 16:35-18:35>");
+        assert_eq!(timestamp(local(28, 23, 0), Some(local(29, 1, 0))), "<2025-04-28 Mon 23:00>--<2025-04-29 Tue 01:00>");
+        assert_eq!(timestamp(local(28, 16, 35), None), "<2025-04-28 Mon 16:35>");
+    }
+
+    #[test]
+    fn entry_per_contest_in_start_order() {
+        let start = local(28, 16, 35).timestamp();
+        let org = format_org(&[
+            contest(2102, "Round 1022", start + 86400),
+            Contest { start_time_seconds: None, ..contest(2103, "Round 1023", 0) },
+            Contest { duration_seconds: None, ..contest(2101, "Round\n1021", start) },
+        ]);
+
+        assert_eq!(org, "\
+#+TITLE: Contests
+* TODO Round 1021
+  SCHEDULED: <2025-04-28 Mon 16:35>
+  :PROPERTIES:
+  :CONTEST_UID: 2101@codeforces.com
+  :URL: https://codeforces.com/contests/2101
+  :END:
+* TODO Round 1022
+  SCHEDULED: <2025-04-29 Tue 16:35-18:35>
+  :PROPERTIES:
+  :CONTEST_UID: 2102@codeforces.com
+  :URL: https://codeforces.com/contests/2102
+  :DURATION: 2 hours
+  :END:
+");
+    }
+
+    #[test]
+    fn writes_only_changed_files() {
+        let path = std::env::temp_dir().join("codeforces-reminder-test-contests.org");
+        let _ = fs::remove_file(&path);
+        let contests = [contest(2101, "Round 1021", 2_000)];
+
+        let first = write_org(&path, &contests).unwrap();
+        let second = write_org(&path, &contests).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert!(first);
+        assert!(!second);
+    }
+}
@@ -1,18 +1,41 @@
+use std::ffi::OsString;
 use std::path::PathBuf;
-use std::sync::OnceLock;
+use std::sync::{Mutex, OnceLock};
 use std::fs::{self, File};
-use std::io::{Write, BufReader, BufRead};
+use std::io::{self, Write, BufReader, BufRead};
 
 static CONTEST_FILE_PATH: OnceLock<PathBuf> = OnceLock::new();
-static LOG_FILE: OnceLock<File> = OnceLock::new();
+static LOG_FILE: OnceLock<Mutex<File>> = OnceLock::new();
+static CONFIG_FILE_PATH: OnceLock<PathBuf> = OnceLock::new();
+static PROFILE: OnceLock<String> = OnceLock::new();
+
+/// Profile used if --profile is not given. Its files stay in the data directory.
+pub const DEFAULT_PROFILE: &str = "default";
 
 const CONTEST_FILE_NAME: &str = "contests.json";
 const LOG_FILE_NAME: &str = "error_log.txt";
+const CONFIG_FILE_NAME: &str = "config.json";
+const HISTORY_FILE_NAME: &str = "history.jsonl";
+const STATUS_FILE_NAME: &str = "status.json";
+const LAST_FETCH_FILE_NAME: &str = "last_fetch.json";
+const ARCHIVE_FILE_NAME: &str = "archive.json";
+const NOTES_FILE_NAME: &str = "notes.json";
+const GOOGLE_TOKEN_FILE_NAME: &str = "google_token.json";
+const OUTLOOK_TOKEN_FILE_NAME: &str = "outlook_token.json";
+const PROFILES_DIR_NAME: &str = "profiles";
 const MAX_LOG_LINES: usize = 2000;
 
+/// Environment variable overriding the data directory.
+const DATA_DIR_VAR: &str = "CFR_DATA_DIR";
+
+/// Exit code if the data directory or its files are unusable (EX_IOERR).
+const IO_EXIT_CODE: i32 = 74;
+
 /// Returns the path to the contests.json
+///
+/// Terminates with IO_EXIT_CODE if the data directory can not be created.
 pub fn contest_path() -> &'static PathBuf {
-    CONTEST_FILE_PATH.get_or_init(build_contest_path) 
+    CONTEST_FILE_PATH.get_or_init(|| build_contest_path().unwrap_or_else(|e| exit_io_error(e)))
 }
 
 /// Creates the folder "codeforces-reminder" in
@@ -20,19 +43,14 @@ pub fn contest_path() -> &'static PathBuf {
 ///
 /// Additionally tries to add the file contests.json as an empty json if
 /// it does not exist, but does not panic if it fails.
-fn build_contest_path() -> PathBuf {
-    let data_dir = dirs::data_local_dir()
-            .expect("OS does not provide data dir")
-            .join("codeforces-reminder");
-
-    std::fs::create_dir_all(&data_dir).expect("Failed to create codeforces-reminder dir");
-
-    let contest_path = data_dir.join(CONTEST_FILE_NAME);
+fn build_contest_path() -> io::Result<PathBuf> {
+    let contest_path = data_dir()?.join(CONTEST_FILE_NAME);
 
     if !contest_path.exists() {
         match File::create(&contest_path) {
             Ok(mut file) => {
-                if let Err(e) = file.write_all(b"[]") {
+                let mode = crate::config::config().file_mode;
+                if let Err(e) = crate::local::set_file_mode(&file, mode).and_then(|_| file.write_all(b"[]")) {
                     let _ = fs::remove_file(&contest_path);
                     crate::log_error(&format!("Could not initialize empty JSON file. {}", e));
                 }
@@ -43,14 +61,111 @@ fn build_contest_path() -> PathBuf {
         }
     }
 
-    contest_path
+    Ok(contest_path)
+}
+
+/// Selects the profile whose files are used from now on.
+///
+/// Has to be called before any path is resolved, later calls are ignored.
+pub fn set_profile(name: String) {
+    let _ = PROFILE.set(name);
+}
+
+/// Returns the selected profile, DEFAULT_PROFILE if none was set.
+pub fn profile() -> &'static str {
+    PROFILE.get().map_or(DEFAULT_PROFILE, String::as_str)
+}
+
+/// Returns the path to the config.json.
+///
+/// The file is optional and never created by the program.
+/// It is shared by all profiles, so it always lives in the data directory.
+pub fn config_path() -> &'static PathBuf {
+    CONFIG_FILE_PATH.get_or_init(|| {
+        base_dir()
+            .map(|dir| dir.join(CONFIG_FILE_NAME))
+            .unwrap_or_else(|e| exit_io_error(e))
+    })
+}
+
+/// Returns the path to the history.jsonl.
+///
+/// Terminates with IO_EXIT_CODE if the data directory can not be created.
+pub fn history_path() -> PathBuf {
+    data_dir()
+        .map(|dir| dir.join(HISTORY_FILE_NAME))
+        .unwrap_or_else(|e| exit_io_error(e))
+}
+
+/// Returns the path to the status.json.
+///
+/// Terminates with IO_EXIT_CODE if the data directory can not be created.
+pub fn status_path() -> PathBuf {
+    data_dir()
+        .map(|dir| dir.join(STATUS_FILE_NAME))
+        .unwrap_or_else(|e| exit_io_error(e))
+}
+
+/// Returns the path to the last_fetch.json.
+///
+/// Terminates with IO_EXIT_CODE if the data directory can not be created.
+pub fn last_fetch_path() -> PathBuf {
+    data_dir()
+        .map(|dir| dir.join(LAST_FETCH_FILE_NAME))
+        .unwrap_or_else(|e| exit_io_error(e))
+}
+
+/// Returns the path to the archive.json.
+///
+/// Terminates with IO_EXIT_CODE if the data directory can not be created.
+pub fn archive_path() -> PathBuf {
+    data_dir()
+        .map(|dir| dir.join(ARCHIVE_FILE_NAME))
+        .unwrap_or_else(|e| exit_io_error(e))
+}
+
+/// Returns the path to the notes.json.
+///
+/// The file is optional and never created by the program.
+/// Terminates with IO_EXIT_CODE if the data directory can not be created.
+pub fn notes_path() -> PathBuf {
+    data_dir()
+        .map(|dir| dir.join(NOTES_FILE_NAME))
+        .unwrap_or_else(|e| exit_io_error(e))
+}
+
+/// Returns the path to the google_token.json with the OAuth tokens
+/// of the google-calendar notifier.
+///
+/// Terminates with IO_EXIT_CODE if the data directory can not be created.
+pub fn google_token_path() -> PathBuf {
+    data_dir()
+        .map(|dir| dir.join(GOOGLE_TOKEN_FILE_NAME))
+        .unwrap_or_else(|e| exit_io_error(e))
+}
+
+/// Returns the path to the outlook_token.json with the OAuth tokens
+/// of the outlook notifier.
+///
+/// Terminates with IO_EXIT_CODE if the data directory can not be created.
+pub fn outlook_token_path() -> PathBuf {
+    data_dir()
+        .map(|dir| dir.join(OUTLOOK_TOKEN_FILE_NAME))
+        .unwrap_or_else(|e| exit_io_error(e))
 }
 
 /// Returns the current open handle to the log file.
 ///
 /// Log file is saved in the same directory as contests.json.
-pub fn log_file() -> &'static File {
-    LOG_FILE.get_or_init(get_log_handle)
+/// The handle is behind a Mutex, so lines written concurrently never interleave.
+/// Terminates with IO_EXIT_CODE if it can not be opened.
+pub fn log_file() -> &'static Mutex<File> {
+    LOG_FILE.get_or_init(|| Mutex::new(get_log_handle().unwrap_or_else(|e| exit_io_error(e))))
+}
+
+/// Returns the handle to the log file if it was opened, without opening it.
+pub fn open_log_file() -> Option<&'static Mutex<File>> {
+    LOG_FILE.get()
 }
 
 /// Creates the folder "codeforces-reminder" in
@@ -58,14 +173,8 @@ pub fn log_file() -> &'static File {
 ///
 /// Creates (or opens) the file error_log in said folder and returns the
 /// file handle. 
-fn get_log_handle() -> File {
-    let state_dir = dirs::data_local_dir()
-            .expect("OS does not provide data dir")
-            .join("codeforces-reminder");
-
-    std::fs::create_dir_all(&state_dir).expect("Failed to create codeforces-reminder dir");
-
-    let log_path = state_dir.join(LOG_FILE_NAME);
+fn get_log_handle() -> io::Result<File> {
+    let log_path = data_dir()?.join(LOG_FILE_NAME);
    
     if log_path.exists() {
         // Check line number, delete logs if number reaches certain threshold
@@ -82,7 +191,8 @@ fn get_log_handle() -> File {
                     .write(true)
                     .truncate(true)
                     .open(&log_path)
-                    .expect("Failed to truncate log file");
+                    .and_then(with_log_mode)
+                    .map_err(|e| with_path(e, "truncate log file", &log_path));
             }
         }
     }   
@@ -91,5 +201,132 @@ fn get_log_handle() -> File {
         .create(true)
         .append(true)
         .open(&log_path)
-        .expect("Could not open or create log file")   
+        .and_then(with_log_mode)
+        .map_err(|e| with_path(e, "open or create log file", &log_path))
+}
+
+/// Sets file_mode on the log file. Uses the default mode if the config is
+/// not loaded yet, as loading it may log and would open the log again.
+fn with_log_mode(file: File) -> io::Result<File> {
+    let mode = crate::config::loaded_config().map_or(crate::config::DEFAULT_FILE_MODE, |config| config.file_mode);
+    crate::local::set_file_mode(&file, mode)?;
+    Ok(file)
+}
+
+/// Returns the path to the error_log.txt without opening it.
+///
+/// Terminates with IO_EXIT_CODE if the data directory can not be created.
+pub fn log_path() -> PathBuf {
+    data_dir()
+        .map(|dir| dir.join(LOG_FILE_NAME))
+        .unwrap_or_else(|e| exit_io_error(e))
+}
+
+/// Creates the directory of the selected profile if it does not exist
+/// and returns its path.
+///
+/// The default profile uses the data directory itself, every other
+/// profile the folder profiles/<name> in it.
+fn data_dir() -> io::Result<PathBuf> {
+    let base_dir = base_dir()?;
+
+    if profile() == DEFAULT_PROFILE { return Ok(base_dir) }
+
+    let profile_dir = base_dir.join(PROFILES_DIR_NAME).join(profile());
+    create_dir(&profile_dir, "profile directory")?;
+
+    Ok(profile_dir)
+}
+
+/// Creates the data directory if it does not exist
+/// and returns its path.
+fn base_dir() -> io::Result<PathBuf> {
+    let data_dir = base_dir_path()?;
+
+    create_dir(&data_dir, "data directory")?;
+
+    Ok(data_dir)
+}
+
+/// Returns the path of the data directory, without creating it.
+///
+/// Tests use a directory of their own, as if CFR_DATA_DIR pointed there,
+/// so they never touch the data directory of the user.
+pub fn base_dir_path() -> io::Result<PathBuf> {
+    if cfg!(test) {
+        return data_dir_from(Some(std::env::temp_dir().join("codeforces-reminder-test").into()), None)
+    }
+
+    data_dir_from(std::env::var_os(DATA_DIR_VAR), dirs::data_local_dir())
+}
+
+/// Returns env, the value of CFR_DATA_DIR, if it is set and not empty,
+/// otherwise the folder "codeforces-reminder" in default, the path
+/// provided by data_local_dir.
+pub fn data_dir_from(env: Option<OsString>, default: Option<PathBuf>) -> io::Result<PathBuf> {
+    match env {
+        Some(dir) if !dir.is_empty() => Ok(PathBuf::from(dir)),
+        _ => Ok(default
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "OS does not provide data dir"))?
+            .join("codeforces-reminder")),
+    }
+}
+
+/// Returns where contests.json of the profile is, creating neither
+/// the file nor its directory, e.g. for read-only checks.
+pub fn contest_path_read_only() -> io::Result<PathBuf> {
+    let base_dir = base_dir_path()?;
+
+    if profile() == DEFAULT_PROFILE { return Ok(base_dir.join(CONTEST_FILE_NAME)) }
+
+    Ok(base_dir.join(PROFILES_DIR_NAME).join(profile()).join(CONTEST_FILE_NAME))
+}
+
+/// Creates dir and its parents if they do not exist, naming
+/// the kind of directory and its path on failure.
+fn create_dir(dir: &std::path::Path, kind: &str) -> io::Result<()> {
+    fs::create_dir_all(dir).map_err(|e| with_path(e, &format!("create {}", kind), dir))
+}
+
+/// Adds what was attempted on which path to an IO error.
+fn with_path(e: io::Error, action: &str, path: &std::path::Path) -> io::Error {
+    io::Error::new(e.kind(), format!("Failed to {} {}: {}", action, path.display(), e))
+}
+
+/// Reports an unusable data directory and terminates.
+///
+/// Prints to stderr, as the log file lives in the same directory
+/// and can not be relied upon.
+fn exit_io_error(e: io::Error) -> ! {
+    eprintln!("codeforces-reminder: {}", e);
+    std::process::exit(IO_EXIT_CODE);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uncreatable_directory_names_its_path() {
+        // A directory can not be created below a regular file.
+        let file = std::env::temp_dir().join(format!("codeforces-reminder-paths-{}", std::process::id()));
+        fs::write(&file, "").unwrap();
+        let dir = file.join("data");
+
+        let e = create_dir(&dir, "data directory").unwrap_err();
+        fs::remove_file(&file).unwrap();
+
+        assert!(e.to_string().starts_with(&format!("Failed to create data directory {}: ", dir.display())));
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn data_dir_is_the_variable_if_set() {
+        let default = Some(PathBuf::from("/home/user/.local/share"));
+
+        assert_eq!(data_dir_from(Some("/srv/cfr".into()), default.clone()).unwrap(), PathBuf::from("/srv/cfr"));
+        assert_eq!(data_dir_from(Some("".into()), default.clone()).unwrap(), PathBuf::from("/home/user/.local/share/codeforces-reminder"));
+        assert_eq!(data_dir_from(None, default).unwrap(), PathBuf::from("/home/user/.local/share/codeforces-reminder"));
+        assert_eq!(data_dir_from(None, None).unwrap_err().kind(), io::ErrorKind::NotFound);
+    }
 }
@@ -0,0 +1,1173 @@
+use crate::config::config;
+use crate::conflict::conflicts;
+use crate::contest::{format_duration, format_local_time, Contest, Phase};
+use crate::local::{log_error, log_info, log_warn};
+use crate::notes::note_for;
+use crate::notify::{Notifier, NotifyError};
+
+use regex::Regex;
+use chrono::{Datelike, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Timelike};
+use std::cell::RefCell;
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Once;
+use std::process::{Command, Output};
+
+/// Lead time used if the config does not list any.
+const DEFAULT_LEAD_TIME_SECONDS: i64 = 1800;
+
+/// Set once osascript reports that automation of the reminders app was
+/// denied, so the remaining reminders of the run are not attempted.
+static AUTOMATION_DENIED: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    /// Lead times replacing lead_times_seconds, see with_lead_times.
+    static LEAD_TIMES: RefCell<Option<Vec<i64>>> = const { RefCell::new(None) };
+}
+
+/// Fields that can be listed in body_fields.
+const BODY_FIELDS: [&str; 11] =
+    ["name", "description", "note", "difficulty", "prep", "url", "time", "countdown", "duration", "type", "conflicts"];
+
+/// Logs the unknown fields in body_fields once per run.
+static UNKNOWN_BODY_FIELDS_WARNING: Once = Once::new();
+
+/// How often osascript is run again after a transient failure.
+const OSASCRIPT_RETRIES: u32 = 2;
+
+/// Wait before running osascript again, e.g. for the app to finish launching.
+const OSASCRIPT_RETRY_DELAY: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Runs the AppleScript of the reminder functions.
+///
+/// Every reminder function takes a runner, so the reminder logic can be
+/// driven by something else than osascript, e.g. to record the scripts.
+pub trait CommandRunner {
+    fn run(&self, apple_script: &str) -> io::Result<Output>;
+}
+
+/// Runs AppleScript with osascript -e.
+pub struct Osascript;
+
+impl CommandRunner for Osascript {
+    fn run(&self, apple_script: &str) -> io::Result<Output> {
+        Command::new("osascript")
+            .arg("-e")
+            .arg(apple_script)
+            .output()
+    }
+}
+
+/// Notifier creating macOS Reminders with the AppleScript
+/// run by its runner, the default backend.
+pub struct Reminders<'a> {
+    runner: &'a dyn CommandRunner,
+}
+
+impl<'a> Reminders<'a> {
+    pub fn new(runner: &'a dyn CommandRunner) -> Self {
+        Reminders { runner }
+    }
+}
+
+impl Notifier for Reminders<'_> {
+    fn name(&self) -> &'static str {
+        "Reminders"
+    }
+
+    fn notify(&self, contest: &mut Contest) -> Result<(), NotifyError> {
+        reported(create_reminder(self.runner, contest))
+    }
+
+    /// Shows the message as Notification Center banner, not as reminder.
+    fn message(&self, title: &str, text: &str) -> Result<(), NotifyError> {
+        reported(display_notification(self.runner, title, "", text))
+    }
+
+    fn update(&self, contest: &mut Contest) -> Result<(), NotifyError> {
+        reported(update_reminder(self.runner, contest))
+    }
+
+    fn withdraw(&self, contest: &Contest) -> Result<(), NotifyError> {
+        reported(delete_reminder(self.runner, contest))
+    }
+}
+
+/// The reminder functions log their failures themselves.
+fn reported(success: bool) -> Result<(), NotifyError> {
+    if success { Ok(()) } else { Err(NotifyError::logged()) }
+}
+
+/// Creates the reminders of a contest using osascript run as a command.
+///
+/// Reminders are created in the list chosen by reminder_list, which is
+/// created if it does not exist.
+/// One reminder is created per lead time in the config, or a single
+/// parent reminder with a subtask per lead time if group_lead_times is set.
+/// Contests requiring registration get another reminder before it closes,
+/// see registration_due.
+/// The ids of the new reminders are saved in contest.reminder_ids,
+/// so they can be found again even if the user renames them.
+///
+/// This function ignores contests without a starting time 
+/// (field start_time_seconds in struct Contest).
+///
+/// Will not terminate if it fails to set a reminder, but will log the failure
+/// and return false. Returns false without trying once automation of the
+/// reminders app was denied in this run.
+pub fn create_reminder(runner: &dyn CommandRunner, contest: &mut Contest) -> bool {
+    if AUTOMATION_DENIED.load(Ordering::Relaxed) { return false }
+
+    let Some(apple_script) = create_script(contest) else {
+        log_error(&format!("Contest without start time: {}, {}", contest.id, contest.name));
+        return false
+    };
+
+    let Some(output) = run_osascript(runner, &apple_script, "add", &describe(contest)) else {
+        return false
+    };
+
+    contest.reminder_ids = parse_reminder_ids(&output);
+    true
+}
+
+/// Updates name, body and due date of the reminder of a changed contest.
+///
+/// With several lead times (or reminders, e.g. the registration reminder) the
+/// reminders are deleted and created again, as the saved ids do not tell which
+/// reminder belongs to which lead time.
+///
+/// Returns false and logs the failure if the reminder could not be updated.
+pub fn update_reminder(runner: &dyn CommandRunner, contest: &mut Contest) -> bool {
+    let Some(start) = contest.start_time_seconds else {
+        log_error(&format!("Contest without start time: {}, {}", contest.id, contest.name));
+        return false
+    };
+
+    let lead_times = contest_lead_times(contest);
+    if lead_times.len() > 1 || contest.reminder_ids.len() > 1 || registration_due(contest).is_some() {
+        return delete_reminder(runner, contest) && create_reminder(runner, contest);
+    }
+
+    let due_date = apple_script_date("dueDate", due_time(contest, start, lead_times[0]));
+
+    let apple_script = format!(
+        r#"
+        with timeout of 30 seconds 
+            {}
+            tell application "{}"
+                repeat with contestReminder in {}
+                    set name of contestReminder to "{}"
+                    set body of contestReminder to "{}"
+                    set due date of contestReminder to dueDate
+                    set flagged of contestReminder to {}
+                end repeat
+            end tell
+        end timeout"#, due_date, app(), reminder_selector(contest),
+            escape(&reminder_name(contest)), escape(&reminder_body(contest)), is_flagged(contest));
+
+    run_osascript(runner, &apple_script, "update", &describe(contest)).is_some()
+}
+
+/// Builds the AppleScript creating the reminders of the contest and
+/// printing their ids, one per line.
+///
+/// Returns None for contests without a start time.
+fn create_script(contest: &Contest) -> Option<String> {
+    let start = contest.start_time_seconds?;
+    let lead_times = contest_lead_times(contest);
+
+    let name = escape(&reminder_name(contest));
+    let body = escape(&reminder_body(contest));
+    let flagged = if is_flagged(contest) { ", flagged:true" } else { "" };
+
+    let mut dates = Vec::new();
+    let mut commands = Vec::new();
+
+    let at = match reminder_list(contest) {
+        Some(list) => {
+            let list = escape(list);
+            commands.push(format!(
+                r#"if not (exists list "{list}") then make new list with properties {{name:"{list}"}}
+                set targetList to list "{list}""#));
+            " at end of targetList"
+        }
+        None => "",
+    };
+
+    for (i, lead_time) in lead_times.iter().enumerate() {
+        dates.push(apple_script_date(&format!("dueDate{}", i), due_time(contest, start, *lead_time)));
+    }
+
+    let mut ids = if config().group_lead_times && lead_times.len() > 1 {
+        commands.push(format!(
+            r#"set parentReminder to make new reminder{} with properties {{name:"{}", body:"{}"{}}}
+                set due date of parentReminder to dueDate0"#, at, name, body, flagged));
+
+        for (i, lead_time) in lead_times.iter().enumerate() {
+            commands.push(format!(
+                r#"set subtask{i} to make new reminder with properties {{name:"Starts in {}"}} at end of reminders of parentReminder
+                set due date of subtask{i} to dueDate{i}"#, format_duration(*lead_time)));
+        }
+
+        "id of parentReminder".to_string()
+    } else {
+        for i in 0..lead_times.len() {
+            commands.push(format!(
+                r#"set newReminder{i} to make new reminder{} with properties {{name:"{}", body:"{}"{}}}
+                set due date of newReminder{i} to dueDate{i}"#, at, name, body, flagged));
+        }
+
+        (0..lead_times.len())
+            .map(|i| format!("(id of newReminder{})", i))
+            .collect::<Vec<_>>()
+            .join(" & linefeed & ")
+    };
+
+    if let Some(due) = registration_due(contest) {
+        dates.push(apple_script_date("registrationDate", due));
+        commands.push(format!(
+            r#"set registrationReminder to make new reminder{} with properties {{name:"{}", body:"{}"{}}}
+                set due date of registrationReminder to registrationDate"#,
+            at, escape(&registration_name(contest)), escape(&contest.url()), flagged));
+        ids.push_str(" & linefeed & (id of registrationReminder)");
+    }
+
+    Some(format!(
+        r#"
+        with timeout of 30 seconds 
+            {}
+            tell application "{}"
+                {}
+                return {}
+            end tell
+        end timeout"#, dates.join("\n            "), app(), commands.join("\n                "), ids))
+}
+
+/// Lead times of the contest, earliest reminder first.
+/// The snoozed lead time replaces the ones from the config.
+pub fn contest_lead_times(contest: &Contest) -> Vec<i64> {
+    match contest.lead_time_override {
+        Some(lead_time) => vec![lead_time],
+        None => lead_times(),
+    }
+}
+
+/// Returns when the reminder lead_time seconds before start is due,
+/// moved to the end of the quiet hours if it falls into them.
+///
+/// Reminders that would move past the start of the contest keep their time.
+pub fn due_time(contest: &Contest, start: i64, lead_time: i64) -> i64 {
+    let due = start - lead_time;
+
+    let Some((quiet_start, quiet_end)) = quiet_hours() else { return due };
+    let Some(shifted) = shift_out_of_quiet_hours(due, quiet_start, quiet_end) else { return due };
+
+    if shifted > start {
+        log_warn(&format!("Reminder of {}, id: {} is in quiet hours, but moving it would pass the start.",
+            contest.name, contest.id));
+        return due;
+    }
+
+    log_info(&format!("Moved reminder of {}, id: {} out of quiet hours to {}",
+        contest.name, contest.id, crate::contest::format_local_time(shifted)));
+    shifted
+}
+
+/// Parses the quiet hours of the config.
+/// Returns None if there are none, or logs and returns None if they are invalid.
+fn quiet_hours() -> Option<(NaiveTime, NaiveTime)> {
+    let quiet_hours = config().quiet_hours.as_ref()?;
+
+    let parse = |time: &str| NaiveTime::parse_from_str(time, "%H:%M");
+    match (parse(&quiet_hours.start), parse(&quiet_hours.end)) {
+        (Ok(start), Ok(end)) => Some((start, end)),
+        _ => {
+            log_error(&format!("Invalid quiet_hours {} to {}, ignoring them.", quiet_hours.start, quiet_hours.end));
+            None
+        }
+    }
+}
+
+/// Returns the next end of the quiet hours if the local time of `seconds`
+/// is within them, None if it is outside or the window is empty.
+///
+/// The window crosses midnight if it ends before it starts.
+fn shift_out_of_quiet_hours(seconds: i64, start: NaiveTime, end: NaiveTime) -> Option<i64> {
+    let time = Local.timestamp_opt(seconds, 0).single()?.naive_local();
+    let end_day = quiet_end_day(time, start, end)?;
+
+    // Falls back to the end of the DST gap if the end does not exist that day.
+    let shifted: NaiveDateTime = end_day.and_time(end);
+    Local.from_local_datetime(&shifted).earliest()
+        .or_else(|| Local.from_local_datetime(&(shifted + Duration::hours(1))).earliest())
+        .map(|shifted| shifted.timestamp())
+}
+
+/// Returns the day the quiet hours containing time end on,
+/// None if time is not within quiet hours.
+fn quiet_end_day(time: NaiveDateTime, start: NaiveTime, end: NaiveTime) -> Option<NaiveDate> {
+    let (day, time_of_day) = (time.date(), time.time());
+
+    if start < end {
+        (start <= time_of_day && time_of_day < end).then_some(day)
+    } else if start > end {
+        if time_of_day >= start {
+            day.succ_opt()
+        } else if time_of_day < end {
+            Some(day)
+        } else {
+            None
+        }
+    } else {
+        None
+    }
+}
+
+/// Lead times from the config or with_lead_times, earliest reminder first.
+/// Falls back to the default if the config lists none.
+fn lead_times() -> Vec<i64> {
+    let mut lead_times = LEAD_TIMES
+        .with(|lead_times| lead_times.borrow().clone())
+        .unwrap_or_else(|| config().lead_times_seconds.clone());
+
+    if lead_times.is_empty() {
+        lead_times.push(DEFAULT_LEAD_TIME_SECONDS);
+    }
+
+    lead_times.sort_unstable_by(|a, b| b.cmp(a));
+    lead_times.dedup();
+    lead_times
+}
+
+/// Runs f with lead_times replacing lead_times_seconds from the config,
+/// e.g. for the lead times of one of several notifiers. None keeps the config.
+pub fn with_lead_times<T>(lead_times: Option<&[i64]>, f: impl FnOnce() -> T) -> T {
+    let previous = LEAD_TIMES.with(|current| current.replace(lead_times.map(<[i64]>::to_vec)));
+    let result = f();
+    LEAD_TIMES.with(|current| *current.borrow_mut() = previous);
+    result
+}
+
+/// Deletes the reminder of a contest that is no longer upcoming.
+///
+/// Returns false and logs the failure if the reminder could not be deleted.
+pub fn delete_reminder(runner: &dyn CommandRunner, contest: &Contest) -> bool {
+    let apple_script = format!(
+        r#"
+        with timeout of 30 seconds 
+            tell application "{}"
+                delete {}
+            end tell
+        end timeout"#, app(), reminder_selector(contest));
+
+    run_osascript(runner, &apple_script, "delete", &describe(contest)).is_some()
+}
+
+/// Creates the summary reminder of all contests starting on date,
+/// due at daily_summary_time.
+///
+/// Returns false and logs the failure if the reminder could not be created.
+pub fn create_summary_reminder(runner: &dyn CommandRunner, date: NaiveDate, contests: &[&Contest]) -> bool {
+    let time = NaiveTime::parse_from_str(&config().daily_summary_time, "%H:%M").unwrap_or_else(|e| {
+        log_error(&format!("Invalid daily_summary_time {}, using 08:00. {}", config().daily_summary_time, e));
+        NaiveTime::from_hms_opt(8, 0, 0).unwrap()
+    });
+
+    let Some(due) = Local.from_local_datetime(&date.and_time(time)).earliest() else {
+        log_error(&format!("Summary time does not exist on {}", date));
+        return false
+    };
+
+    let names = contests.iter().map(|contest| contest.name.as_str()).collect::<Vec<_>>();
+    let body = contests
+        .iter()
+        .map(|contest| {
+            let start = contest.start_time_seconds.unwrap_or_default();
+            match Local.timestamp_opt(start, 0).earliest() {
+                Some(time) => format!("{} {}, id: {}", time.format("%H:%M"), contest.name, contest.id),
+                None => format!("{}, id: {}", contest.name, contest.id),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let apple_script = format!(
+        r#"
+        with timeout of 30 seconds 
+            {}
+            tell application "{}"
+                set newReminder to make new reminder with properties {{name:"{}", body:"{}"}}
+                set due date of newReminder to dueDate
+            end tell
+        end timeout"#, apple_script_date("dueDate", due.timestamp()), app(),
+            escape(&format!("Contests today: {}{}", names.join(", "), summary_suffix(date))), escape(&body));
+
+    run_osascript(runner, &apple_script, "add", &format!("contests on {}", date)).is_some()
+}
+
+/// Deletes the summary reminder of date, if there is one.
+///
+/// Returns false and logs the failure if the reminder could not be deleted.
+pub fn delete_summary_reminder(runner: &dyn CommandRunner, date: NaiveDate) -> bool {
+    let apple_script = format!(
+        r#"
+        with timeout of 30 seconds 
+            tell application "{}"
+                delete (every reminder whose name ends with "{}")
+            end tell
+        end timeout"#, app(), summary_suffix(date));
+
+    run_osascript(runner, &apple_script, "delete", &format!("contests on {}", date)).is_some()
+}
+
+/// Shows a Notification Center banner listing the contests whose
+/// reminders were created by this run, with their local start times.
+///
+/// Failing to show it is logged, the banner is not retried.
+pub fn display_banner(runner: &dyn CommandRunner, contests: &[&Contest]) {
+    let plural = if contests.len() == 1 { "" } else { "s" };
+    let lines = contests
+        .iter()
+        .map(|contest| match contest.start_time_seconds {
+            Some(start) => format!("{} ({})", contest.name, format_local_time(start)),
+            None => contest.name.clone(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    display_notification(runner, "codeforces-reminder", &format!("Scheduled {} new contest{}", contests.len(), plural), &lines);
+}
+
+/// Shows a Notification Center banner with display notification.
+///
+/// Logs and returns false if it can not be shown.
+pub fn display_notification(runner: &dyn CommandRunner, title: &str, subtitle: &str, text: &str) -> bool {
+    let apple_script = format!(r#"display notification "{}" with title "{}" subtitle "{}""#,
+        escape(text), escape(title), escape(subtitle));
+
+    match runner.run(&apple_script) {
+        Ok(output) if output.status.success() => true,
+        Ok(output) => {
+            log_error(&format!("Failed to display notification {}. Error: {}", title, String::from_utf8_lossy(&output.stderr).trim()));
+            false
+        }
+        Err(e) => {
+            log_error(&format!("Failed to display notification {}, could not run osascript. Error: {}", title, e));
+            false
+        }
+    }
+}
+
+/// Title suffix used to find the summary reminder of a day again.
+fn summary_suffix(date: NaiveDate) -> String {
+    format!(", day: {}", date.format("%Y-%m-%d"))
+}
+
+/// Describes the contest in log messages.
+fn describe(contest: &Contest) -> String {
+    format!("Contest {}, id: {}", contest.name, contest.id)
+}
+
+/// Title of the reminder, prefixed with "LIVE: " for running contests
+/// and the short label if show_short_label is set.
+/// The id suffix is used to find reminders created before their id was saved.
+///
+/// The name is shortened with an ellipsis if the title would exceed
+/// max_title_length, the id suffix is always kept whole.
+pub fn reminder_name(contest: &Contest) -> String {
+    let live = if contest.phase == Phase::Coding { "LIVE: " } else { "" };
+    let label = if config().show_short_label { format!("[{}] ", contest.short_label()) } else { String::new() };
+    let name = format!("{}{}{}", live, label, contest.name);
+    let suffix = format!(", id: {}", contest.id);
+
+    match config().max_title_length {
+        Some(max) => truncate(&name, max.saturating_sub(suffix.chars().count())) + &suffix,
+        None => name + &suffix,
+    }
+}
+
+/// Returns the list of the first rule in reminder_lists matching the contest,
+/// or default_list if none matches. None means the default list of the app.
+fn reminder_list(contest: &Contest) -> Option<&'static str> {
+    let config = config();
+
+    config.reminder_lists
+        .iter()
+        .find(|rule| rule_matches(contest, rule.contest_type.as_deref(), rule.pattern.as_deref(), "list"))
+        .map(|rule| rule.list.as_str())
+        .or(config.default_list.as_deref())
+}
+
+/// Returns true if the contest type equals contest_type (ignoring case)
+/// and the name matches the regex pattern, leaving out either matches
+/// every contest.
+/// Invalid patterns are logged as invalid `kind` pattern and never match.
+pub fn rule_matches(contest: &Contest, contest_type: Option<&str>, pattern: Option<&str>, kind: &str) -> bool {
+    let type_matches = contest_type.is_none_or(|rule_type| {
+        contest.contest_type.as_ref().is_some_and(|contest_type| rule_type.eq_ignore_ascii_case(contest_type))
+    });
+
+    type_matches && pattern.is_none_or(|pattern| match Regex::new(pattern) {
+        Ok(regex) => regex.is_match(&contest.name),
+        Err(e) => {
+            log_error(&format!("Ignoring invalid {} pattern {}. {}", kind, pattern, e));
+            false
+        }
+    })
+}
+
+/// Returns true if the reminder of the contest should be flagged, i.e.
+/// its type is in flag_types or its name matches one of flag_patterns.
+/// Invalid patterns are logged and ignored.
+fn is_flagged(contest: &Contest) -> bool {
+    let config = config();
+
+    let type_matches = contest.contest_type.as_ref().is_some_and(|contest_type| {
+        config.flag_types.iter().any(|flag_type| flag_type.eq_ignore_ascii_case(contest_type))
+    });
+
+    type_matches || config.flag_patterns.iter().any(|pattern| match Regex::new(pattern) {
+        Ok(regex) => regex.is_match(&contest.name),
+        Err(e) => {
+            log_error(&format!("Ignoring invalid flag pattern {}. {}", pattern, e));
+            false
+        }
+    })
+}
+
+/// Returns when the registration reminder of the contest is due,
+/// registration_buffer_seconds before its start.
+///
+/// None if registration_buffer_seconds is not set, the contest has no start
+/// time or its name matches none of registration_patterns (if there are any).
+/// Invalid patterns are logged and ignored.
+pub fn registration_due(contest: &Contest) -> Option<i64> {
+    let config = config();
+    let buffer = config.registration_buffer_seconds?;
+    let start = contest.start_time_seconds?;
+
+    let requires_registration = config.registration_patterns.is_empty()
+        || config.registration_patterns.iter().any(|pattern| match Regex::new(pattern) {
+            Ok(regex) => regex.is_match(&contest.name),
+            Err(e) => {
+                log_error(&format!("Ignoring invalid registration pattern {}. {}", pattern, e));
+                false
+            }
+        });
+
+    requires_registration.then_some(start - buffer)
+}
+
+/// Title of the registration reminder, with the same id suffix as reminder_name.
+pub fn registration_name(contest: &Contest) -> String {
+    format!("Registration for {} closes soon, id: {}", contest.name, contest.id)
+}
+
+/// Returns true if reminder_name shortens the contest name.
+fn title_truncated(contest: &Contest) -> bool {
+    !reminder_name(contest).contains(&contest.name)
+}
+
+/// Shortens text to at most max_chars characters, ending with "…" if cut.
+fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    if max_chars == 0 {
+        return String::new();
+    }
+
+    text.chars().take(max_chars - 1).chain(std::iter::once('…')).collect()
+}
+
+/// AppleScript list of the reminders belonging to the contest.
+///
+/// Uses the saved reminder ids, or falls back to matching the
+/// name suffix for contests saved without them. Those all come from the
+/// Codeforces API, ids were saved before other sources were added, so
+/// contests of other sources without ids select nothing rather than a
+/// reminder of a contest with the same id.
+fn reminder_selector(contest: &Contest) -> String {
+    if contest.reminder_ids.is_empty() {
+        if !contest.is_from_codeforces_api() {
+            return r#"(every reminder whose id is "")"#.to_string();
+        }
+        return format!(r#"(every reminder whose name ends with ", id: {}")"#, contest.id);
+    }
+
+    let ids = contest.reminder_ids
+        .iter()
+        .map(|id| format!(r#"id is "{}""#, escape(id)))
+        .collect::<Vec<_>>()
+        .join(" or ");
+
+    format!("(every reminder whose {})", ids)
+}
+
+/// Name of the application receiving the AppleScript, escaped.
+fn app() -> String {
+    escape(&config().reminders_app)
+}
+
+/// Escapes backslashes and double quotes for use inside an AppleScript string.
+pub fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Extracts the reminder ids printed by osascript, one per line,
+/// e.g. "x-apple-reminder://0A1B2C3D-...".
+fn parse_reminder_ids(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Runs the AppleScript and returns what it printed.
+///
+/// Retries up to OSASCRIPT_RETRIES times, logging each retry, if osascript
+/// can not be spawned or fails transiently (see is_transient).
+/// Logs and returns None if osascript can not be run or fails.
+fn run_osascript(runner: &dyn CommandRunner, apple_script: &str, action: &str, subject: &str) -> Option<String> {
+    let mut attempt = 0;
+
+    let output = loop {
+        let retry = match runner.run(apple_script) {
+            Ok(output) if output.status.success() || !is_transient(&String::from_utf8_lossy(&output.stderr)) => {
+                break output
+            }
+            Ok(output) => format!("Error: {}", String::from_utf8_lossy(&output.stderr).trim()),
+            Err(e) => format!("Failed to run osascript. Error: {}", e),
+        };
+
+        if attempt == OSASCRIPT_RETRIES {
+            log_error(&format!("Failed to {} reminder for {} after {} retries. {}", action, subject, attempt, retry));
+            return None;
+        }
+
+        attempt += 1;
+        log_warn(&format!("Retrying to {} reminder for {} ({}/{}). {}", action, subject, attempt, OSASCRIPT_RETRIES, retry));
+        std::thread::sleep(OSASCRIPT_RETRY_DELAY);
+    };
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+
+        if is_not_authorized(&stderr) {
+            if !AUTOMATION_DENIED.swap(true, Ordering::Relaxed) {
+                log_error(&format!("Not allowed to control {}. Grant access in System Settings > \
+                    Privacy & Security > Automation and run again. Error: {}", config().reminders_app, stderr.trim()));
+            }
+            return None;
+        }
+
+        log_error(&format!("Failed to {} reminder for {}. Error: {}", action, subject, stderr.trim()));
+        return None;
+    }
+
+    Some(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Returns true if osascript failed before the script ran because the app is
+/// still launching or syncing: procNotFound (-600) or connectionInvalid (-609).
+///
+/// Timeouts are not transient, the script may have run and retrying
+/// could create a reminder twice.
+fn is_transient(stderr: &str) -> bool {
+    !is_not_authorized(stderr) && (stderr.contains("(-600)") || stderr.contains("(-609)"))
+}
+
+/// Returns true if osascript failed because automation of the app was denied
+/// (errAEEventNotPermitted, -1743).
+fn is_not_authorized(stderr: &str) -> bool {
+    stderr.contains("Not authorized") || stderr.contains("(-1743)")
+}
+
+/// Builds the reminder body from the fields in body_fields, in their order.
+///
+/// Fields without a value for the contest are left out. Unknown fields
+/// are logged at WARN once per run and skipped.
+pub fn reminder_body(contest: &Contest) -> String {
+    let fields = &config().body_fields;
+
+    UNKNOWN_BODY_FIELDS_WARNING.call_once(|| {
+        for field in fields.iter().filter(|field| !BODY_FIELDS.contains(&field.as_str())) {
+            log_warn(&format!("Skipping unknown body field {}, expected one of {}.", field, BODY_FIELDS.join(", ")));
+        }
+    });
+
+    let now = chrono::Utc::now().timestamp();
+
+    fields
+        .iter()
+        .filter_map(|field| body_field(field, contest, now))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Renders one line (or the checklist) of the reminder body, None if the
+/// contest has no value for the field or the field is unknown.
+fn body_field(field: &str, contest: &Contest, now: i64) -> Option<String> {
+    let config = config();
+
+    match field {
+        "name" => title_truncated(contest).then(|| contest.name.clone()),
+        "description" if config.debug_description => Some(format!("{:?}", contest.description())),
+        "description" => contest.description().map(str::to_string),
+        "note" => note_for(contest).map(str::to_string),
+        "difficulty" if config.show_difficulty => {
+            contest.difficulty(&config.difficulty_tiers).map(|difficulty| format!("Difficulty: {}", difficulty))
+        }
+        "prep" if !config.prep_items.is_empty() => Some(prep_checklist(&config.prep_items)),
+        "url" => Some(contest.url()),
+        "time" => contest.start_time_seconds.map(|start| format!("Starts: {}", format_local_time(start))),
+        "countdown" => contest.start_time_seconds
+            .filter(|start| *start > now)
+            .map(|start| format!("Starts in {}", format_duration(start - now))),
+        "duration" => contest.duration_seconds.map(|duration| format!("Duration: {}", format_duration(duration))),
+        "type" => contest.contest_type.as_ref().map(|contest_type| format!("Type: {}", contest_type)),
+        "conflicts" => {
+            let events = conflicts(contest);
+            (!events.is_empty()).then(|| format!("Conflicts with: {}", events.join(", ")))
+        }
+        _ => None,
+    }
+}
+
+/// Renders the preparation items as a checklist, one "☐ item" per line.
+fn prep_checklist(items: &[String]) -> String {
+    items
+        .iter()
+        .map(|item| format!("☐ {}", item))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Builds AppleScript statements setting the variable `name` to the
+/// local time of `seconds` since Unix epoch.
+///
+/// Parsing a date string in AppleScript depends on the system locale
+/// (dd/mm vs. mm/dd), so every component is set numerically instead.
+/// The day is reset first so changing the month never overflows
+/// (e.g. current date 31st, target month with 30 days). Times chrono
+/// cannot represent leave the variable at the current date.
+pub fn apple_script_date(name: &str, seconds: i64) -> String {
+    let Some(time) = Local.timestamp_opt(seconds, 0).earliest() else {
+        return format!("set {name} to current date");
+    };
+
+    format!(
+        "set {name} to current date
+            set day of {name} to 1
+            set year of {name} to {}
+            set month of {name} to {}
+            set day of {name} to {}
+            set hours of {name} to {}
+            set minutes of {name} to {}
+            set seconds of {name} to 0",
+        time.year(), time.month(), time.day(), time.hour(), time.minute())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::tests::with_config;
+    use crate::config::Config;
+    use crate::contest::tests::contest;
+
+    /// Records the scripts instead of running them, answering each like osascript.
+    struct Recorder {
+        scripts: RefCell<Vec<String>>,
+        code: i32,
+        stdout: &'static str,
+        stderr: &'static str,
+    }
+
+    impl Recorder {
+        fn answering(stdout: &'static str) -> Self {
+            Recorder { scripts: RefCell::new(Vec::new()), code: 0, stdout, stderr: "" }
+        }
+
+        fn failing(stderr: &'static str) -> Self {
+            Recorder { scripts: RefCell::new(Vec::new()), code: 1, stdout: "", stderr }
+        }
+    }
+
+    impl CommandRunner for Recorder {
+        fn run(&self, apple_script: &str) -> io::Result<Output> {
+            self.scripts.borrow_mut().push(apple_script.to_string());
+            Ok(Output { status: exit_status(self.code), stdout: self.stdout.into(), stderr: self.stderr.into() })
+        }
+    }
+
+    fn exit_status(code: i32) -> std::process::ExitStatus {
+        #[cfg(unix)]
+        return std::os::unix::process::ExitStatusExt::from_raw(code << 8);
+        #[cfg(windows)]
+        return std::os::windows::process::ExitStatusExt::from_raw(code as u32);
+    }
+
+    #[test]
+    fn create_saves_the_returned_ids() {
+        let runner = Recorder::answering("x-apple-reminder://A\n");
+        let mut contest = contest(2100, "Codeforces Round 1020 (Div. 2)", 1_745_591_700);
+
+        assert!(with_config(Config::default(), || create_reminder(&runner, &mut contest)));
+
+        let scripts = runner.scripts.borrow();
+        assert_eq!(scripts.len(), 1);
+        assert!(scripts[0].contains(r#"make new reminder with properties {name:"Codeforces Round 1020 (Div. 2), id: 2100""#));
+        assert_eq!(contest.reminder_ids, ["x-apple-reminder://A"]);
+    }
+
+    #[test]
+    fn snoozed_lead_time_replaces_configured_ones() {
+        let start = Local.with_ymd_and_hms(2025, 4, 25, 16, 35, 0).unwrap().timestamp();
+        let runner = Recorder::answering("");
+        let mut contest = Contest {
+            reminder_ids: vec!["x-apple-reminder://A".to_string()],
+            lead_time_override: Some(300),
+            ..contest(2100, "Round", start)
+        };
+        let config = Config { lead_times_seconds: vec![3600, 600], ..Config::default() };
+
+        assert!(with_config(config, || update_reminder(&runner, &mut contest)));
+
+        let scripts = runner.scripts.borrow();
+        assert_eq!(scripts.len(), 1, "a single lead time updates the reminder in place");
+        assert!(scripts[0].contains(&apple_script_date("dueDate", start - 300)));
+        assert!(scripts[0].contains(r#"repeat with contestReminder in (every reminder whose id is "x-apple-reminder://A")"#));
+    }
+
+    #[test]
+    fn delete_selects_the_saved_ids() {
+        let runner = Recorder::answering("");
+        let contest = Contest { reminder_ids: vec!["x-apple-reminder://A".to_string()], ..contest(2100, "Round", 0) };
+
+        assert!(with_config(Config::default(), || delete_reminder(&runner, &contest)));
+
+        assert!(runner.scripts.borrow()[0].contains(r#"delete (every reminder whose id is "x-apple-reminder://A")"#));
+    }
+
+    #[test]
+    fn failed_script_keeps_the_contest() {
+        let runner = Recorder::failing("execution error: Reminders got an error: Can't make list. (-2700)");
+        let mut contest = contest(2100, "Round", 1_745_591_700);
+
+        assert!(!with_config(Config::default(), || create_reminder(&runner, &mut contest)));
+
+        assert_eq!(runner.scripts.borrow().len(), 1, "only transient errors are retried");
+        assert!(contest.reminder_ids.is_empty());
+    }
+
+    #[test]
+    fn scripts_tell_the_configured_app() {
+        let config = Config { reminders_app: r#"Reminders "Beta""#.to_string(), ..Config::default() };
+
+        let (create, app_name) = with_config(config, || {
+            (create_script(&contest(1, "Codeforces Round 1000 (Div. 2)", 1_000_000)).unwrap(), app())
+        });
+
+        assert!(create.contains(r#"tell application "Reminders \"Beta\"""#));
+        assert_eq!(app_name, r#"Reminders \"Beta\""#);
+        assert_eq!(with_config(Config::default(), app), "Reminders");
+    }
+
+    #[test]
+    fn grouped_lead_times_become_subtasks() {
+        let config = Config { lead_times_seconds: vec![600, 3600], group_lead_times: true, ..Config::default() };
+
+        let script = with_config(config, || create_script(&contest(1, "Codeforces Round 1000 (Div. 2)", 1_000_000))).unwrap();
+
+        assert_eq!(script.matches("make new reminder").count(), 3);
+        assert!(script.contains(r#"set parentReminder to make new reminder with properties {name:"Codeforces Round 1000 (Div. 2), id: 1""#));
+        assert!(script.contains(r#"set subtask0 to make new reminder with properties {name:"Starts in 1 hour"} at end of reminders of parentReminder"#));
+        assert!(script.contains(r#"set subtask1 to make new reminder with properties {name:"Starts in 10 minutes"} at end of reminders of parentReminder"#));
+        assert!(script.contains("set due date of subtask1 to dueDate1"));
+        assert!(script.contains("return id of parentReminder"));
+    }
+
+    #[test]
+    fn ungrouped_lead_times_are_separate_reminders() {
+        let config = Config { lead_times_seconds: vec![600, 3600], ..Config::default() };
+
+        let script = with_config(config, || create_script(&contest(1, "Round", 1_000_000))).unwrap();
+
+        assert!(!script.contains("parentReminder"));
+        assert!(script.contains("return (id of newReminder0) & linefeed & (id of newReminder1)"));
+    }
+
+    #[test]
+    fn truncates_with_ellipsis() {
+        assert_eq!(truncate("Codeforces", 20), "Codeforces");
+        assert_eq!(truncate("Codeforces", 10), "Codeforces");
+        assert_eq!(truncate("Codeforces", 6), "Codef…");
+        assert_eq!(truncate("Ünïcödé", 4), "Ünï…");
+        assert_eq!(truncate("Codeforces", 0), "");
+    }
+
+    #[test]
+    fn title_keeps_whole_id_suffix() {
+        let contest = contest(2100, "Educational Codeforces Round 178 (Rated for Div. 2)", 0);
+        let name = |max_title_length| {
+            with_config(Config { max_title_length, ..Config::default() }, || reminder_name(&contest))
+        };
+
+        assert_eq!(name(None), "Educational Codeforces Round 178 (Rated for Div. 2), id: 2100");
+        assert_eq!(name(Some(100)), "Educational Codeforces Round 178 (Rated for Div. 2), id: 2100");
+        assert_eq!(name(Some(20)), "Education…, id: 2100");
+        assert_eq!(name(Some(20)).chars().count(), 20);
+        assert_eq!(name(Some(5)), ", id: 2100");
+    }
+
+    #[test]
+    fn body_lists_prep_items_as_checklist() {
+        let config = Config {
+            prep_items: vec!["Register".to_string(), "Open template".to_string()],
+            body_fields: ["prep", "url"].map(String::from).to_vec(),
+            ..Config::default()
+        };
+
+        let body = with_config(config, || reminder_body(&contest(2100, "Round", 0)));
+
+        assert_eq!(body, "☐ Register\n☐ Open template\nhttps://codeforces.com/contests/2100");
+    }
+
+    #[test]
+    fn body_without_prep_items_leaves_them_out() {
+        let config = Config { body_fields: ["prep", "url"].map(String::from).to_vec(), ..Config::default() };
+
+        let body = with_config(config, || reminder_body(&contest(2100, "Round", 0)));
+
+        assert_eq!(body, "https://codeforces.com/contests/2100");
+    }
+
+    #[test]
+    fn title_starts_with_short_label_if_enabled() {
+        let config = Config { show_short_label: true, ..Config::default() };
+
+        let name = with_config(config, || reminder_name(&contest(2100, "Codeforces Global Round 28", 0)));
+
+        assert_eq!(name, "[CGR28] Codeforces Global Round 28, id: 2100");
+    }
+
+    #[test]
+    fn body_has_full_name_of_truncated_title() {
+        let contest = contest(2100, "Educational Codeforces Round 178 (Rated for Div. 2)", 0);
+        let body = |max_title_length| {
+            with_config(Config { max_title_length, ..Config::default() }, || reminder_body(&contest))
+        };
+
+        assert_eq!(body(Some(20)), contest.name);
+        assert_eq!(body(None), "");
+    }
+
+    #[test]
+    fn flags_reminders_by_type_or_pattern() {
+        let config = || Config {
+            flag_types: vec!["icpc".to_string()],
+            flag_patterns: vec!["Div. 1".to_string()],
+            ..Config::default()
+        };
+        let script = |contest: &Contest| with_config(config(), || create_script(contest)).unwrap();
+
+        let icpc = Contest { contest_type: Some("ICPC".to_string()), ..contest(1, "Codeforces Round 1000 (Div. 3)", 1_000_000) };
+        assert!(script(&icpc).contains(r#", id: 1", body:"", flagged:true}"#));
+        assert!(script(&contest(2, "Codeforces Round 999 (Div. 1)", 1_000_000)).contains("flagged:true"));
+        assert!(!script(&contest(3, "Codeforces Round 1000 (Div. 2)", 1_000_000)).contains("flagged"));
+    }
+
+    #[test]
+    fn parses_one_reminder_id_per_line() {
+        let output = "x-apple-reminder://0A1B2C3D\n x-apple-reminder://4E5F \n\n";
+
+        assert_eq!(parse_reminder_ids(output), ["x-apple-reminder://0A1B2C3D", "x-apple-reminder://4E5F"]);
+        assert!(parse_reminder_ids("\n").is_empty());
+    }
+
+    #[test]
+    fn selects_saved_ids_or_name_suffix() {
+        let saved = Contest { reminder_ids: vec!["x-apple-reminder://A".to_string(), "x-apple-reminder://B".to_string()], ..contest(7, "Round", 0) };
+
+        assert_eq!(reminder_selector(&saved), r#"(every reminder whose id is "x-apple-reminder://A" or id is "x-apple-reminder://B")"#);
+        assert_eq!(reminder_selector(&contest(7, "Round", 0)), r#"(every reminder whose name ends with ", id: 7")"#);
+    }
+
+    #[test]
+    fn apple_script_date_out_of_range_is_current_date() {
+        assert_eq!(apple_script_date("dueDate", i64::MAX), "set dueDate to current date");
+    }
+
+    fn at(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    fn local(day: u32, hour: u32, minute: u32) -> i64 {
+        Local.with_ymd_and_hms(2025, 4, day, hour, minute, 0).unwrap().timestamp()
+    }
+
+    #[test]
+    fn due_times_in_quiet_hours_move_to_their_end() {
+        assert_eq!(shift_out_of_quiet_hours(local(25, 2, 0), at(1, 0), at(7, 0)), Some(local(25, 7, 0)));
+        // The window includes its start, but not its end.
+        assert_eq!(shift_out_of_quiet_hours(local(25, 1, 0), at(1, 0), at(7, 0)), Some(local(25, 7, 0)));
+        assert_eq!(shift_out_of_quiet_hours(local(25, 7, 0), at(1, 0), at(7, 0)), None);
+        assert_eq!(shift_out_of_quiet_hours(local(25, 12, 0), at(1, 0), at(7, 0)), None);
+    }
+
+    #[test]
+    fn quiet_hours_across_midnight_end_next_day() {
+        assert_eq!(shift_out_of_quiet_hours(local(25, 23, 30), at(23, 0), at(7, 0)), Some(local(26, 7, 0)));
+        assert_eq!(shift_out_of_quiet_hours(local(26, 3, 0), at(23, 0), at(7, 0)), Some(local(26, 7, 0)));
+        assert_eq!(shift_out_of_quiet_hours(local(25, 22, 59), at(23, 0), at(7, 0)), None);
+        assert_eq!(shift_out_of_quiet_hours(local(25, 3, 0), at(7, 0), at(7, 0)), None, "an empty window is never quiet");
+    }
+
+    #[test]
+    fn due_time_never_moves_past_the_start() {
+        let config = || Config {
+            quiet_hours: Some(crate::config::QuietHours { start: "23:00".to_string(), end: "07:00".to_string() }),
+            ..Config::default()
+        };
+        let early = contest(1, "Round", local(26, 8, 0));
+        let night = contest(2, "Night Round", local(26, 5, 0));
+
+        assert_eq!(with_config(config(), || due_time(&early, local(26, 8, 0), 7200)), local(26, 7, 0));
+        assert_eq!(with_config(config(), || due_time(&night, local(26, 5, 0), 1800)), local(26, 4, 30));
+        assert_eq!(with_config(Config::default(), || due_time(&early, local(26, 8, 0), 7200)), local(26, 6, 0));
+    }
+
+    #[test]
+    fn apple_script_date_sets_every_component() {
+        let seconds = Local.with_ymd_and_hms(2025, 4, 25, 16, 35, 0).unwrap().timestamp();
+
+        let script = apple_script_date("dueDate", seconds);
+
+        let lines: Vec<&str> = script.lines().map(str::trim).collect();
+        assert_eq!(lines, [
+            "set dueDate to current date",
+            "set day of dueDate to 1",
+            "set year of dueDate to 2025",
+            "set month of dueDate to 4",
+            "set day of dueDate to 25",
+            "set hours of dueDate to 16",
+            "set minutes of dueDate to 35",
+            "set seconds of dueDate to 0",
+        ]);
+    }
+
+    #[test]
+    fn registration_due_buffer_before_start() {
+        let config = Config { registration_buffer_seconds: Some(300), ..Config::default() };
+
+        let due = with_config(config, || registration_due(&contest(2101, "Codeforces Round 1021 (Div. 2)", 1745850900)));
+
+        assert_eq!(due, Some(1745850600));
+    }
+
+    #[test]
+    fn registration_due_only_for_matching_contests() {
+        let config = Config {
+            registration_buffer_seconds: Some(300),
+            registration_patterns: vec!["Div.".to_string()],
+            ..Config::default()
+        };
+
+        with_config(config, || {
+            assert_eq!(registration_due(&contest(2101, "Codeforces Round 1021 (Div. 2)", 3600)), Some(3300));
+            assert_eq!(registration_due(&contest(2102, "Kotlin Heroes: Practice", 3600)), None);
+            assert_eq!(registration_due(&Contest { start_time_seconds: None, ..contest(2103, "Round (Div. 1)", 0) }), None);
+        });
+    }
+
+    #[test]
+    fn no_registration_reminder_without_buffer() {
+        assert_eq!(with_config(Config::default(), || registration_due(&contest(2101, "Round (Div. 2)", 3600))), None);
+    }
+
+    #[test]
+    fn detects_denied_automation() {
+        assert!(is_not_authorized("execution error: Not authorized to send Apple events to Reminders. (-1743)"));
+        assert!(is_not_authorized("execution error: Reminders got an error: (-1743)"));
+        assert!(!is_not_authorized("execution error: Reminders got an error: Can't get list \"Contests\". (-1728)"));
+        assert!(!is_not_authorized(""));
+    }
+
+    #[test]
+    fn description_is_plain_text() {
+        let described = Contest { description: Some("Rated for all".to_string()), ..contest(2101, "Round", 0) };
+
+        with_config(Config::default(), || {
+            assert_eq!(body_field("description", &described, 0).as_deref(), Some("Rated for all"));
+            assert_eq!(body_field("description", &contest(2102, "Round", 0), 0), None);
+        });
+    }
+
+    #[test]
+    fn debug_description_keeps_the_option() {
+        let described = Contest { description: Some("Rated for all".to_string()), ..contest(2101, "Round", 0) };
+        let config = Config { debug_description: true, ..Config::default() };
+
+        with_config(config, || {
+            assert_eq!(body_field("description", &described, 0).as_deref(), Some(r#"Some("Rated for all")"#));
+            assert_eq!(body_field("description", &contest(2102, "Round", 0), 0).as_deref(), Some("None"));
+        });
+    }
+
+    #[test]
+    fn retries_only_transient_failures() {
+        assert!(is_transient("execution error: Reminders got an error: Application isn't running. (-600)"));
+        assert!(is_transient("execution error: Connection is invalid. (-609)"));
+        assert!(!is_transient("execution error: Not authorized to send Apple events to Reminders. (-1743)"));
+        assert!(!is_transient("execution error: Not authorized to send Apple events to Reminders. (-600)"));
+        assert!(!is_transient("execution error: Reminders got an error: AppleEvent timed out. (-1712)"));
+        assert!(!is_transient("syntax error: Expected end of line. (-2741)"));
+    }
+
+    #[test]
+    fn first_matching_list_rule_wins() {
+        let config: Config = serde_json::from_value(serde_json::json!({
+            "default_list": "Contests",
+            "reminder_lists": [
+                {"pattern": "Div. 1", "list": "Hard"},
+                {"type": "ICPC", "pattern": "Div. 4", "list": "Easy ICPC"},
+                {"pattern": "Div. 4", "list": "Easy"}
+            ]
+        })).unwrap();
+        let typed = |id, name, contest_type: &str| Contest { contest_type: Some(contest_type.to_string()), ..contest(id, name, 0) };
+
+        with_config(config, || {
+            assert_eq!(reminder_list(&contest(1, "Codeforces Round 1000 (Div. 1)", 0)), Some("Hard"));
+            assert_eq!(reminder_list(&typed(2, "Codeforces Round 1001 (Div. 4)", "icpc")), Some("Easy ICPC"));
+            assert_eq!(reminder_list(&typed(3, "Codeforces Round 1002 (Div. 4)", "CF")), Some("Easy"));
+            assert_eq!(reminder_list(&contest(4, "Codeforces Round 1003 (Div. 2)", 0)), Some("Contests"));
+        });
+    }
+
+    #[test]
+    fn no_list_rule_uses_the_default_list_of_the_app() {
+        assert_eq!(with_config(Config::default(), || reminder_list(&contest(1, "Round (Div. 1)", 0))), None);
+    }
+
+    #[test]
+    fn body_follows_body_fields() {
+        let typed = Contest { contest_type: Some("ICPC".to_string()), ..contest(2101, "Round", 0) };
+        let fields = |fields: &[&str]| Config { body_fields: fields.iter().map(|field| field.to_string()).collect(), ..Config::default() };
+
+        let body = with_config(fields(&["type", "unknown", "url", "duration"]), || reminder_body(&typed));
+
+        assert_eq!(body, format!("Type: ICPC\n{}\nDuration: 2 hours", typed.url()));
+        assert_eq!(with_config(fields(&["url", "type"]), || reminder_body(&typed)), format!("{}\nType: ICPC", typed.url()));
+    }
+
+    #[test]
+    fn fields_without_value_are_left_out() {
+        let config = Config { body_fields: vec!["type".to_string(), "description".to_string(), "url".to_string()], ..Config::default() };
+        let untyped = Contest { contest_type: None, ..contest(2101, "Round", 0) };
+
+        assert_eq!(with_config(config, || reminder_body(&untyped)), untyped.url());
+    }
+}
@@ -0,0 +1,62 @@
+use crate::api::CodeforcesClient;
+use crate::contest::Contest;
+use crate::local::{log_error, log_warn};
+
+/// Fetches the Codeforces page of the contest and extracts a description.
+///
+/// Returns None and logs the failure if the page can not be retrieved
+/// or contains nothing usable.
+pub fn fetch_contest_description(contest: &Contest) -> Option<String> {
+    let url = contest.url();
+
+    let html = match CodeforcesClient::from_config().get(&url).and_then(|response| response.error_for_status()?.text()) {
+        Ok(html) => html,
+        Err(e) => {
+            log_error(&format!("Could not retrieve contest page {}. {}", url, e));
+            return None;
+        }
+    };
+
+    let description = extract_description(&html);
+    if description.is_none() {
+        log_warn(&format!("No announcement found on contest page {}", url));
+    }
+    description
+}
+
+/// Extracts the announcement link of a contest page.
+///
+/// Codeforces announces rounds in blog entries, which are
+/// linked from the contest page as /blog/entry/{id}.
+pub fn extract_description(html: &str) -> Option<String> {
+    const BLOG_LINK: &str = "href=\"/blog/entry/";
+
+    let start = html.find(BLOG_LINK)? + BLOG_LINK.len();
+    let entry: String = html[start..]
+        .chars()
+        .take_while(|c| c.is_ascii_digit())
+        .collect();
+
+    if entry.is_empty() { return None }
+
+    Some(format!("Announcement: https://codeforces.com/blog/entry/{}", entry))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_announcement_link() {
+        let html = r#"<div class="sidebox"><div class="caption">Announcement</div>
+            <a href="/blog/entry/138187" title="Codeforces Round 1000">Announcement</a></div>"#;
+
+        assert_eq!(extract_description(html).as_deref(), Some("Announcement: https://codeforces.com/blog/entry/138187"));
+    }
+
+    #[test]
+    fn pages_without_blog_entry_have_no_description() {
+        assert_eq!(extract_description(r#"<a href="/contests">Contests</a>"#), None);
+        assert_eq!(extract_description(r#"<a href="/blog/entry/">Broken</a>"#), None);
+    }
+}
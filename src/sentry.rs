@@ -0,0 +1,121 @@
+use crate::config::config;
+use crate::contest::Contest;
+use crate::local::{log_error, log_warn};
+use crate::notify::http_client;
+
+use serde_json::json;
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+
+static DSN: OnceLock<Dsn> = OnceLock::new();
+
+/// Number of events sent by this run, part of their ids.
+static EVENT_COUNT: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+    /// Id, name and link of the contest being synced, see contest_context.
+    static CONTEXT: RefCell<Option<(usize, String, String)>> = const { RefCell::new(None) };
+}
+
+/// Parts of a Sentry DSN, https://<public key>@<host>[/<path>]/<project id>.
+struct Dsn {
+    public_key: String,
+    envelope_url: String,
+    dsn: String,
+}
+
+/// Attaches the contest to the errors captured while the returned guard lives.
+pub struct ContestContext {
+    previous: Option<(usize, String, String)>,
+}
+
+impl Drop for ContestContext {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        CONTEXT.with(|context| *context.borrow_mut() = previous);
+    }
+}
+
+/// Sets the contest errors are captured with until the guard is dropped.
+pub fn contest_context(contest: &Contest) -> ContestContext {
+    let current = Some((contest.id, contest.name.clone(), contest.url()));
+    ContestContext { previous: CONTEXT.with(|context| context.replace(current)) }
+}
+
+/// Captures every error logged from now on as Sentry event, if sentry_dsn
+/// is set in the config.
+///
+/// Terminates and logs errors if the DSN can not be parsed.
+pub fn init() {
+    let Some(dsn) = &config().sentry_dsn else { return };
+
+    let Some(parsed) = parse_dsn(dsn) else {
+        log_error(&format!("Invalid sentry_dsn {}, expected https://<key>@<host>/<project id>.", dsn));
+        std::process::exit(1);
+    };
+    let _ = DSN.set(parsed);
+    crate::local::on_error(capture);
+}
+
+fn parse_dsn(dsn: &str) -> Option<Dsn> {
+    let (scheme, rest) = dsn.split_once("://")?;
+    let (key, location) = rest.split_once('@')?;
+    let (path, project) = location.trim_end_matches('/').rsplit_once('/')?;
+
+    // The secret after the colon of old DSNs is no longer needed.
+    let public_key = key.split(':').next()?;
+    if public_key.is_empty() || project.is_empty() { return None }
+
+    Some(Dsn {
+        public_key: public_key.to_string(),
+        envelope_url: format!("{}://{}/api/{}/envelope/", scheme, path, project),
+        dsn: dsn.to_string(),
+    })
+}
+
+/// Sends an error as event, with the contest of contest_context as tag
+/// and extra data. Failing to send it is logged as warning.
+fn capture(message: &str) {
+    let Some(dsn) = DSN.get() else { return };
+
+    let now = chrono::Utc::now();
+    let event_id = format!("{:016x}{:08x}{:08x}",
+        now.timestamp_nanos_opt().unwrap_or_default(), std::process::id(), EVENT_COUNT.fetch_add(1, Ordering::Relaxed));
+
+    let mut event = json!({
+        "event_id": event_id,
+        "timestamp": now.timestamp(),
+        "platform": "other",
+        "level": "error",
+        "logger": "codeforces-reminder",
+        "release": concat!("codeforces-reminder@", env!("CARGO_PKG_VERSION")),
+        "message": { "formatted": message },
+    });
+    CONTEXT.with(|context| {
+        if let Some((id, name, url)) = &*context.borrow() {
+            event["tags"] = json!({ "contest_id": id.to_string() });
+            event["extra"] = json!({ "contest_name": name, "contest_url": url });
+        }
+    });
+
+    // An envelope is a header line followed by items, each with its own header line.
+    let envelope = format!("{}\n{}\n{}",
+        json!({ "event_id": event_id, "dsn": dsn.dsn }),
+        json!({ "type": "event" }),
+        event);
+    let auth = format!("Sentry sentry_version=7, sentry_key={}, sentry_client=codeforces-reminder/{}",
+        dsn.public_key, env!("CARGO_PKG_VERSION"));
+
+    let request = http_client()
+        .post(&dsn.envelope_url)
+        .header("X-Sentry-Auth", auth)
+        .header("Content-Type", "application/x-sentry-envelope")
+        .body(envelope);
+
+    match request.send() {
+        Ok(response) if response.status().is_success() => {}
+        Ok(response) => log_warn(&format!("Sentry rejected error event with {}.", response.status())),
+        Err(e) => log_warn(&format!("Failed to send error event to Sentry. {}", e.without_url())),
+    }
+}
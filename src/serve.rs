@@ -0,0 +1,123 @@
+use crate::contest::Contest;
+use crate::ics::format_ics_with_alarms;
+use crate::local::{log_warn, read_local_upcoming_contests};
+use crate::shutdown;
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::Duration;
+
+/// Path of the feed.
+const FEED_PATH: &str = "/contests.ics";
+
+/// How long accepting waits before checking for an interruption again.
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// How long a client may take to send its request.
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Serves the saved contests at FEED_PATH as calendar with alarms, read
+/// from contests.json for every request, until SIGINT or SIGTERM.
+///
+/// Runs of the sync keep the feed up to date. Failing requests are
+/// logged as warnings, only failing to listen is returned.
+pub fn serve(address: &str) -> io::Result<()> {
+    let listener = TcpListener::bind(address)?;
+    // The signal handler only sets a flag, so accept must not block forever.
+    listener.set_nonblocking(true)?;
+    println!("Serving http://{}{}", listener.local_addr()?, FEED_PATH);
+
+    while !shutdown::interrupted() {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                if let Err(e) = handle(stream) {
+                    log_warn(&format!("Failed to answer feed request. {}", e));
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => std::thread::sleep(POLL_INTERVAL),
+            Err(e) => log_warn(&format!("Failed to accept feed connection. {}", e)),
+        }
+    }
+    Ok(())
+}
+
+/// Answers GET and HEAD of FEED_PATH with the calendar, everything else
+/// with an error status.
+fn handle(mut stream: TcpStream) -> io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+
+    let mut request_line = String::new();
+    BufReader::new(&stream).read_line(&mut request_line)?;
+
+    let mut parts = request_line.split_whitespace();
+    let (method, target) = (parts.next().unwrap_or_default(), parts.next().unwrap_or_default());
+    // Calendar apps add query strings, e.g. to bust caches.
+    let path = target.split('?').next().unwrap_or_default();
+
+    let (status, content_type, body) = match (method, path) {
+        ("GET" | "HEAD", FEED_PATH) => match read_local_upcoming_contests() {
+            Ok(contests) => {
+                let contests: Vec<Contest> = contests.into_iter().collect();
+                ("200 OK", "text/calendar; charset=utf-8", format_ics_with_alarms(&contests))
+            }
+            Err(e) => {
+                log_warn(&format!("Failed to read local contests for the feed. {}", e));
+                ("500 Internal Server Error", "text/plain", "Failed to read contests.\n".to_string())
+            }
+        },
+        ("GET" | "HEAD", _) => ("404 Not Found", "text/plain", format!("The feed is at {}.\n", FEED_PATH)),
+        _ => ("405 Method Not Allowed", "text/plain", "Only GET and HEAD are supported.\n".to_string()),
+    };
+
+    write!(stream, "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status, content_type, body.len())?;
+    if method != "HEAD" {
+        stream.write_all(body.as_bytes())?;
+    }
+    stream.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    /// Sends the raw request to handle and returns the raw response.
+    fn request(raw: &str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        client.write_all(raw.as_bytes()).unwrap();
+
+        let (server, _) = listener.accept().unwrap();
+        handle(server).unwrap();
+
+        let mut response = String::new();
+        client.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn other_paths_point_to_the_feed() {
+        let response = request("GET /calendar.ics HTTP/1.1\r\nHost: localhost\r\n\r\n");
+
+        assert!(response.starts_with("HTTP/1.1 404 Not Found\r\n"), "{}", response);
+        assert!(response.ends_with("\r\n\r\nThe feed is at /contests.ics.\n"), "{}", response);
+    }
+
+    #[test]
+    fn head_has_no_body() {
+        let response = request("HEAD /calendar.ics HTTP/1.1\r\n\r\n");
+
+        assert!(response.starts_with("HTTP/1.1 404 Not Found\r\n"), "{}", response);
+        assert!(response.contains("Content-Length: 30\r\n"), "{}", response);
+        assert!(response.ends_with("\r\n\r\n"), "{}", response);
+    }
+
+    #[test]
+    fn only_get_and_head_are_allowed() {
+        let response = request("POST /contests.ics HTTP/1.1\r\nContent-Length: 0\r\n\r\n");
+
+        assert!(response.starts_with("HTTP/1.1 405 Method Not Allowed\r\n"), "{}", response);
+    }
+}
@@ -0,0 +1,65 @@
+use crate::contest::Contest;
+use crate::local::{log_error, save_contests_locally};
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Exit code used after an interruption (128 + SIGINT).
+const INTERRUPTED_EXIT_CODE: i32 = 130;
+
+/// Installs a handler for SIGINT and SIGTERM, only for the sync and
+/// --serve, other commands keep terminating right away.
+///
+/// The first signal is only recorded, the sync loops check interrupted()
+/// between contests so that a reminder is never left half created.
+/// A second signal terminates right away, e.g. during a slow fetch.
+pub fn install_handler() {
+    let handler = || {
+        if record_signal(&INTERRUPTED) {
+            std::process::exit(INTERRUPTED_EXIT_CODE);
+        }
+    };
+    if let Err(e) = ctrlc::set_handler(handler) {
+        log_error(&format!("Failed to install signal handler. {}", e));
+    }
+}
+
+/// Records a signal in interrupted, returning true if one was received before.
+fn record_signal(interrupted: &AtomicBool) -> bool {
+    interrupted.swap(true, Ordering::SeqCst)
+}
+
+/// Returns true once SIGINT or SIGTERM was received.
+pub fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+/// Saves the contests handled so far and terminates.
+///
+/// Called instead of processing the next contest after an interruption,
+/// so the cache matches the reminders that were actually created.
+pub fn save_and_exit(contests: &Vec<Contest>) -> ! {
+    log_error(&format!("Interrupted, saving {} contests before exiting.", contests.len()));
+
+    if let Err(e) = save_contests_locally(contests) {
+        log_error(&format!("Failed to save local contests atomically. Error: {}", e));
+    }
+
+    std::process::exit(INTERRUPTED_EXIT_CODE);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn second_signal_terminates() {
+        // Not INTERRUPTED, other tests run sync loops checking it.
+        let interrupted = AtomicBool::new(false);
+
+        assert!(!record_signal(&interrupted), "the first signal only interrupts the sync");
+        assert!(interrupted.load(Ordering::SeqCst));
+        assert!(record_signal(&interrupted), "the second signal terminates");
+    }
+}
@@ -0,0 +1,211 @@
+use crate::api::{source_result, CodeforcesClient};
+use crate::atcoder::{AtCoder, ATCODER};
+use crate::clist::{Clist, CLIST};
+use crate::codechef::{CodeChef, CODECHEF};
+use crate::config::config;
+use crate::contest::{Contest, ContestResponse, CODEFORCES_API, CODEFORCES_GYM};
+use crate::feed::Feed;
+use crate::leetcode::{LeetCode, LEETCODE};
+use crate::local::log_error;
+
+use regex::Regex;
+use reqwest::blocking::Response;
+
+/// Where contests are fetched from, e.g. the Codeforces API or AtCoder.
+///
+/// The contests of all sources are merged by api::fetch_contests_from.
+pub trait ContestSource {
+    /// Identifies the source in sources, last_fetch.json and
+    /// Contest::source, e.g. "https://atcoder.jp".
+    fn url(&self) -> &str;
+
+    /// Requests the contest list, sending the ETag of the last
+    /// response if the source answers 304 Not Modified.
+    fn request(&self, client: &CodeforcesClient, etag: Option<&str>) -> reqwest::Result<Response>;
+
+    /// Returns the contests of a response body.
+    fn parse(&self, body: &str) -> Result<Vec<Contest>, String>;
+}
+
+/// API answering contest.list like Codeforces, e.g. an adapter for another judge.
+pub struct CodeforcesApi {
+    base_url: String,
+}
+
+impl CodeforcesApi {
+    pub fn new(base_url: &str) -> Self {
+        CodeforcesApi { base_url: base_url.to_string() }
+    }
+}
+
+impl ContestSource for CodeforcesApi {
+    fn url(&self) -> &str {
+        &self.base_url
+    }
+
+    fn request(&self, client: &CodeforcesClient, etag: Option<&str>) -> reqwest::Result<Response> {
+        let url = format!("{}/contest.list?gym=false", self.base_url.trim_end_matches('/'));
+        client.get_if_none_match(&url, etag)
+    }
+
+    fn parse(&self, body: &str) -> Result<Vec<Contest>, String> {
+        let response: ContestResponse = serde_json::from_str(body)
+            .map_err(|e| format!("Could not parse online contest JSON. {}", e))?;
+        source_result(response)
+    }
+}
+
+/// Gym contests of the Codeforces API matching gym_patterns in the config.
+pub struct Gym;
+
+impl ContestSource for Gym {
+    fn url(&self) -> &str {
+        CODEFORCES_GYM
+    }
+
+    fn request(&self, client: &CodeforcesClient, etag: Option<&str>) -> reqwest::Result<Response> {
+        client.get_if_none_match(&format!("{}/contest.list?gym=true", CODEFORCES_API), etag)
+    }
+
+    fn parse(&self, body: &str) -> Result<Vec<Contest>, String> {
+        Ok(matching_gyms(CodeforcesApi::new(CODEFORCES_API).parse(body)?))
+    }
+}
+
+/// Sources from the config, or the Codeforces API if there are none,
+/// the gyms, LeetCode and clist.by if they are enabled, and the ICS feeds.
+pub fn configured() -> Vec<Box<dyn ContestSource>> {
+    let mut urls = if config().sources.is_empty() {
+        vec![CODEFORCES_API.to_string()]
+    } else {
+        config().sources.clone()
+    };
+    if config().include_gym && !urls.iter().any(|url| url == CODEFORCES_GYM) {
+        urls.push(CODEFORCES_GYM.to_string());
+    }
+    if config().leetcode.enabled && !urls.iter().any(|url| url == LEETCODE) {
+        urls.push(LEETCODE.to_string());
+    }
+    if config().clist.is_some() && !urls.iter().any(|url| url == CLIST) {
+        urls.push(CLIST.to_string());
+    }
+    for feed in &config().ics_sources {
+        if !urls.contains(feed) {
+            urls.push(feed.clone());
+        }
+    }
+
+    urls.iter().map(|url| source(url)).collect()
+}
+
+/// Source listed as url in sources. AtCoder, CodeChef, LeetCode,
+/// clist.by and ICS feeds have their own, every other url is taken
+/// as API answering contest.list like Codeforces.
+fn source(url: &str) -> Box<dyn ContestSource> {
+    if config().ics_sources.iter().any(|feed| feed == url) {
+        return Box::new(Feed::new(url))
+    }
+
+    match (url, &config().clist) {
+        (ATCODER, _) => Box::new(AtCoder),
+        (CODECHEF, _) => Box::new(CodeChef),
+        (LEETCODE, _) => Box::new(LeetCode::new(&config().leetcode)),
+        (CLIST, Some(clist)) => Box::new(Clist::new(clist)),
+        (CODEFORCES_GYM, _) => Box::new(Gym),
+        _ => Box::new(CodeforcesApi::new(url)),
+    }
+}
+
+/// Keeps the gyms whose name matches one of gym_patterns in the config,
+/// all of them if there are none, linking their gym pages.
+///
+/// Invalid patterns are logged and skipped, if all are invalid no gym matches.
+fn matching_gyms(gyms: Vec<Contest>) -> Vec<Contest> {
+    let match_all = config().gym_patterns.is_empty();
+    let patterns: Vec<Regex> = config().gym_patterns
+        .iter()
+        .filter_map(|pattern| match Regex::new(pattern) {
+            Ok(regex) => Some(regex),
+            Err(e) => {
+                log_error(&format!("Skipping invalid gym pattern {}. {}", pattern, e));
+                None
+            }
+        })
+        .collect();
+
+    gyms.into_iter()
+        .filter(|gym| match_all || patterns.iter().any(|pattern| pattern.is_match(&gym.name)))
+        .map(|gym| Contest { page_url: Some(format!("https://codeforces.com/gym/{}", gym.id)), ..gym })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::tests::with_config;
+    use crate::config::Config;
+    use crate::contest::tests::contest;
+
+    fn gyms() -> Vec<Contest> {
+        vec![contest(105800, "2025 ICPC Mashup", 0), contest(105801, "Team Training #3", 0)]
+    }
+
+    fn names(contests: &[Contest]) -> Vec<&str> {
+        contests.iter().map(|contest| contest.name.as_str()).collect()
+    }
+
+    #[test]
+    fn gyms_matching_a_pattern_link_their_page() {
+        let config = Config { gym_patterns: vec!["ICPC".to_string()], ..Config::default() };
+
+        let gyms = with_config(config, || matching_gyms(gyms()));
+
+        assert_eq!(names(&gyms), ["2025 ICPC Mashup"]);
+        assert_eq!(gyms[0].page_url.as_deref(), Some("https://codeforces.com/gym/105800"));
+    }
+
+    #[test]
+    fn no_gym_patterns_match_all_gyms() {
+        assert_eq!(names(&with_config(Config::default(), || matching_gyms(gyms()))), ["2025 ICPC Mashup", "Team Training #3"]);
+    }
+
+    #[test]
+    fn only_invalid_gym_patterns_match_no_gym() {
+        let config = Config { gym_patterns: vec!["(ICPC".to_string()], ..Config::default() };
+
+        assert!(with_config(config, || matching_gyms(gyms())).is_empty());
+    }
+
+    #[test]
+    fn include_gym_adds_the_gym_source() {
+        let urls = |config| with_config(config, || configured().iter().map(|source| source.url().to_string()).collect::<Vec<_>>());
+
+        assert_eq!(urls(Config { include_gym: true, ..Config::default() }), [CODEFORCES_API, CODEFORCES_GYM]);
+        assert_eq!(urls(Config::default()), [CODEFORCES_API]);
+    }
+
+    #[test]
+    fn configured_sources_keep_their_order_without_duplicates() {
+        let config = Config {
+            sources: vec![ATCODER.to_string(), "https://example.org/api".to_string(), LEETCODE.to_string()],
+            leetcode: crate::config::LeetCodeConfig { enabled: true, ..Default::default() },
+            ics_sources: vec!["webcal://example.org/training.ics".to_string()],
+            ..Config::default()
+        };
+
+        let urls = with_config(config, || configured().iter().map(|source| source.url().to_string()).collect::<Vec<_>>());
+
+        assert_eq!(urls, [ATCODER, "https://example.org/api", LEETCODE, "webcal://example.org/training.ics"]);
+    }
+
+    #[test]
+    fn codeforces_compatible_api_parses_contest_list() {
+        let api = CodeforcesApi::new("https://example.org/api");
+        let body = r#"{"status": "OK", "result": [{"id": 1, "name": "Mirror Round", "phase": "BEFORE"}]}"#;
+
+        assert_eq!(api.url(), "https://example.org/api");
+        assert_eq!(api.parse(body).unwrap()[0].name, "Mirror Round");
+        assert!(api.parse(r#"{"status": "FAILED", "comment": "down", "result": []}"#).is_err());
+        assert!(api.parse("not json").unwrap_err().starts_with("Could not parse online contest JSON."));
+    }
+}
@@ -0,0 +1,125 @@
+use crate::archive::load_archive;
+use crate::contest::{format_duration, next_contest, Contest};
+use crate::local::fetch_local_upcoming_contests;
+use crate::paths::history_path;
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::fs;
+
+/// Prints the stats of the saved, archived and historical contests.
+///
+/// Only reads files, an unreadable archive or history is reported in the output.
+pub fn stats_command() {
+    let upcoming: Vec<Contest> = fetch_local_upcoming_contests().into_iter().collect();
+
+    let archived = load_archive().unwrap_or_else(|e| {
+        println!("Could not read archive: {}", e);
+        Vec::new()
+    });
+
+    let history = fs::read_to_string(history_path()).unwrap_or_default();
+
+    print!("{}", format_stats(&upcoming, &archived, &history, chrono::Utc::now().timestamp()));
+}
+
+/// Formats the number of upcoming and archived contests, both by type,
+/// the actions in the history and the countdown to the next contest.
+fn format_stats(upcoming: &[Contest], archived: &[Contest], history: &str, now: i64) -> String {
+    let mut stats = String::new();
+
+    let _ = writeln!(stats, "Upcoming contests: {}", upcoming.len());
+    write_by_type(&mut stats, upcoming);
+
+    let _ = writeln!(stats, "Archived contests: {}", archived.len());
+    write_by_type(&mut stats, archived);
+
+    let actions = count_actions(history);
+    if !actions.is_empty() {
+        let _ = writeln!(stats, "History:");
+        for (action, count) in actions {
+            let _ = writeln!(stats, "  {}: {}", action, count);
+        }
+    }
+
+    match next_contest(upcoming, now) {
+        Some(contest) => {
+            let start = contest.start_time_seconds.unwrap_or(now);
+            let _ = writeln!(stats, "Next: {}, id: {} in {}", contest.name, contest.id, format_duration(start - now));
+        }
+        None => {
+            let _ = writeln!(stats, "Next: no upcoming contests");
+        }
+    }
+
+    stats
+}
+
+/// Writes how many of the contests have each type, "unknown" if they have none.
+fn write_by_type(stats: &mut String, contests: &[Contest]) {
+    let mut by_type: BTreeMap<&str, usize> = BTreeMap::new();
+    for contest in contests {
+        *by_type.entry(contest.contest_type.as_deref().unwrap_or("unknown")).or_default() += 1;
+    }
+
+    for (contest_type, count) in by_type {
+        let _ = writeln!(stats, "  {}: {}", contest_type, count);
+    }
+}
+
+/// Counts the lines of history.jsonl by action, skipping unreadable lines.
+fn count_actions(history: &str) -> BTreeMap<String, usize> {
+    let mut actions = BTreeMap::new();
+
+    for line in history.lines() {
+        let action = serde_json::from_str::<serde_json::Value>(line)
+            .ok()
+            .and_then(|entry| entry.get("action").and_then(|action| action.as_str().map(str::to_string)));
+
+        if let Some(action) = action {
+            *actions.entry(action).or_default() += 1;
+        }
+    }
+
+    actions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contest::tests::contest;
+
+    #[test]
+    fn counts_saved_archived_and_history() {
+        let typed = |id, start, contest_type: Option<&str>| Contest {
+            contest_type: contest_type.map(str::to_string),
+            ..contest(id, "Round", start)
+        };
+        let upcoming = [typed(2102, 10_800, Some("CF")), typed(2101, 4_600, Some("ICPC")), typed(2103, 20_000, Some("CF"))];
+        let archived = [typed(2099, 0, Some("CF")), typed(2100, 0, None)];
+        let history = concat!(
+            r#"{"timestamp": "2025-04-25T10:00:00+02:00", "action": "created", "id": 2101, "name": "Round"}"#, "\n",
+            r#"{"timestamp": "2025-04-25T10:00:00+02:00", "action": "created", "id": 2102, "name": "Round"}"#, "\n",
+            "not json\n",
+            r#"{"timestamp": "2025-04-26T10:00:00+02:00", "action": "deleted", "id": 2099, "name": "Round"}"#, "\n",
+        );
+
+        assert_eq!(format_stats(&upcoming, &archived, history, 1_000), "\
+Upcoming contests: 3
+  CF: 2
+  ICPC: 1
+Archived contests: 2
+  CF: 1
+  unknown: 1
+History:
+  created: 2
+  deleted: 1
+Next: Round, id: 2101 in 1 hour
+");
+    }
+
+    #[test]
+    fn nothing_saved() {
+        assert_eq!(format_stats(&[], &[], "", 0), "Upcoming contests: 0\nArchived contests: 0\nNext: no upcoming contests\n");
+    }
+}
@@ -0,0 +1,118 @@
+use crate::local::{log_error, write_atomically};
+use crate::paths::status_path;
+
+use serde::{Serialize, Deserialize};
+use std::fs;
+use std::time::{Duration, Instant};
+
+/// Weight of the latest run in the moving averages.
+const EMA_ALPHA: f64 = 0.2;
+
+/// Information about past runs saved in status.json.
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct Status {
+    /// Local time the last run finished in RFC 3339.
+    pub last_run: Option<String>,
+    /// Time spent fetching the contest list in the last run.
+    pub last_fetch_ms: u64,
+    /// Time spent creating, updating and deleting reminders in the last run.
+    pub last_reminders_ms: u64,
+    /// Exponential moving average of last_fetch_ms.
+    pub fetch_ms_ema: Option<f64>,
+    /// Exponential moving average of last_reminders_ms.
+    pub reminders_ms_ema: Option<f64>,
+    /// Highest id of the Codeforces contests saved so far.
+    pub max_contest_id: Option<usize>,
+    /// Reminders created by all runs.
+    pub reminders_created_total: u64,
+}
+
+/// Runs f and adds the time it took to total.
+pub fn timed<T>(total: &mut Duration, f: impl FnOnce() -> T) -> T {
+    let start = Instant::now();
+    let result = f();
+    *total += start.elapsed();
+    result
+}
+
+/// Updates status.json with the timings of this run, the highest contest id
+/// it saved and the number of reminders it created, returning the new status.
+///
+/// Failing to read or save the status is logged, but never aborts the run.
+pub fn record_timings(fetch: Duration, reminders: Duration, max_contest_id: Option<usize>, created: usize) -> Status {
+    let mut status = load_status();
+
+    status.max_contest_id = status.max_contest_id.max(max_contest_id);
+    status.reminders_created_total += created as u64;
+    status.last_run = Some(chrono::Local::now().to_rfc3339());
+    status.last_fetch_ms = fetch.as_millis() as u64;
+    status.last_reminders_ms = reminders.as_millis() as u64;
+    status.fetch_ms_ema = Some(ema(status.fetch_ms_ema, status.last_fetch_ms as f64));
+    status.reminders_ms_ema = Some(ema(status.reminders_ms_ema, status.last_reminders_ms as f64));
+
+    let result = serde_json::to_string_pretty(&status)
+        .map_err(std::io::Error::from)
+        .and_then(|json| write_atomically(&status_path(), &json));
+
+    if let Err(e) = result {
+        log_error(&format!("Failed to save status file. {}", e));
+    }
+
+    status
+}
+
+/// Returns when the last run finished in seconds (Unix epoch),
+/// None before the first run.
+pub fn last_run_seconds() -> Option<i64> {
+    let last_run = load_status().last_run?;
+    chrono::DateTime::parse_from_rfc3339(&last_run).ok().map(|time| time.timestamp())
+}
+
+/// Returns the highest contest id saved by earlier runs.
+pub fn high_water_mark() -> Option<usize> {
+    load_status().max_contest_id
+}
+
+/// Exponential moving average, starting at the first sample.
+fn ema(previous: Option<f64>, sample: f64) -> f64 {
+    match previous {
+        Some(average) => EMA_ALPHA * sample + (1.0 - EMA_ALPHA) * average,
+        None => sample,
+    }
+}
+
+/// Deserializes status.json, or returns an empty status if it
+/// does not exist or can not be parsed.
+fn load_status() -> Status {
+    let path = status_path();
+
+    if !path.exists() { return Status::default() }
+
+    fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|contents| serde_json::from_str(&contents).map_err(|e| e.to_string()))
+        .unwrap_or_else(|e| {
+            log_error(&format!("Failed to read status file, starting over. {}", e));
+            Status::default()
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ema_starts_at_first_sample() {
+        assert_eq!(ema(None, 120.0), 120.0);
+    }
+
+    #[test]
+    fn ema_moves_towards_samples() {
+        let average = ema(Some(100.0), 200.0);
+
+        assert_eq!(average, EMA_ALPHA * 200.0 + (1.0 - EMA_ALPHA) * 100.0);
+        assert!(100.0 < average && average < 200.0);
+        assert_eq!(ema(Some(100.0), 100.0), 100.0);
+    }
+}
@@ -0,0 +1,95 @@
+use crate::contest::Contest;
+use crate::diff::ContestDiff;
+use crate::reminder::{create_summary_reminder, delete_summary_reminder, CommandRunner};
+use crate::status::timed;
+
+use chrono::{Local, NaiveDate, TimeZone};
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::Duration;
+
+/// Groups contests by the local calendar day they start on.
+///
+/// Contests without a start time belong to no day.
+pub fn group_by_day<'a>(contests: impl IntoIterator<Item = &'a Contest>) -> BTreeMap<NaiveDate, Vec<&'a Contest>> {
+    let mut days: BTreeMap<NaiveDate, Vec<&Contest>> = BTreeMap::new();
+
+    for contest in contests {
+        if let Some(day) = start_day(contest) {
+            days.entry(day).or_default().push(contest);
+        }
+    }
+
+    for contests in days.values_mut() {
+        contests.sort_by_key(|contest| (contest.start_time_seconds, contest.id));
+    }
+
+    days
+}
+
+/// Replaces the per-contest reminders by one summary reminder per day.
+///
+/// The summary of every day with a new or changed contest is recreated
+/// from the contests on that day, days without contests get no reminder.
+/// Finished contests leave their summary alone, it is already due.
+/// Returns the contests to save locally.
+pub fn sync_daily_summaries(runner: &dyn CommandRunner, diff: ContestDiff, reminders_time: &mut Duration) -> Vec<Contest> {
+    let mut affected: BTreeSet<NaiveDate> = BTreeSet::new();
+    affected.extend(diff.new.iter().filter_map(start_day));
+    for (local, upcoming) in &diff.changed {
+        affected.extend(start_day(local));
+        affected.extend(start_day(upcoming));
+    }
+
+    let mut contests = diff.unchanged;
+    contests.extend(diff.changed.into_iter().map(|(local, upcoming)| local.updated_from(upcoming)));
+    contests.extend(diff.new);
+
+    let days = group_by_day(&contests);
+
+    for day in affected {
+        timed(reminders_time, || {
+            if delete_summary_reminder(runner, day) {
+                if let Some(contests) = days.get(&day) {
+                    create_summary_reminder(runner, day, contests);
+                }
+            }
+        });
+    }
+
+    contests
+}
+
+/// Local calendar day the contest starts on.
+fn start_day(contest: &Contest) -> Option<NaiveDate> {
+    let start = contest.start_time_seconds?;
+    Some(Local.timestamp_opt(start, 0).single()?.date_naive())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contest::tests::contest;
+
+    fn local(year: i32, month: u32, day: u32, hour: u32) -> i64 {
+        Local.with_ymd_and_hms(year, month, day, hour, 0, 0).unwrap().timestamp()
+    }
+
+    #[test]
+    fn groups_contests_by_local_start_day() {
+        let contests = [
+            contest(3, "Evening", local(2025, 4, 25, 20)),
+            contest(1, "Morning", local(2025, 4, 25, 8)),
+            contest(2, "Next day", local(2025, 4, 26, 8)),
+            Contest { start_time_seconds: None, ..contest(4, "Unknown start", 0) },
+        ];
+
+        let days = group_by_day(&contests);
+
+        let day = |d| NaiveDate::from_ymd_opt(2025, 4, d).unwrap();
+        let names: Vec<(NaiveDate, Vec<&str>)> = days
+            .iter()
+            .map(|(day, contests)| (*day, contests.iter().map(|contest| contest.name.as_str()).collect()))
+            .collect();
+        assert_eq!(names, [(day(25), vec!["Morning", "Evening"]), (day(26), vec!["Next day"])]);
+    }
+}
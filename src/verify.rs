@@ -0,0 +1,141 @@
+use crate::contest::Contest;
+use crate::paths::contest_path_read_only;
+
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+
+/// Fields every saved contest needs.
+const REQUIRED_FIELDS: [&str; 3] = ["id", "name", "phase"];
+
+/// Start times before Codeforces existed (01/01/2010) are bogus.
+const EARLIEST_START_SECONDS: i64 = 1_262_304_000;
+
+/// Checks contests.json without modifying or creating it and prints every
+/// issue followed by an OK or ISSUES summary, or that nothing is saved yet.
+///
+/// Terminates with exit code 1 if there are issues.
+pub fn verify_cache_command() {
+    let path = match contest_path_read_only() {
+        Ok(path) => path,
+        Err(e) => {
+            println!("ISSUES: could not locate contests.json: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let issues = match fs::read_to_string(&path) {
+        Ok(contents) => verify_cache(&contents),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            println!("Nothing saved: {} does not exist yet.", path.display());
+            return;
+        }
+        Err(e) => vec![format!("Could not read {}: {}", path.display(), e)],
+    };
+
+    for issue in &issues {
+        println!("{}", issue);
+    }
+
+    if issues.is_empty() {
+        println!("OK: {} is valid.", path.display());
+    } else {
+        println!("ISSUES: {} found in {}.", issues.len(), path.display());
+        std::process::exit(1);
+    }
+}
+
+/// Returns a description of every problem found in the contents of contests.json.
+pub fn verify_cache(contents: &str) -> Vec<String> {
+    let entries = match serde_json::from_str::<Value>(contents) {
+        Ok(Value::Array(entries)) => entries,
+        Ok(_) => return vec!["contests.json is not a JSON array.".to_string()],
+        Err(e) => return vec![format!("contests.json is not valid JSON: {}", e)],
+    };
+
+    let mut issues = Vec::new();
+    let mut first_index_of: HashMap<(Option<String>, u64), usize> = HashMap::new();
+
+    for (index, entry) in entries.iter().enumerate() {
+        let prefix = format!("Entry {}", index);
+
+        let Some(object) = entry.as_object() else {
+            issues.push(format!("{}: not a JSON object.", prefix));
+            continue;
+        };
+
+        let missing: Vec<&str> = REQUIRED_FIELDS
+            .into_iter()
+            .filter(|field| !object.contains_key(*field))
+            .collect();
+        if !missing.is_empty() {
+            issues.push(format!("{}: missing {}.", prefix, missing.join(", ")));
+            continue;
+        }
+
+        if let Err(e) = serde_json::from_value::<Contest>(entry.clone()) {
+            issues.push(format!("{}: invalid contest: {}.", prefix, e));
+            continue;
+        }
+
+        if let Some(start) = object.get("startTimeSeconds").and_then(Value::as_i64) {
+            if start < EARLIEST_START_SECONDS {
+                issues.push(format!("{}: bogus start time {}.", prefix, start));
+            }
+        }
+
+        if let Some(id) = object.get("id").and_then(Value::as_u64) {
+            let source = object.get("source").and_then(Value::as_str).map(str::to_string);
+            if let Some(first) = first_index_of.get(&(source.clone(), id)) {
+                issues.push(format!("{}: duplicate id {} (first in entry {}).", prefix, id, first));
+            } else {
+                first_index_of.insert((source, id), index);
+            }
+        }
+    }
+
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_cache_has_no_issues() {
+        let cache = r#"[
+            {"id": 2100, "name": "Round", "phase": "BEFORE", "startTimeSeconds": 1745591700},
+            {"id": 2100, "name": "AtCoder", "phase": "BEFORE", "source": "https://atcoder.jp"}
+        ]"#;
+
+        assert!(verify_cache(cache).is_empty());
+        assert!(verify_cache("[]").is_empty());
+    }
+
+    #[test]
+    fn reports_every_invalid_entry() {
+        let cache = r#"[
+            {"id": 1, "name": "Fine", "phase": "BEFORE"},
+            {"id": 2, "name": "No phase"},
+            42,
+            {"id": 3, "name": "Wrong phase type", "phase": 7},
+            {"id": 4, "name": "From 1970", "phase": "BEFORE", "startTimeSeconds": 0},
+            {"id": 1, "name": "Twice", "phase": "BEFORE"}
+        ]"#;
+
+        let issues = verify_cache(cache);
+
+        assert_eq!(issues.len(), 5, "{:?}", issues);
+        assert_eq!(issues[0], "Entry 1: missing phase.");
+        assert_eq!(issues[1], "Entry 2: not a JSON object.");
+        assert!(issues[2].starts_with("Entry 3: invalid contest: "));
+        assert_eq!(issues[3], "Entry 4: bogus start time 0.");
+        assert_eq!(issues[4], "Entry 5: duplicate id 1 (first in entry 0).");
+    }
+
+    #[test]
+    fn rejects_other_json() {
+        assert_eq!(verify_cache(r#"{"id": 1}"#), ["contests.json is not a JSON array."]);
+        assert!(verify_cache("[{").first().is_some_and(|issue| issue.starts_with("contests.json is not valid JSON: ")));
+    }
+}